@@ -0,0 +1,58 @@
+//! Counts the allocations stepping a board makes, comparing fresh maps each step with the ones
+//! Life reuses. The counting allocator lives here rather than in the app, so only this run pays
+//! for it: `cargo run --release --example allocations`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+use rust_game_of_life::life::{process_cells, Life};
+use rust_game_of_life::patterns::gosper_glider_gun;
+
+const STEPS: u64 = 10_000;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn main() {
+    println!("Stepping a Gosper glider gun {STEPS} generations, fresh and reused maps");
+    let gun = Life::from_cells(gosper_glider_gun().into_iter().collect());
+    let count_allocations = |name: &str, run: &mut dyn FnMut()| {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        let start = Instant::now();
+        run();
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+        let per_step = allocations as f64 / STEPS as f64;
+        println!("{name:>11}: {elapsed:.1} ms, {allocations} allocations, {per_step:.2} per step");
+    };
+
+    let mut fresh = gun.cells().clone();
+    count_allocations("fresh", &mut || {
+        for _ in 0..STEPS {
+            fresh = process_cells(&fresh, gun.rule(), None);
+        }
+    });
+    let mut reused = gun.clone();
+    count_allocations("reused", &mut || reused.advance(STEPS));
+    println!("Results identical: {}", &fresh == reused.cells());
+}
@@ -0,0 +1,261 @@
+use glam::I64Vec2;
+use itertools::Itertools;
+use crate::selection::CellRect;
+
+/// The largest brush radius, which paints a 65x65 square.
+pub const MAX_BRUSH_RADIUS: i64 = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrushShape {
+    Square,
+    Circle,
+}
+
+impl BrushShape {
+    pub fn name(self) -> &'static str {
+        match self {
+            BrushShape::Square => "square",
+            BrushShape::Circle => "circle",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            BrushShape::Square => BrushShape::Circle,
+            BrushShape::Circle => BrushShape::Square,
+        }
+    }
+}
+
+/// The cells painted around the hovered cell. A radius of 0 paints just that cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Brush {
+    pub radius: i64,
+    pub shape: BrushShape,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self { radius: 0, shape: BrushShape::Square }
+    }
+}
+
+impl Brush {
+    pub fn grow(&mut self) {
+        self.radius = (self.radius + 1).min(MAX_BRUSH_RADIUS);
+    }
+
+    pub fn shrink(&mut self) {
+        self.radius = (self.radius - 1).max(0);
+    }
+
+    /// Every cell the brush covers when centred on `centre`, in row order.
+    pub fn footprint(&self, centre: I64Vec2) -> Vec<I64Vec2> {
+        let radius = self.radius;
+        (-radius..=radius)
+            .flat_map(|y| (-radius..=radius).map(move |x| I64Vec2::new(x, y)))
+            .filter(|offset| match self.shape {
+                BrushShape::Square => true,
+                // Rounds the edge out by half a cell so small circles aren't diamonds.
+                BrushShape::Circle => offset.length_squared() <= radius * radius + radius,
+            })
+            .map(|offset| centre.saturating_add(offset))
+            .collect()
+    }
+}
+
+/// A shape drawn between two clicked cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShapeTool {
+    Line,
+    Rectangle,
+    FilledRectangle,
+}
+
+impl ShapeTool {
+    pub fn name(self) -> &'static str {
+        match self {
+            ShapeTool::Line => "line",
+            ShapeTool::Rectangle => "rectangle",
+            ShapeTool::FilledRectangle => "filled rectangle",
+        }
+    }
+
+    /// The cells of the shape from `start` to `end`, which are opposite corners for rectangles.
+    pub fn cells(self, start: I64Vec2, end: I64Vec2) -> Vec<I64Vec2> {
+        let rect = CellRect::from_corners(start, end);
+        let rows = rect.min.y..=rect.max.y;
+        match self {
+            ShapeTool::Line => line(start, end),
+            ShapeTool::Rectangle => rows
+                .flat_map(|y| {
+                    let is_edge = y == rect.min.y || y == rect.max.y;
+                    let columns: Vec<i64> = if is_edge {
+                        (rect.min.x..=rect.max.x).collect()
+                    } else {
+                        vec![rect.min.x, rect.max.x]
+                    };
+                    columns.into_iter().map(move |x| I64Vec2::new(x, y))
+                })
+                .dedup()
+                .collect(),
+            ShapeTool::FilledRectangle => {
+                rows.flat_map(|y| (rect.min.x..=rect.max.x).map(move |x| I64Vec2::new(x, y))).collect()
+            }
+        }
+    }
+}
+
+/// Mirrors painted cells about a centre cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symmetry {
+    /// Mirrored left to right, across the vertical line through the centre.
+    Horizontal,
+    /// Mirrored top to bottom, across the horizontal line through the centre.
+    Vertical,
+    /// Mirrored both ways, for four copies of every cell.
+    FourFold,
+}
+
+impl Symmetry {
+    pub fn name(self) -> &'static str {
+        match self {
+            Symmetry::Horizontal => "horizontal",
+            Symmetry::Vertical => "vertical",
+            Symmetry::FourFold => "4-fold",
+        }
+    }
+
+    /// Steps through the symmetries and back to none.
+    pub fn next(symmetry: Option<Self>) -> Option<Self> {
+        match symmetry {
+            None => Some(Symmetry::Horizontal),
+            Some(Symmetry::Horizontal) => Some(Symmetry::Vertical),
+            Some(Symmetry::Vertical) => Some(Symmetry::FourFold),
+            Some(Symmetry::FourFold) => None,
+        }
+    }
+
+    /// Whether cells are flipped in x and in y.
+    fn flips(self) -> (bool, bool) {
+        match self {
+            Symmetry::Horizontal => (true, false),
+            Symmetry::Vertical => (false, true),
+            Symmetry::FourFold => (true, true),
+        }
+    }
+
+    /// The cells together with their mirror images about `centre`, without duplicates.
+    pub fn apply(self, cells: &[I64Vec2], centre: I64Vec2) -> Vec<I64Vec2> {
+        let (flip_x, flip_y) = self.flips();
+        let mirror = |value: i64, centre: i64| centre.saturating_mul(2).saturating_sub(value);
+        cells
+            .iter()
+            .flat_map(|&cell| {
+                let x = [Some(cell.x), flip_x.then(|| mirror(cell.x, centre.x))];
+                let y = [Some(cell.y), flip_y.then(|| mirror(cell.y, centre.y))];
+                x.into_iter().flatten().cartesian_product(y.into_iter().flatten()).map(|(x, y)| I64Vec2::new(x, y))
+            })
+            .unique()
+            .collect()
+    }
+}
+
+/// The cells on the straight line from `from` to `to`, both included, by Bresenham's
+/// algorithm. Consecutive cells touch, so a fast drag painted through it leaves no gaps.
+pub fn line(from: I64Vec2, to: I64Vec2) -> Vec<I64Vec2> {
+    let delta = (to - from).abs();
+    let step = (to - from).signum();
+    let mut error = delta.x - delta.y;
+    let mut cell = from;
+    let mut cells = vec![cell];
+    while cell != to {
+        let doubled = 2 * error;
+        if doubled > -delta.y {
+            error -= delta.y;
+            cell.x += step.x;
+        }
+        if doubled < delta.x {
+            error += delta.x;
+            cell.y += step.y;
+        }
+        cells.push(cell);
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_brush_paints_one_cell() {
+        let centre = I64Vec2::new(-5, 9);
+        assert_eq!(Brush::default().footprint(centre), [centre]);
+        assert_eq!(Brush { radius: 0, shape: BrushShape::Circle }.footprint(centre), [centre]);
+    }
+
+    #[test]
+    fn footprints_by_shape() {
+        let square = Brush { radius: 2, shape: BrushShape::Square };
+        assert_eq!(square.footprint(I64Vec2::ZERO).len(), 25);
+
+        let circle = Brush { shape: BrushShape::Circle, ..square }.footprint(I64Vec2::new(10, 10));
+        assert_eq!(circle.len(), 21);
+        assert!(circle.contains(&I64Vec2::new(12, 10)) && circle.contains(&I64Vec2::new(11, 11)));
+        assert!(!circle.contains(&I64Vec2::new(12, 12)));
+    }
+
+    #[test]
+    fn lines_are_gapless() {
+        assert_eq!(line(I64Vec2::ONE, I64Vec2::ONE), [I64Vec2::ONE]);
+        let row: Vec<I64Vec2> = (0..4).map(|x| I64Vec2::new(x, 0)).collect();
+        assert_eq!(line(I64Vec2::ZERO, I64Vec2::new(3, 0)), row);
+
+        for to in [I64Vec2::new(7, 3), I64Vec2::new(-2, 9), I64Vec2::new(-6, -6), I64Vec2::new(5, -1)] {
+            let cells = line(I64Vec2::ZERO, to);
+            assert_eq!(cells.len() as i64, to.abs().max_element() + 1, "{to}");
+            assert_eq!(cells.last(), Some(&to));
+            assert!(cells.windows(2).all(|pair| (pair[1] - pair[0]).abs().max_element() == 1), "{to}");
+        }
+    }
+
+    #[test]
+    fn rectangles_span_either_corner_order() {
+        let (start, end) = (I64Vec2::new(3, 2), I64Vec2::new(0, 0));
+        let filled = ShapeTool::FilledRectangle.cells(start, end);
+        assert_eq!(filled.len(), 12);
+        assert_eq!(filled.first(), Some(&end));
+
+        let outline = ShapeTool::Rectangle.cells(start, end);
+        assert_eq!(outline.len(), 10);
+        assert!(!outline.contains(&I64Vec2::new(1, 1)));
+        assert_eq!(ShapeTool::Rectangle.cells(start, start), [start]);
+        assert_eq!(ShapeTool::Rectangle.cells(I64Vec2::ZERO, I64Vec2::new(0, 2)).len(), 3);
+    }
+
+    #[test]
+    fn mirrors_about_the_centre() {
+        let centre = I64Vec2::new(10, -5);
+        let cell = I64Vec2::new(12, -4);
+        assert_eq!(Symmetry::Horizontal.apply(&[cell], centre), [cell, I64Vec2::new(8, -4)]);
+        assert_eq!(Symmetry::Vertical.apply(&[cell], centre), [cell, I64Vec2::new(12, -6)]);
+
+        let four = Symmetry::FourFold.apply(&[cell], centre);
+        assert_eq!(four.len(), 4);
+        assert!(four.contains(&I64Vec2::new(8, -6)));
+        // Cells on an axis are their own reflection.
+        assert_eq!(Symmetry::FourFold.apply(&[centre, I64Vec2::new(10, 0)], centre).len(), 3);
+    }
+
+    #[test]
+    fn radius_stays_in_range() {
+        let mut brush = Brush::default();
+        brush.shrink();
+        assert_eq!(brush.radius, 0);
+        for _ in 0..100 {
+            brush.grow();
+        }
+        assert_eq!(brush.radius, MAX_BRUSH_RADIUS);
+    }
+}
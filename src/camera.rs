@@ -0,0 +1,316 @@
+use glam::{I64Vec2, IVec2};
+
+/// Zoom limits in pixels per cell.
+pub const MIN_CELL_SIZE: i32 = 2;
+pub const MAX_CELL_SIZE: i32 = 100;
+pub const DEFAULT_CELL_SIZE: i32 = 12;
+
+/// How far the mouse has to move with a button down before it counts as a drag rather than a
+/// click, unless the config says otherwise. It is a squared distance in pixels, so a press only
+/// drags once the mouse is more than about 2.2 pixels away.
+pub const DRAG_THRESHOLD: i32 = 5;
+/// The largest squared drag threshold, 10 pixels away.
+pub const MAX_DRAG_THRESHOLD: i32 = 100;
+
+/// Limits of the multiplier on how far a drag pans, below 1 for finer panning.
+pub const MIN_PAN_SENSITIVITY: f64 = 0.25;
+pub const MAX_PAN_SENSITIVITY: f64 = 4.0;
+
+/// How many camera bookmarks can be saved, one per digit key.
+pub const BOOKMARK_SLOTS: usize = 9;
+
+/// A saved view to jump back to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bookmark {
+    pub origin: I64Vec2,
+    pub cell_size: i32,
+}
+
+/// How much of the remaining way a smooth zoom covers each frame.
+pub const ZOOM_EASING: f32 = 0.35;
+
+/// A wheel zoom easing towards its target cell size over a few frames, about the pixel the
+/// cursor was on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmoothZoom {
+    /// The cell size so far, kept fractional so small steps add up.
+    pub size: f32,
+    pub target: f32,
+    pub pixel: IVec2,
+}
+
+impl SmoothZoom {
+    /// Starts a zoom in or out by one wheel step from `cell_size`, or adds the step to the
+    /// target of a zoom still under way. Each step changes the size by at least a pixel.
+    pub fn step(zoom: Option<SmoothZoom>, cell_size: i32, is_zooming_in: bool, pixel: IVec2) -> SmoothZoom {
+        let (size, target) = zoom.map_or((cell_size as f32, cell_size as f32), |zoom| (zoom.size, zoom.target));
+        let target = if is_zooming_in {
+            (target * 1.1).max(target.round() + 1.0)
+        } else {
+            (target * 0.9).min(target.round() - 1.0)
+        };
+        SmoothZoom { size, target: target.clamp(MIN_CELL_SIZE as f32, MAX_CELL_SIZE as f32), pixel }
+    }
+
+    /// Moves a frame's worth towards the target and returns the whole cell size to draw at,
+    /// or `None` once it has arrived.
+    pub fn advance(&mut self) -> Option<i32> {
+        self.size += (self.target - self.size) * ZOOM_EASING;
+        if (self.target - self.size).abs() < 0.05 {
+            self.size = self.target;
+        }
+        (self.size != self.target).then_some(self.size.round() as i32)
+    }
+}
+
+/// Returns the origin that puts `cell` in the middle of a `screen_size` pixel view.
+///
+/// The origin is the offset from world to screen cells, so a cell is drawn at
+/// `(cell + origin) * cell_size`.
+pub fn centre_on(cell: I64Vec2, screen_size: IVec2, cell_size: i32) -> I64Vec2 {
+    (screen_size / cell_size / 2).as_i64vec2().saturating_sub(cell)
+}
+
+/// Returns the world cell in the middle of the view, the inverse of [`centre_on`].
+pub fn centre_cell(origin: I64Vec2, screen_size: IVec2, cell_size: i32) -> I64Vec2 {
+    (screen_size / cell_size / 2).as_i64vec2().saturating_sub(origin)
+}
+
+/// Returns the world cell under a screen pixel. Rounds down, so pixels left of or above the
+/// view land on the cells there rather than being pulled towards the screen's corner.
+pub fn cell_at(pixel: IVec2, origin: I64Vec2, cell_size: i32) -> I64Vec2 {
+    pixel.div_euclid(IVec2::splat(cell_size)).as_i64vec2().saturating_sub(origin)
+}
+
+/// Returns the origin for zooming to `new_cell_size` that keeps the cell under `pixel` where
+/// it is. Works out that cell with [`cell_at`]'s rounding down, so the view doesn't creep
+/// over repeated zooms, even left of or above the world origin.
+pub fn zoom_at(pixel: IVec2, origin: I64Vec2, cell_size: i32, new_cell_size: i32) -> I64Vec2 {
+    let cell = cell_at(pixel, origin, cell_size);
+    pixel.div_euclid(IVec2::splat(new_cell_size)).as_i64vec2().saturating_sub(cell)
+}
+
+/// How much of the way to its target a followed view moves each frame, as a divisor.
+pub const FOLLOW_EASING: i64 = 8;
+
+/// Moves `origin` part of the way to `target`, so a view following a pattern glides after
+/// it rather than jumping. Always moves at least a cell, so it settles exactly on the target.
+pub fn follow_step(origin: I64Vec2, target: I64Vec2) -> I64Vec2 {
+    let gap = target.saturating_sub(origin);
+    // Rounds away from zero, so the last few cells of the gap are still covered.
+    origin.saturating_add(gap / FOLLOW_EASING + (gap % FOLLOW_EASING).signum())
+}
+
+/// How many whole cells a drag of `pixels` moves the view, rounded to the nearest cell the
+/// same way in every direction. The drag is measured from where it started, so slow movement
+/// still adds up to a cell.
+pub fn drag_cells(pixels: IVec2, cell_size: i32) -> I64Vec2 {
+    (pixels + cell_size / 2).div_euclid(IVec2::splat(cell_size)).as_i64vec2()
+}
+
+/// Whether the mouse has moved `pixels` far enough from where it was pressed to be dragging,
+/// beyond the squared `threshold`.
+pub fn is_drag(pixels: IVec2, threshold: i32) -> bool {
+    pixels.length_squared() > threshold
+}
+
+/// Scales a drag of `pixels` by the pan sensitivity before it is turned into cells with
+/// [`drag_cells`], rounding halves away from zero so both directions match.
+pub fn scale_drag(pixels: IVec2, sensitivity: f64) -> IVec2 {
+    (pixels.as_dvec2() * sensitivity).round().as_ivec2()
+}
+
+/// Parses `x,y` coordinates, tolerating spaces, surrounding parentheses and a plain space in
+/// place of the comma.
+pub fn parse_cell(text: &str) -> Option<I64Vec2> {
+    let text = text.trim().trim_start_matches('(').trim_end_matches(')');
+    let mut parts = text.split(|ch: char| ch == ',' || ch.is_whitespace()).filter(|part| !part.is_empty());
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(x), Some(y), None) => Some(I64Vec2::new(x.parse().ok()?, y.parse().ok()?)),
+        _ => None,
+    }
+}
+
+/// Rounds `cell` down to the nearest corner of a grid `stride` cells apart, counted from the
+/// world origin, so pastes line up into regular arrays.
+pub fn snap_to_grid(cell: I64Vec2, stride: i64) -> I64Vec2 {
+    cell - cell.rem_euclid(I64Vec2::splat(stride))
+}
+
+/// How many cells a keyboard pan moves, so each press covers about `step_pixels` of the
+/// screen at any zoom, and always at least one cell.
+pub fn pan_step(step_pixels: i32, cell_size: i32) -> i64 {
+    (step_pixels / cell_size).max(1) as i64
+}
+
+/// How many pixels to leave blank around each drawn cell so neighbours stay apart. At most
+/// a quarter of the cell is given up, so cells under 4 pixels are drawn whole.
+pub fn cell_inset(inset: i32, cell_size: i32) -> i32 {
+    inset.min(cell_size / 4)
+}
+
+/// Returns the origin and cell size that fit the inclusive box `min..=max`, plus `margin`
+/// cells on every side, in the view. The zoom stays within the cell size limits, so very
+/// large boxes are centred without fitting entirely.
+pub fn fit_box(min: I64Vec2, max: I64Vec2, screen_size: IVec2, margin: i64) -> (I64Vec2, i32) {
+    let size = max - min + 1 + 2 * margin;
+    let fit = (screen_size.as_i64vec2() / size).min_element();
+    let cell_size = fit.clamp(MIN_CELL_SIZE as i64, MAX_CELL_SIZE as i64) as i32;
+    (centre_on(min + (max - min) / 2, screen_size, cell_size), cell_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: IVec2 = IVec2::new(800, 600);
+
+    #[test]
+    fn centres_a_cell() {
+        let origin = centre_on(I64Vec2::new(10, -4), SCREEN, 10);
+        assert_eq!((I64Vec2::new(10, -4) + origin) * 10, I64Vec2::new(400, 300));
+        assert_eq!(centre_cell(origin, SCREEN, 10), I64Vec2::new(10, -4));
+        assert_eq!(centre_on(I64Vec2::splat(i64::MIN), SCREEN, 10), I64Vec2::splat(i64::MAX));
+    }
+
+    #[test]
+    fn zooming_keeps_the_cell_under_the_cursor() {
+        for origin in [I64Vec2::ZERO, I64Vec2::new(37, -12), I64Vec2::new(-500, 9_000)] {
+            for pixel in [IVec2::new(0, 0), IVec2::new(413, 287), IVec2::new(799, 599)] {
+                let cell = cell_at(pixel, origin, 12);
+                let (mut origin, mut cell_size) = (origin, 12);
+                // In all the way and back out, as repeated wheel steps would.
+                for new_cell_size in (MIN_CELL_SIZE..=MAX_CELL_SIZE).chain((MIN_CELL_SIZE..MAX_CELL_SIZE).rev()) {
+                    origin = zoom_at(pixel, origin, cell_size, new_cell_size);
+                    cell_size = new_cell_size;
+                    assert_eq!(cell_at(pixel, origin, cell_size), cell, "{pixel} at {cell_size}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn following_glides_onto_the_target() {
+        let target = I64Vec2::new(100, -37);
+        let mut origin = I64Vec2::ZERO;
+        let mut gaps = Vec::new();
+        while origin != target {
+            origin = follow_step(origin, target);
+            gaps.push((target - origin).abs().max_element());
+        }
+        assert_eq!(gaps[..3], [87, 76, 66]);
+        assert!(gaps.is_sorted_by(|a, b| a > b) && gaps.len() < 40, "{gaps:?}");
+        assert_eq!(follow_step(target, target), target);
+    }
+
+    #[test]
+    fn smooth_zoom_eases_onto_the_target() {
+        let mut zoom = SmoothZoom::step(None, 12, true, IVec2::ZERO);
+        zoom = SmoothZoom::step(Some(zoom), 12, true, IVec2::ZERO);
+        assert_eq!(zoom.target, 12.0 * 1.1 * 1.1);
+        let sizes: Vec<i32> = std::iter::from_fn(|| zoom.advance()).collect();
+        assert!(sizes.len() < 20 && sizes.is_sorted(), "{sizes:?}");
+        assert_eq!(zoom.size, zoom.target);
+
+        // The smallest cells still change by a pixel, and the limits hold.
+        assert_eq!(SmoothZoom::step(None, MIN_CELL_SIZE, true, IVec2::ZERO).target, 3.0);
+        assert_eq!(SmoothZoom::step(None, MIN_CELL_SIZE, false, IVec2::ZERO).target, MIN_CELL_SIZE as f32);
+        assert_eq!(SmoothZoom::step(None, MAX_CELL_SIZE, true, IVec2::ZERO).target, MAX_CELL_SIZE as f32);
+    }
+
+    #[test]
+    fn drags_round_the_same_both_ways() {
+        assert_eq!(drag_cells(IVec2::new(4, -4), 10), I64Vec2::ZERO);
+        assert_eq!(drag_cells(IVec2::new(5, -6), 10), I64Vec2::new(1, -1));
+        assert_eq!(drag_cells(IVec2::new(-15, 24), 10), I64Vec2::new(-1, 2));
+        assert_eq!(drag_cells(IVec2::new(-16, 25), 10), I64Vec2::new(-2, 3));
+    }
+
+    #[test]
+    fn drags_start_beyond_the_threshold() {
+        assert!(!is_drag(IVec2::new(2, 1), DRAG_THRESHOLD));
+        assert!(!is_drag(IVec2::new(0, -2), DRAG_THRESHOLD));
+        assert!(is_drag(IVec2::new(-2, 2), DRAG_THRESHOLD));
+        assert!(is_drag(IVec2::new(3, 0), DRAG_THRESHOLD));
+        assert!(is_drag(IVec2::new(0, 1), 0));
+        assert!(!is_drag(IVec2::ZERO, 0));
+    }
+
+    #[test]
+    fn sensitivity_scales_drags() {
+        assert_eq!(scale_drag(IVec2::new(10, -7), 1.0), IVec2::new(10, -7));
+        assert_eq!(scale_drag(IVec2::new(10, -7), 0.5), IVec2::new(5, -4));
+        assert_eq!(scale_drag(IVec2::new(7, -7), 0.5), IVec2::new(4, -4));
+        assert_eq!(drag_cells(scale_drag(IVec2::new(36, 0), MIN_PAN_SENSITIVITY), 12), I64Vec2::new(1, 0));
+        assert_eq!(drag_cells(scale_drag(IVec2::new(36, 0), 2.0), 12), I64Vec2::new(6, 0));
+    }
+
+    #[test]
+    fn insets_shrink_with_the_cells() {
+        assert_eq!(cell_inset(1, 10), 1);
+        assert_eq!(cell_inset(3, 8), 2);
+        assert_eq!(cell_inset(1, 3), 0);
+        assert_eq!(cell_inset(0, 40), 0);
+    }
+
+    #[test]
+    fn clicks_land_on_the_cell_under_the_cursor() {
+        let origin = I64Vec2::new(30, 20);
+        assert_eq!(cell_at(IVec2::new(5, 5), origin, 10), I64Vec2::new(-30, -20));
+        assert_eq!(cell_at(IVec2::new(-1, 9), origin, 10), I64Vec2::new(-31, -20));
+        assert_eq!(cell_at(IVec2::new(-10, -11), origin, 10), I64Vec2::new(-31, -22));
+        assert_eq!(cell_at(IVec2::new(-1, 0), I64Vec2::ZERO, 12), I64Vec2::new(-1, 0));
+    }
+
+    #[test]
+    fn parses_coordinates_tolerantly() {
+        assert_eq!(parse_cell("12,-7"), Some(I64Vec2::new(12, -7)));
+        assert_eq!(parse_cell("  -3 ,  4 "), Some(I64Vec2::new(-3, 4)));
+        assert_eq!(parse_cell("(5, 6)"), Some(I64Vec2::new(5, 6)));
+        assert_eq!(parse_cell("100 200"), Some(I64Vec2::new(100, 200)));
+        assert_eq!(parse_cell("-9223372036854775808,0"), Some(I64Vec2::new(i64::MIN, 0)));
+
+        for malformed in ["", "1", "1,2,3", "a,b", "1.5,2", "--1,2", "99999999999999999999,0"] {
+            assert_eq!(parse_cell(malformed), None, "{malformed:?}");
+        }
+    }
+
+    #[test]
+    fn snaps_down_to_the_grid() {
+        assert_eq!(snap_to_grid(I64Vec2::new(9, 15), 8), I64Vec2::new(8, 8));
+        assert_eq!(snap_to_grid(I64Vec2::new(-1, -8), 8), I64Vec2::new(-8, -8));
+        assert_eq!(snap_to_grid(I64Vec2::new(-9, 7), 4), I64Vec2::new(-12, 4));
+        assert_eq!(snap_to_grid(I64Vec2::new(5, -3), 1), I64Vec2::new(5, -3));
+        assert_eq!(snap_to_grid(I64Vec2::splat(i64::MIN), 8), I64Vec2::splat(i64::MIN));
+    }
+
+    #[test]
+    fn pan_step_scales_with_zoom() {
+        assert_eq!(pan_step(48, 12), 4);
+        assert_eq!(pan_step(48, MIN_CELL_SIZE), 24);
+        assert_eq!(pan_step(48, MAX_CELL_SIZE), 1);
+        assert_eq!(pan_step(0, 12), 1);
+    }
+
+    #[test]
+    fn fits_box_with_margin() {
+        let (min, max) = (I64Vec2::new(-20, 5), I64Vec2::new(19, 24));
+        let (origin, cell_size) = fit_box(min, max, SCREEN, 2);
+        assert_eq!(cell_size, 18);
+
+        let top_left = (min + origin) * cell_size as i64;
+        let bottom_right = (max + 1 + origin) * cell_size as i64;
+        assert!(top_left.cmpge(I64Vec2::ZERO).all());
+        assert!(bottom_right.cmple(SCREEN.as_i64vec2()).all());
+    }
+
+    #[test]
+    fn fit_respects_zoom_limits() {
+        assert_eq!(fit_box(I64Vec2::ZERO, I64Vec2::ZERO, SCREEN, 0).1, MAX_CELL_SIZE);
+
+        let far = I64Vec2::splat(1 << 40);
+        let (origin, cell_size) = fit_box(-far, far, SCREEN, 2);
+        assert_eq!(cell_size, MIN_CELL_SIZE);
+        assert_eq!(origin, centre_on(I64Vec2::ZERO, SCREEN, MIN_CELL_SIZE));
+    }
+}
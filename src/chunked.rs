@@ -0,0 +1,215 @@
+use std::collections::{HashMap, HashSet};
+use glam::I64Vec2;
+use crate::life::table_bytes;
+use crate::rule::Rule;
+
+/// Width and height of a chunk in cells. Each chunk row is one `u64`.
+pub const CHUNK_SIZE: i64 = 64;
+
+/// A 64x64 block of cells, where bit `x` of row `y` is the cell at `(x, y)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Chunk {
+    rows: [u64; CHUNK_SIZE as usize],
+}
+
+impl Chunk {
+    const EMPTY: Chunk = Chunk { rows: [0; CHUNK_SIZE as usize] };
+
+    fn is_empty(&self) -> bool {
+        self.rows.iter().all(|&row| row == 0)
+    }
+
+    fn population(&self) -> u64 {
+        self.rows.iter().map(|row| row.count_ones() as u64).sum()
+    }
+}
+
+/// A board stored as bitset chunks keyed by chunk coordinate, which packs dense regions into
+/// one bit per cell instead of a hash table entry.
+///
+/// Each chunk is stepped a row at a time with bitwise arithmetic, reading a one-cell halo from
+/// its eight neighbouring chunks. Only two-state Moore rules are supported.
+#[derive(Debug, Clone)]
+pub struct ChunkedWorld {
+    chunks: HashMap<I64Vec2, Chunk>,
+    rule: Rule,
+}
+
+impl ChunkedWorld {
+    pub fn new(rule: Rule) -> Self {
+        Self { chunks: HashMap::new(), rule }
+    }
+
+    pub fn from_cells(cells: &HashSet<I64Vec2>, rule: Rule) -> Self {
+        let mut world = Self::new(rule);
+        for &cell in cells {
+            let (chunk, local) = split(cell);
+            world.chunks.entry(chunk).or_insert(Chunk::EMPTY).rows[local.y as usize] |= 1 << local.x;
+        }
+        world
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    pub fn population(&self) -> u64 {
+        self.chunks.values().map(Chunk::population).sum()
+    }
+
+    /// How many chunks hold at least one live cell.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Roughly how much memory the chunk table takes.
+    pub fn memory_bytes(&self) -> usize {
+        table_bytes(&self.chunks)
+    }
+
+    pub fn cells(&self) -> HashSet<I64Vec2> {
+        let mut cells = HashSet::with_capacity(self.population() as usize);
+        for (&chunk, bits) in &self.chunks {
+            for (y, &row) in bits.rows.iter().enumerate() {
+                let mut row = row;
+                while row != 0 {
+                    let x = row.trailing_zeros() as i64;
+                    cells.insert(chunk * CHUNK_SIZE + I64Vec2::new(x, y as i64));
+                    row &= row - 1;
+                }
+            }
+        }
+        cells
+    }
+
+    /// Advances the board by one generation.
+    pub fn step(&mut self) {
+        // Births can spill one cell into a neighbouring chunk, so those are stepped too.
+        let candidates: HashSet<I64Vec2> = self
+            .chunks
+            .keys()
+            .flat_map(|&chunk| (-1..=1).flat_map(move |y| (-1..=1).map(move |x| chunk + I64Vec2::new(x, y))))
+            .collect();
+
+        self.chunks = candidates
+            .into_iter()
+            .filter_map(|chunk| {
+                let next = self.step_chunk(chunk);
+                (!next.is_empty()).then_some((chunk, next))
+            })
+            .collect();
+    }
+
+    fn step_chunk(&self, chunk: I64Vec2) -> Chunk {
+        let neighbourhood: [[&Chunk; 3]; 3] = [-1, 0, 1].map(|y| {
+            [-1, 0, 1].map(|x| self.chunks.get(&(chunk + I64Vec2::new(x, y))).unwrap_or(&Chunk::EMPTY))
+        });
+        // Row `y` of the chunk, widened with the cells either side of it: bit 0 is the cell at
+        // x = -1 and bit 65 the cell at x = 64.
+        let halo_row = |y: i64| -> u128 {
+            let (band, local) = match y {
+                -1 => (0, CHUNK_SIZE - 1),
+                CHUNK_SIZE => (2, 0),
+                _ => (1, y),
+            };
+            let [west, centre, east] = neighbourhood[band].map(|chunk| chunk.rows[local as usize]);
+            (west >> 63) as u128 | (centre as u128) << 1 | ((east & 1) as u128) << 65
+        };
+
+        let mut next = Chunk::EMPTY;
+        let (mut above, mut middle) = (halo_row(-1), halo_row(0));
+        for y in 0..CHUNK_SIZE {
+            let below = halo_row(y + 1);
+            let neighbours = [
+                above as u64,
+                (above >> 1) as u64,
+                (above >> 2) as u64,
+                middle as u64,
+                (middle >> 2) as u64,
+                below as u64,
+                (below >> 1) as u64,
+                (below >> 2) as u64,
+            ];
+            next.rows[y as usize] = next_row((middle >> 1) as u64, neighbours, &self.rule);
+            (above, middle) = (middle, below);
+        }
+        next
+    }
+}
+
+/// Splits a world cell into its chunk coordinate and its position within that chunk.
+fn split(cell: I64Vec2) -> (I64Vec2, I64Vec2) {
+    (cell.div_euclid(I64Vec2::splat(CHUNK_SIZE)), cell.rem_euclid(I64Vec2::splat(CHUNK_SIZE)))
+}
+
+/// Applies the rule to 64 cells at once, given the row and the eight rows of its neighbours
+/// lined up bit for bit.
+fn next_row(alive: u64, neighbours: [u64; 8], rule: &Rule) -> u64 {
+    // Bit-sliced counters: bit x of plane i is bit i of cell x's neighbour count.
+    let mut planes = [0u64; 4];
+    for neighbour in neighbours {
+        let mut carry = neighbour;
+        for plane in &mut planes {
+            let overflow = *plane & carry;
+            *plane ^= carry;
+            carry = overflow;
+        }
+    }
+
+    (0..=8).fold(0, |next, count| {
+        let has_count = planes
+            .iter()
+            .enumerate()
+            .fold(u64::MAX, |mask, (bit, &plane)| mask & if count >> bit & 1 == 1 { plane } else { !plane });
+        let survives = if rule.survival[count] { alive } else { 0 };
+        let born = if rule.birth[count] { !alive } else { 0 };
+        next | has_count & (survives | born)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::Life;
+    use crate::patterns::{glider, random_soup};
+    use crate::rng::Rng;
+
+    fn naive(cells: &HashSet<I64Vec2>, rule: Rule, generations: u64) -> HashSet<I64Vec2> {
+        let mut life = Life::from_cells(cells.clone());
+        life.set_rule(rule);
+        life.advance(generations);
+        life.live_cells()
+    }
+
+    #[test]
+    fn round_trips_cells() {
+        let soup = random_soup(&mut Rng::seed_from_u64(5), I64Vec2::splat(-100), I64Vec2::splat(100), 0.3);
+        let world = ChunkedWorld::from_cells(&soup, Rule::CONWAY);
+        assert_eq!(world.cells(), soup);
+        assert_eq!(world.population(), soup.len() as u64);
+        assert_eq!(world.chunk_count(), 16);
+    }
+
+    #[test]
+    fn matches_naive_engine_across_chunk_borders() {
+        let soup = random_soup(&mut Rng::seed_from_u64(6), I64Vec2::new(-90, -40), I64Vec2::new(70, 90), 0.4);
+        for rule in [Rule::CONWAY, Rule::parse("B36/S23").unwrap(), Rule::parse("B2/S").unwrap()] {
+            let mut world = ChunkedWorld::from_cells(&soup, rule);
+            for generation in 1..=20 {
+                world.step();
+                assert_eq!(world.cells(), naive(&soup, rule, generation), "{rule} at generation {generation}");
+            }
+        }
+    }
+
+    #[test]
+    fn glider_crosses_into_empty_chunks() {
+        let start: HashSet<I64Vec2> = glider().into_iter().map(|cell| cell - I64Vec2::splat(4)).collect();
+        let mut world = ChunkedWorld::from_cells(&start, Rule::CONWAY);
+        for _ in 0..40 {
+            world.step();
+        }
+        assert_eq!(world.cells(), naive(&start, Rule::CONWAY, 40));
+        assert_eq!(world.chunk_count(), 1);
+    }
+}
@@ -0,0 +1,482 @@
+use std::path::PathBuf;
+use glam::I64Vec2;
+use crate::headless::DEFAULT_HEADLESS_STEPS;
+use crate::kernel::Weights;
+use crate::noise::Noise;
+use crate::pixel_art::{DEFAULT_EXPORT_SCALE, DEFAULT_IMAGE_THRESHOLD};
+use crate::rule::{Counts, Neighbourhood, Rule, MAX_RANGE};
+use crate::torus::{Edges, Torus};
+use crate::trails::DEFAULT_TRAIL_DURATION;
+
+pub const USAGE: &str = "Usage: rust-game-of-life [--load <file>] [--image <file>] [--image-threshold <0-255>] \
+    [--rule <B3/S23>] [--range <1-7>] [--birth <min..max>] [--survival <min..max>] [--kernel <weights>] \
+    [--history-depth <n>] [--cycle-window <n>] [--seed <n>] [--torus-size <WxH>] [--snap-stride <cells>] \
+    [--theme <file>] [--keys <file>] [--pan-step <pixels>] [--export-scale <pixels>] [--cell-inset <pixels>] \
+    [--trail-duration <seconds>] [--snapshot-edits] [--screenshot-ui] [--bench] [--headless] [--steps <n>] [--csv] \
+    [--birth-chance <0-1>] [--survival-chance <0-1>] [--noise-seed <n>] \
+    [--max-population <n>] [--frame-stride <n>] [--max-frames <n>] [--frames-board] [--diff <a> <b>] [<file>]";
+
+/// How many past generations can be stepped back through by default.
+pub const DEFAULT_HISTORY_DEPTH: usize = 100;
+
+/// The longest oscillator period looked for by default.
+pub const DEFAULT_CYCLE_WINDOW: usize = 64;
+
+/// Roughly how far one arrow key press pans, in pixels.
+pub const DEFAULT_PAN_STEP: i32 = 48;
+
+/// Running pauses once the population passes this, unless `--max-population` says otherwise.
+pub const DEFAULT_MAX_POPULATION: usize = 5_000_000;
+
+/// The most PNGs a frame sequence writes unless `--max-frames` says otherwise.
+pub const DEFAULT_MAX_FRAMES: usize = 1000;
+
+/// How far apart snapped pastes land unless `--snap-stride` says otherwise.
+pub const DEFAULT_SNAP_STRIDE: i64 = 8;
+
+/// The size of the wrapped or walled board unless `--torus-size` says otherwise.
+pub const DEFAULT_TORUS_SIZE: I64Vec2 = I64Vec2::new(100, 100);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Args {
+    pub load: Option<PathBuf>,
+    /// Two pattern files to compare, overlaying the cells only one of them has.
+    pub diff: Option<(PathBuf, PathBuf)>,
+    /// An image whose dark pixels are added to the board as live cells.
+    pub image: Option<PathBuf>,
+    /// Pixels darker than this brightness are live.
+    pub image_threshold: u8,
+    pub rule: Rule,
+    /// Chances for births and survivals to start with, if any of them were given.
+    pub noise: Option<Noise>,
+    pub history_depth: usize,
+    /// How many generations back to look for a repeated board.
+    pub cycle_window: usize,
+    /// Seed for the first random fill. Each later fill uses the next seed.
+    pub seed: Option<u64>,
+    /// The board to wrap onto when toroidal mode is switched on, or to wall in when walls are.
+    pub torus: Torus,
+    /// A theme file to start with instead of the light preset.
+    pub theme: Option<PathBuf>,
+    /// A key bindings file to read instead of `keys.json`.
+    pub keys: Option<PathBuf>,
+    /// Roughly how many pixels an arrow key press pans, whatever the zoom.
+    pub pan_step: i32,
+    /// How many cells apart snapped pastes land.
+    pub snap_stride: i64,
+    /// How many pixels wide each cell is in a board PNG export.
+    pub export_scale: i32,
+    /// Pixels left blank around each drawn cell so neighbouring cells stay apart.
+    pub cell_inset: i32,
+    /// The population that pauses a run with a warning, before the board outgrows memory.
+    pub max_population: usize,
+    /// How many seconds the trail of a cell that died takes to fade out.
+    pub trail_duration: f64,
+    /// How many generations apart the frames of a PNG sequence are.
+    pub frame_stride: u64,
+    /// The most frames a PNG sequence writes before it stops by itself.
+    pub max_frames: usize,
+    /// Whether a PNG sequence renders the whole board, as a board PNG export does, rather than
+    /// the view on screen.
+    pub frames_board: bool,
+    /// Whether edits made while paused after the first start are taken into the generation
+    /// zero snapshot when the simulation starts again.
+    pub snapshot_edits: bool,
+    /// Whether screenshots keep the UI text and cursor highlight.
+    pub screenshot_ui: bool,
+    pub bench: bool,
+    /// Runs the loaded pattern without a window and prints where it ended up.
+    pub headless: bool,
+    /// Whether a headless run prints a CSV row per generation instead of a summary at the end.
+    pub csv: bool,
+    /// The most generations a headless run steps.
+    pub steps: u64,
+}
+
+impl Default for Args {
+    fn default() -> Self {
+        Self {
+            load: None,
+            diff: None,
+            image: None,
+            image_threshold: DEFAULT_IMAGE_THRESHOLD,
+            rule: Rule::default(),
+            noise: None,
+            history_depth: DEFAULT_HISTORY_DEPTH,
+            cycle_window: DEFAULT_CYCLE_WINDOW,
+            seed: None,
+            torus: Torus { size: DEFAULT_TORUS_SIZE, edges: Edges::Wrap },
+            theme: None,
+            keys: None,
+            pan_step: DEFAULT_PAN_STEP,
+            snap_stride: DEFAULT_SNAP_STRIDE,
+            export_scale: DEFAULT_EXPORT_SCALE,
+            cell_inset: 0,
+            max_population: DEFAULT_MAX_POPULATION,
+            trail_duration: DEFAULT_TRAIL_DURATION,
+            frame_stride: 1,
+            max_frames: DEFAULT_MAX_FRAMES,
+            frames_board: false,
+            snapshot_edits: false,
+            screenshot_ui: false,
+            bench: false,
+            headless: false,
+            csv: false,
+            steps: DEFAULT_HEADLESS_STEPS,
+        }
+    }
+}
+
+impl Args {
+    /// Parses the command-line arguments, excluding the program name. `--range`, `--birth`,
+    /// `--survival` and `--kernel` change the `--rule`, whichever order they come in.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        let (mut range, mut birth, mut survival, mut weights) = (None, None, None, None);
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--load" => {
+                    let path = args.next().ok_or("--load expects a file path")?;
+                    parsed.load = Some(PathBuf::from(path));
+                }
+                "--diff" => {
+                    let a = args.next().ok_or("--diff expects two file paths")?;
+                    let b = args.next().ok_or("--diff expects two file paths")?;
+                    parsed.diff = Some((PathBuf::from(a), PathBuf::from(b)));
+                }
+                "--image" => {
+                    let path = args.next().ok_or("--image expects a file path")?;
+                    parsed.image = Some(PathBuf::from(path));
+                }
+                "--image-threshold" => {
+                    let threshold = args.next().ok_or("--image-threshold expects a brightness from 0 to 255")?;
+                    parsed.image_threshold =
+                        threshold.parse().map_err(|_| format!("invalid --image-threshold: {threshold}"))?;
+                }
+                "--rule" => {
+                    let rule = args.next().ok_or("--rule expects a rule such as B3/S23")?;
+                    parsed.rule = Rule::parse(&rule).map_err(|err| format!("invalid --rule: {err}"))?;
+                }
+                "--range" => {
+                    let cells = args.next().ok_or("--range expects a number of cells")?;
+                    let reach = cells.parse().ok().filter(|reach| (1..=MAX_RANGE).contains(reach));
+                    range = Some(reach.ok_or_else(|| format!("invalid --range: {cells}"))?);
+                }
+                "--birth" => {
+                    let span = args.next().ok_or("--birth expects neighbour counts such as 34..45")?;
+                    birth = Some(Counts::parse_spans(&span).ok_or_else(|| format!("invalid --birth: {span}"))?);
+                }
+                "--survival" => {
+                    let span = args.next().ok_or("--survival expects neighbour counts such as 33..57")?;
+                    survival = Some(Counts::parse_spans(&span).ok_or_else(|| format!("invalid --survival: {span}"))?);
+                }
+                "--kernel" => {
+                    let digits = args.next().ok_or("--kernel expects weights such as 121202121")?;
+                    weights = Some(Weights::parse(&digits).ok_or_else(|| format!("invalid --kernel: {digits}"))?);
+                }
+                "--history-depth" => {
+                    let depth = args.next().ok_or("--history-depth expects a number of generations")?;
+                    parsed.history_depth = depth.parse().map_err(|_| format!("invalid --history-depth: {depth}"))?;
+                }
+                "--cycle-window" => {
+                    let window = args.next().ok_or("--cycle-window expects a number of generations")?;
+                    parsed.cycle_window = window.parse().map_err(|_| format!("invalid --cycle-window: {window}"))?;
+                }
+                "--seed" => {
+                    let seed = args.next().ok_or("--seed expects a number")?;
+                    parsed.seed = Some(seed.parse().map_err(|_| format!("invalid --seed: {seed}"))?);
+                }
+                "--birth-chance" | "--survival-chance" => {
+                    let chance = args.next().ok_or_else(|| format!("{arg} expects a chance from 0 to 1"))?;
+                    let parsed_chance = chance.parse().ok().filter(|chance| (0.0..=1.0).contains(chance));
+                    let parsed_chance = parsed_chance.ok_or_else(|| format!("invalid {arg}: {chance}"))?;
+                    let noise = parsed.noise.get_or_insert_with(Noise::default);
+                    *if arg == "--birth-chance" { &mut noise.birth } else { &mut noise.survival } = parsed_chance;
+                }
+                "--noise-seed" => {
+                    let seed = args.next().ok_or("--noise-seed expects a number")?;
+                    let seed = seed.parse().map_err(|_| format!("invalid --noise-seed: {seed}"))?;
+                    parsed.noise.get_or_insert_with(Noise::default).seed = seed;
+                }
+                "--torus-size" => {
+                    let size = args.next().ok_or("--torus-size expects a size such as 100x80")?;
+                    parsed.torus = Torus::parse(&size).ok_or_else(|| format!("invalid --torus-size: {size}"))?;
+                }
+                "--theme" => {
+                    let path = args.next().ok_or("--theme expects a file path")?;
+                    parsed.theme = Some(PathBuf::from(path));
+                }
+                "--keys" => {
+                    let path = args.next().ok_or("--keys expects a file path")?;
+                    parsed.keys = Some(PathBuf::from(path));
+                }
+                "--pan-step" => {
+                    let step = args.next().ok_or("--pan-step expects a number of pixels")?;
+                    let pixels = step.parse().ok().filter(|&pixels| pixels > 0);
+                    parsed.pan_step = pixels.ok_or_else(|| format!("invalid --pan-step: {step}"))?;
+                }
+                "--snap-stride" => {
+                    let stride = args.next().ok_or("--snap-stride expects a number of cells")?;
+                    let cells = stride.parse().ok().filter(|&cells| cells > 0);
+                    parsed.snap_stride = cells.ok_or_else(|| format!("invalid --snap-stride: {stride}"))?;
+                }
+                "--export-scale" => {
+                    let scale = args.next().ok_or("--export-scale expects a number of pixels per cell")?;
+                    let pixels = scale.parse().ok().filter(|&pixels| pixels > 0);
+                    parsed.export_scale = pixels.ok_or_else(|| format!("invalid --export-scale: {scale}"))?;
+                }
+                "--cell-inset" => {
+                    let inset = args.next().ok_or("--cell-inset expects a number of pixels")?;
+                    let pixels = inset.parse().ok().filter(|&pixels| pixels >= 0);
+                    parsed.cell_inset = pixels.ok_or_else(|| format!("invalid --cell-inset: {inset}"))?;
+                }
+                "--max-population" => {
+                    let population = args.next().ok_or("--max-population expects a number of cells")?;
+                    let cells = population.parse().ok().filter(|&cells| cells > 0);
+                    parsed.max_population = cells.ok_or_else(|| format!("invalid --max-population: {population}"))?;
+                }
+                "--trail-duration" => {
+                    let duration = args.next().ok_or("--trail-duration expects a number of seconds")?;
+                    let seconds = duration.parse().ok().filter(|&seconds: &f64| seconds > 0.0 && seconds.is_finite());
+                    parsed.trail_duration = seconds.ok_or_else(|| format!("invalid --trail-duration: {duration}"))?;
+                }
+                "--snapshot-edits" => parsed.snapshot_edits = true,
+                "--frame-stride" => {
+                    let stride = args.next().ok_or("--frame-stride expects a number of generations")?;
+                    let generations = stride.parse().ok().filter(|&generations| generations > 0);
+                    parsed.frame_stride = generations.ok_or_else(|| format!("invalid --frame-stride: {stride}"))?;
+                }
+                "--max-frames" => {
+                    let frames = args.next().ok_or("--max-frames expects a number of frames")?;
+                    let count = frames.parse().ok().filter(|&count| count > 0);
+                    parsed.max_frames = count.ok_or_else(|| format!("invalid --max-frames: {frames}"))?;
+                }
+                "--frames-board" => parsed.frames_board = true,
+                "--screenshot-ui" => parsed.screenshot_ui = true,
+                "--bench" => parsed.bench = true,
+                "--headless" => parsed.headless = true,
+                "--csv" => parsed.csv = true,
+                "--steps" => {
+                    let steps = args.next().ok_or("--steps expects a number of generations")?;
+                    parsed.steps = steps.parse().map_err(|_| format!("invalid --steps: {steps}"))?;
+                }
+                flag if flag.starts_with("--") => return Err(format!("unknown flag {flag}")),
+                _ => parsed.load = Some(PathBuf::from(arg)),
+            }
+        }
+
+        if range.is_some() || birth.is_some() || survival.is_some() || weights.is_some() {
+            let rule = Rule {
+                neighbourhood: weights.map_or(parsed.rule.neighbourhood, Neighbourhood::Weighted),
+                range: range.or(weights.map(|weights| weights.range())).unwrap_or(parsed.rule.range),
+                birth: birth.unwrap_or(parsed.rule.birth),
+                survival: survival.unwrap_or(parsed.rule.survival),
+                ..parsed.rule
+            };
+            parsed.rule = rule.checked().map_err(|err| format!("invalid rule: {err}"))?;
+        }
+
+        if parsed.csv && !parsed.headless {
+            return Err("--csv only applies with --headless".to_string());
+        }
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        Args::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_load_flag_and_positional_path() {
+        assert_eq!(parse(&[]), Ok(Args::default()));
+        assert_eq!(parse(&["--load", "a.rle"]).unwrap().load, Some(PathBuf::from("a.rle")));
+        assert_eq!(parse(&["b.cells"]).unwrap().load, Some(PathBuf::from("b.cells")));
+    }
+
+    #[test]
+    fn parses_image_flags() {
+        let args = parse(&["--image", "art.png", "--image-threshold", "200"]).unwrap();
+        assert_eq!(args.image, Some(PathBuf::from("art.png")));
+        assert_eq!(args.image_threshold, 200);
+        assert_eq!(parse(&[]).unwrap().image_threshold, DEFAULT_IMAGE_THRESHOLD);
+        assert!(parse(&["--image-threshold", "256"]).is_err());
+    }
+
+    #[test]
+    fn parses_rule_flag() {
+        assert_eq!(parse(&[]).unwrap().rule, Rule::CONWAY);
+        assert_eq!(parse(&["--rule", "B36/S23"]).unwrap().rule.to_string(), "B36/S23");
+    }
+
+    #[test]
+    fn parses_range_and_count_flags() {
+        let bosco = parse(&["--range", "5", "--birth", "34..45", "--survival", "33..57"]).unwrap().rule;
+        assert_eq!(bosco.to_string(), "R5,C0,M0,S33..57,B34..45,NM");
+        let ranged = parse(&["--range", "2", "--rule", "B36/S23"]).unwrap().rule;
+        assert_eq!(ranged.to_string(), "R2,C0,M0,S2..3,B3,6,NM");
+        assert!(parse(&["--range", "0"]).is_err());
+        assert!(parse(&["--range", "8"]).is_err());
+        assert!(parse(&["--birth", "45..34"]).is_err());
+        assert!(parse(&["--birth", "0..3"]).is_err());
+        assert!(parse(&["--survival", "9"]).is_err());
+
+        let weighted = parse(&["--kernel", "121202121", "--birth", "4", "--survival", "3..5"]).unwrap().rule;
+        assert_eq!(weighted.to_string(), "R1,C0,M0,S3..5,B4,NW121202121");
+        assert!(parse(&["--kernel", "1212"]).is_err());
+        assert!(parse(&["--kernel", "121202121", "--range", "2"]).is_err());
+    }
+
+    #[test]
+    fn parses_history_depth_flag() {
+        assert_eq!(parse(&[]).unwrap().history_depth, DEFAULT_HISTORY_DEPTH);
+        assert_eq!(parse(&["--history-depth", "0"]).unwrap().history_depth, 0);
+        assert_eq!(parse(&["--history-depth", "500"]).unwrap().history_depth, 500);
+    }
+
+    #[test]
+    fn parses_cycle_window_flag() {
+        assert_eq!(parse(&[]).unwrap().cycle_window, DEFAULT_CYCLE_WINDOW);
+        assert_eq!(parse(&["--cycle-window", "8"]).unwrap().cycle_window, 8);
+        assert!(parse(&["--cycle-window", "many"]).is_err());
+    }
+
+    #[test]
+    fn parses_diff_flag() {
+        assert_eq!(parse(&[]).unwrap().diff, None);
+        let diff = parse(&["--diff", "a.rle", "b.rle"]).unwrap().diff;
+        assert_eq!(diff, Some((PathBuf::from("a.rle"), PathBuf::from("b.rle"))));
+        assert!(parse(&["--diff", "a.rle"]).is_err());
+    }
+
+    #[test]
+    fn parses_seed_flag() {
+        assert_eq!(parse(&[]).unwrap().seed, None);
+        assert_eq!(parse(&["--seed", "42"]).unwrap().seed, Some(42));
+        assert!(parse(&["--seed", "x"]).is_err());
+    }
+
+    #[test]
+    fn parses_noise_flags() {
+        assert_eq!(parse(&[]).unwrap().noise, None);
+        let noise = parse(&["--survival-chance", "0.5", "--noise-seed", "7"]).unwrap().noise;
+        assert_eq!(noise, Some(Noise { birth: 1.0, survival: 0.5, seed: 7 }));
+        assert_eq!(parse(&["--birth-chance", "0"]).unwrap().noise.unwrap().birth, 0.0);
+        assert!(parse(&["--birth-chance", "1.5"]).is_err());
+        assert!(parse(&["--survival-chance", "NaN"]).is_err());
+        assert!(parse(&["--noise-seed", "-1"]).is_err());
+    }
+
+    #[test]
+    fn parses_torus_size_flag() {
+        assert_eq!(parse(&[]).unwrap().torus.size, DEFAULT_TORUS_SIZE);
+        assert_eq!(parse(&["--torus-size", "64x48"]).unwrap().torus.size, I64Vec2::new(64, 48));
+        assert!(parse(&["--torus-size", "0x48"]).is_err());
+        assert!(parse(&["--torus-size"]).is_err());
+    }
+
+    #[test]
+    fn parses_theme_flag() {
+        assert_eq!(parse(&[]).unwrap().theme, None);
+        assert_eq!(parse(&["--theme", "night.theme"]).unwrap().theme, Some(PathBuf::from("night.theme")));
+        assert!(parse(&["--theme"]).is_err());
+    }
+
+    #[test]
+    fn parses_keys_flag() {
+        assert_eq!(parse(&[]).unwrap().keys, None);
+        assert_eq!(parse(&["--keys", "mine.json"]).unwrap().keys, Some(PathBuf::from("mine.json")));
+        assert!(parse(&["--keys"]).is_err());
+    }
+
+    #[test]
+    fn parses_pan_step_flag() {
+        assert_eq!(parse(&[]).unwrap().pan_step, DEFAULT_PAN_STEP);
+        assert_eq!(parse(&["--pan-step", "100"]).unwrap().pan_step, 100);
+        assert!(parse(&["--pan-step", "0"]).is_err());
+    }
+
+    #[test]
+    fn parses_snap_stride_flag() {
+        assert_eq!(parse(&[]).unwrap().snap_stride, DEFAULT_SNAP_STRIDE);
+        assert_eq!(parse(&["--snap-stride", "4"]).unwrap().snap_stride, 4);
+        assert!(parse(&["--snap-stride", "0"]).is_err());
+    }
+
+    #[test]
+    fn parses_export_scale_flag() {
+        assert_eq!(parse(&[]).unwrap().export_scale, DEFAULT_EXPORT_SCALE);
+        assert_eq!(parse(&["--export-scale", "1"]).unwrap().export_scale, 1);
+        assert!(parse(&["--export-scale", "0"]).is_err());
+    }
+
+    #[test]
+    fn parses_cell_inset_flag() {
+        assert_eq!(parse(&[]).unwrap().cell_inset, 0);
+        assert_eq!(parse(&["--cell-inset", "2"]).unwrap().cell_inset, 2);
+        assert!(parse(&["--cell-inset", "-1"]).is_err());
+    }
+
+    #[test]
+    fn parses_max_population_flag() {
+        assert_eq!(parse(&[]).unwrap().max_population, DEFAULT_MAX_POPULATION);
+        assert_eq!(parse(&["--max-population", "1000"]).unwrap().max_population, 1000);
+        assert!(parse(&["--max-population", "0"]).is_err());
+    }
+
+    #[test]
+    fn parses_frame_sequence_flags() {
+        let defaults = parse(&[]).unwrap();
+        assert_eq!((defaults.frame_stride, defaults.max_frames, defaults.frames_board), (1, DEFAULT_MAX_FRAMES, false));
+        let args = parse(&["--frame-stride", "5", "--max-frames", "250", "--frames-board"]).unwrap();
+        assert_eq!((args.frame_stride, args.max_frames, args.frames_board), (5, 250, true));
+        assert!(parse(&["--frame-stride", "0"]).is_err());
+        assert!(parse(&["--max-frames", "-1"]).is_err());
+        assert!(parse(&["--max-frames"]).is_err());
+    }
+
+    #[test]
+    fn parses_trail_duration_flag() {
+        assert_eq!(parse(&[]).unwrap().trail_duration, DEFAULT_TRAIL_DURATION);
+        assert_eq!(parse(&["--trail-duration", "1.5"]).unwrap().trail_duration, 1.5);
+        assert!(parse(&["--trail-duration", "0"]).is_err());
+        assert!(parse(&["--trail-duration", "inf"]).is_err());
+    }
+
+    #[test]
+    fn parses_steps_flag() {
+        assert_eq!(parse(&[]).unwrap().steps, DEFAULT_HEADLESS_STEPS);
+        assert_eq!(parse(&["--steps", "5000"]).unwrap().steps, 5000);
+        assert!(parse(&["--steps", "-1"]).is_err());
+    }
+
+    #[test]
+    fn parses_boolean_flags() {
+        assert!(!parse(&[]).unwrap().bench);
+        assert!(parse(&["--bench"]).unwrap().bench);
+        assert!(!parse(&[]).unwrap().screenshot_ui);
+        assert!(parse(&["--screenshot-ui"]).unwrap().screenshot_ui);
+        assert!(!parse(&[]).unwrap().headless);
+        assert!(parse(&["--headless"]).unwrap().headless);
+        assert!(!parse(&[]).unwrap().snapshot_edits);
+        assert!(parse(&["--snapshot-edits"]).unwrap().snapshot_edits);
+        assert!(parse(&["--headless", "--csv"]).unwrap().csv);
+        assert!(parse(&["--csv"]).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert!(parse(&["--rule", "B9/S23"]).is_err());
+        assert!(parse(&["--rule"]).is_err());
+        assert!(parse(&["--load"]).is_err());
+        assert!(parse(&["--history-depth", "-1"]).is_err());
+        assert!(parse(&["--nope"]).is_err());
+    }
+}
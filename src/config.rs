@@ -0,0 +1,247 @@
+use std::path::{Path, PathBuf};
+use std::{env, fmt, fs, io};
+use crate::brush::{BrushShape, MAX_BRUSH_RADIUS};
+use crate::camera::{MAX_DRAG_THRESHOLD, MAX_PAN_SENSITIVITY, MIN_PAN_SENSITIVITY};
+use crate::json::{Json, JsonError};
+
+/// The file preferences are kept in, inside [`config_dir`].
+pub const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Preferences kept between launches. A field left as `None` wasn't in the file, so the app's
+/// own default applies.
+///
+/// Key bindings aren't kept here, as the app can't change them: they stay in their own file,
+/// edited by hand, and only which file to read is a preference.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Config {
+    /// Whether the dark preset is the starting theme rather than the light one.
+    pub dark_theme: Option<bool>,
+    /// Steps per second, where 0 is as fast as the board can go.
+    pub speed: Option<u32>,
+    pub brush_radius: Option<i64>,
+    pub brush_shape: Option<BrushShape>,
+    pub fill_density: Option<f64>,
+    pub show_grid: Option<bool>,
+    pub draw_mode: Option<bool>,
+    pub click_toggles: Option<bool>,
+    /// How far the mouse moves before a press becomes a drag, as a squared distance in pixels.
+    pub drag_threshold: Option<i32>,
+    /// How far a drag pans, as a multiple of the distance the mouse moved.
+    pub pan_sensitivity: Option<f64>,
+    /// Whether wheel zooms ease over a few frames rather than jumping straight there.
+    pub smooth_zoom: Option<bool>,
+    /// A key bindings file to read when `--keys` isn't given, in place of `keys.json`.
+    pub keys: Option<PathBuf>,
+}
+
+impl Config {
+    /// Reads `key = value` lines, with values written as in TOML: quoted strings, numbers and
+    /// `true` or `false`. Blank lines and lines starting with `#` are ignored.
+    ///
+    /// A line that can't be read is reported and its field left unset, so a broken file only
+    /// loses the settings it got wrong.
+    pub fn parse(input: &str) -> (Config, Vec<ConfigError>) {
+        let mut config = Config::default();
+        let mut errors = Vec::new();
+
+        for (index, line) in input.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                errors.push(ConfigError::InvalidLine { line: line_number });
+                continue;
+            };
+            let key = key.trim();
+            // TOML's strings, numbers and booleans are written just as in JSON.
+            let value = match Json::parse(value.trim()) {
+                Ok(value) => value,
+                Err(err) => {
+                    errors.push(ConfigError::Value { line: line_number, err });
+                    continue;
+                }
+            };
+            let is_valid = match key {
+                "theme" => set(&mut config.dark_theme, value.as_str().and_then(|theme| match theme {
+                    "light" => Some(false),
+                    "dark" => Some(true),
+                    _ => None,
+                })),
+                "speed" => set(&mut config.speed, value.as_i64().and_then(|speed| u32::try_from(speed).ok())),
+                "brush_radius" => {
+                    let radius = value.as_i64().filter(|radius| (0..=MAX_BRUSH_RADIUS).contains(radius));
+                    set(&mut config.brush_radius, radius)
+                }
+                "brush_shape" => set(&mut config.brush_shape, value.as_str().and_then(|name| match name {
+                    "square" => Some(BrushShape::Square),
+                    "circle" => Some(BrushShape::Circle),
+                    _ => None,
+                })),
+                "fill_density" => {
+                    let density = as_number(&value).filter(|density| (0.0..=1.0).contains(density));
+                    set(&mut config.fill_density, density)
+                }
+                "show_grid" => set(&mut config.show_grid, value.as_bool()),
+                "draw_mode" => set(&mut config.draw_mode, value.as_bool()),
+                "click_toggles" => set(&mut config.click_toggles, value.as_bool()),
+                "drag_threshold" => {
+                    let squared = value.as_i64().filter(|squared| (0..=MAX_DRAG_THRESHOLD as i64).contains(squared));
+                    set(&mut config.drag_threshold, squared.map(|squared| squared as i32))
+                }
+                "smooth_zoom" => set(&mut config.smooth_zoom, value.as_bool()),
+                "pan_sensitivity" => {
+                    let range = MIN_PAN_SENSITIVITY..=MAX_PAN_SENSITIVITY;
+                    let sensitivity = as_number(&value).filter(|sensitivity| range.contains(sensitivity));
+                    set(&mut config.pan_sensitivity, sensitivity)
+                }
+                "keys" => set(&mut config.keys, value.as_str().map(PathBuf::from)),
+                _ => {
+                    errors.push(ConfigError::UnknownKey { line: line_number, key: key.to_string() });
+                    continue;
+                }
+            };
+            if !is_valid {
+                errors.push(ConfigError::InvalidValue { line: line_number, key: key.to_string() });
+            }
+        }
+
+        (config, errors)
+    }
+
+    /// Writes one `key = value` line per field that is set.
+    pub fn to_toml(&self) -> String {
+        let fields = [
+            ("theme", self.dark_theme.map(|dark| Json::String(if dark { "dark" } else { "light" }.to_string()))),
+            ("speed", self.speed.map(|speed| Json::Int(speed as i64))),
+            ("brush_radius", self.brush_radius.map(Json::Int)),
+            ("brush_shape", self.brush_shape.map(|shape| Json::String(shape.name().to_string()))),
+            ("fill_density", self.fill_density.map(Json::Float)),
+            ("show_grid", self.show_grid.map(Json::Bool)),
+            ("draw_mode", self.draw_mode.map(Json::Bool)),
+            ("click_toggles", self.click_toggles.map(Json::Bool)),
+            ("drag_threshold", self.drag_threshold.map(|pixels| Json::Int(pixels as i64))),
+            ("pan_sensitivity", self.pan_sensitivity.map(Json::Float)),
+            ("smooth_zoom", self.smooth_zoom.map(Json::Bool)),
+            ("keys", self.keys.as_ref().map(|path| Json::String(path.display().to_string()))),
+        ];
+        fields.into_iter().filter_map(|(key, value)| Some(format!("{key} = {}\n", value?))).collect()
+    }
+
+    /// Reads the config file, or an empty config when there isn't one yet.
+    pub fn load(path: &Path) -> (Config, Vec<ConfigError>) {
+        match fs::read_to_string(path) {
+            Ok(input) => Config::parse(&input),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (Config::default(), Vec::new()),
+            Err(err) => (Config::default(), vec![ConfigError::Io(err)]),
+        }
+    }
+
+    /// Writes the config file, making its directory first if need be.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_toml())
+    }
+}
+
+/// A number written with or without a decimal point.
+fn as_number(value: &Json) -> Option<f64> {
+    match *value {
+        Json::Float(number) => Some(number),
+        Json::Int(number) => Some(number as f64),
+        _ => None,
+    }
+}
+
+/// Sets `field` to `value` if there is one, returning whether there was.
+fn set<T>(field: &mut Option<T>, value: Option<T>) -> bool {
+    let is_valid = value.is_some();
+    if is_valid {
+        *field = value;
+    }
+    is_valid
+}
+
+/// The directory this app keeps its config in: under `%APPDATA%` on Windows,
+/// `~/Library/Application Support` on macOS and `$XDG_CONFIG_HOME` or `~/.config` elsewhere.
+pub fn config_dir() -> Option<PathBuf> {
+    let var = |name: &str| env::var_os(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    let base = if cfg!(windows) {
+        var("APPDATA")?
+    } else if cfg!(target_os = "macos") {
+        var("HOME")?.join("Library/Application Support")
+    } else {
+        var("XDG_CONFIG_HOME").filter(|dir| dir.is_absolute()).or_else(|| Some(var("HOME")?.join(".config")))?
+    };
+    Some(base.join("infinite-game-of-life"))
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    InvalidLine { line: usize },
+    Value { line: usize, err: JsonError },
+    UnknownKey { line: usize, key: String },
+    InvalidValue { line: usize, key: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "{err}"),
+            ConfigError::InvalidLine { line } => write!(f, "expected `key = value` on line {line}"),
+            ConfigError::Value { line, err } => write!(f, "invalid value on line {line}: {err}"),
+            ConfigError::UnknownKey { line, key } => write!(f, "unknown setting {key:?} on line {line}"),
+            ConfigError::InvalidValue { line, key } => write!(f, "invalid {key} on line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_setting() {
+        let config = Config {
+            dark_theme: Some(true),
+            speed: Some(0),
+            brush_radius: Some(3),
+            brush_shape: Some(BrushShape::Circle),
+            fill_density: Some(0.35),
+            show_grid: Some(false),
+            draw_mode: Some(true),
+            click_toggles: Some(false),
+            drag_threshold: Some(4),
+            pan_sensitivity: Some(0.5),
+            smooth_zoom: Some(false),
+            keys: Some(PathBuf::from("my keys.json")),
+        };
+        let toml = config.to_toml();
+        assert!(toml.starts_with("theme = \"dark\"\nspeed = 0\n"), "{toml}");
+        let (parsed, errors) = Config::parse(&toml);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(parsed, config);
+        assert_eq!(Config::default().to_toml(), "");
+    }
+
+    #[test]
+    fn keeps_the_settings_it_can_read() {
+        let input = "# preferences\n\nspeed = 20\ntheme = \"sepia\"\nbrush_radius = 99\nfill_density = 1\n\
+            volume = 11\nshow_grid = yes\ngarbage\npan_sensitivity = 2\npan_sensitivity = 0\n";
+        let (config, errors) = Config::parse(input);
+        let expected = Config { speed: Some(20), fill_density: Some(1.0), ..Config::default() };
+        assert_eq!(config, Config { pan_sensitivity: Some(2.0), ..expected });
+        let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+        assert_eq!(messages[..2], ["invalid theme on line 4", "invalid brush_radius on line 5"]);
+        assert_eq!(messages[2], "unknown setting \"volume\" on line 7");
+        assert!(messages[3].starts_with("invalid value on line 8"));
+        assert_eq!(messages[4], "expected `key = value` on line 9");
+        assert_eq!(messages[5], "invalid pan_sensitivity on line 11");
+    }
+}
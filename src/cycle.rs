@@ -0,0 +1,182 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use glam::I64Vec2;
+use crate::life::Life;
+
+/// A board state that recurred `period` generations after it was first seen, shifted by
+/// `displacement`. A period of 1 with no displacement is a still life.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub period: u64,
+    pub displacement: I64Vec2,
+    /// The generation at which the repeat was detected.
+    pub generation: u64,
+}
+
+impl Cycle {
+    pub fn is_still_life(&self) -> bool {
+        self.period == 1
+    }
+}
+
+/// Spots boards that repeat an earlier state within the last `window` generations, wherever
+/// on the board the repeat appears, so oscillators and spaceships are caught alike.
+///
+/// States are compared by hash, so a collision could report a cycle that isn't there, but with
+/// 64-bit hashes that is vanishingly unlikely.
+#[derive(Debug, Clone)]
+pub struct CycleDetector {
+    window: usize,
+    /// The latest generation each state hash was seen at, and the board's offset then.
+    seen: HashMap<u64, (u64, I64Vec2)>,
+    order: VecDeque<(u64, u64)>,
+    last: Option<(u64, u64)>,
+    cycle: Option<Cycle>,
+}
+
+impl CycleDetector {
+    pub fn new(window: usize) -> Self {
+        Self { window, seen: HashMap::new(), order: VecDeque::new(), last: None, cycle: None }
+    }
+
+    /// Records the board, returning the cycle if this is the step that first closes one.
+    ///
+    /// Call it before and after each step. Going back a generation, or an edit between steps,
+    /// breaks the run of generations, so everything seen so far is forgotten.
+    pub fn observe(&mut self, life: &Life) -> Option<Cycle> {
+        let generation = life.generation();
+        let (hash, offset) = fingerprint(life);
+        match self.last {
+            Some(last) if last == (generation, hash) => return None,
+            Some((last_generation, _)) if last_generation.checked_add(1) == Some(generation) => {}
+            _ => self.reset(),
+        }
+        self.last = Some((generation, hash));
+
+        let repeat = self.seen.insert(hash, (generation, offset)).map(|(seen_generation, seen_offset)| Cycle {
+            period: generation - seen_generation,
+            displacement: offset - seen_offset,
+            generation,
+        });
+        self.order.push_back((generation, hash));
+        while self.order.len() > self.window {
+            let Some((old_generation, old_hash)) = self.order.pop_front() else {
+                break;
+            };
+            if self.seen.get(&old_hash).is_some_and(|&(seen_generation, _)| seen_generation == old_generation) {
+                self.seen.remove(&old_hash);
+            }
+        }
+
+        if self.cycle.is_none() && repeat.is_some() {
+            self.cycle = repeat;
+            return repeat;
+        }
+        None
+    }
+
+    /// The cycle the board has been in since it was detected, if any.
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+
+    pub fn reset(&mut self) {
+        self.seen.clear();
+        self.order.clear();
+        self.last = None;
+        self.cycle = None;
+    }
+}
+
+/// Hashes the cells relative to their top-left corner, returning the hash and that corner.
+/// Cell hashes are summed so the hash doesn't depend on iteration order.
+fn fingerprint(life: &Life) -> (u64, I64Vec2) {
+    let cells = life.cells();
+    let min = cells.keys().copied().reduce(I64Vec2::min).unwrap_or(I64Vec2::ZERO);
+    let hash = cells.iter().fold(cells.len() as u64, |sum, (&cell, &state)| {
+        let mut hasher = DefaultHasher::new();
+        (cell - min, state).hash(&mut hasher);
+        sum.wrapping_add(hasher.finish())
+    });
+    (hash, min)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rule::Rule;
+
+    fn life(points: &[(i64, i64)]) -> Life {
+        Life::from_cells(points.iter().map(|&(x, y)| I64Vec2::new(x, y)).collect())
+    }
+
+    /// Steps the board until a cycle is found, giving up after `limit` generations.
+    fn run(detector: &mut CycleDetector, life: &mut Life, limit: u64) -> Option<Cycle> {
+        detector.observe(life);
+        for _ in 0..limit {
+            life.step();
+            if let Some(cycle) = detector.observe(life) {
+                return Some(cycle);
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn detects_still_lifes_and_oscillators() {
+        let block = run(&mut CycleDetector::new(8), &mut life(&[(0, 0), (1, 0), (0, 1), (1, 1)]), 10).unwrap();
+        assert!(block.is_still_life());
+        assert_eq!(block.generation, 1);
+
+        let mut blinker = life(&[(0, 0), (1, 0), (2, 0)]);
+        let mut detector = CycleDetector::new(8);
+        let cycle = run(&mut detector, &mut blinker, 10).unwrap();
+        assert_eq!((cycle.period, cycle.displacement, cycle.generation), (2, I64Vec2::ZERO, 2));
+        assert_eq!(detector.cycle(), Some(cycle));
+
+        blinker.step();
+        assert_eq!(detector.observe(&blinker), None, "only the first repeat is reported");
+        assert_eq!(detector.cycle(), Some(cycle));
+    }
+
+    #[test]
+    fn detects_spaceships_with_their_displacement() {
+        let mut glider = life(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        let cycle = run(&mut CycleDetector::new(8), &mut glider, 20).unwrap();
+        assert_eq!((cycle.period, cycle.displacement), (4, I64Vec2::new(1, 1)));
+    }
+
+    #[test]
+    fn ignores_periods_longer_than_the_window() {
+        let mut blinker = life(&[(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(run(&mut CycleDetector::new(1), &mut blinker, 10), None);
+    }
+
+    #[test]
+    fn tells_decaying_states_apart() {
+        // A lone cell decays through two states before the board settles empty, which only the
+        // states tell apart.
+        let mut decaying = life(&[(0, 0)]);
+        decaying.set_rule(Rule::parse("B3/S23/C4").unwrap());
+        assert_eq!(run(&mut CycleDetector::new(8), &mut decaying, 20).map(|cycle| cycle.generation), Some(4));
+    }
+
+    #[test]
+    fn edits_and_stepping_back_forget_the_past() {
+        let mut blinker = life(&[(0, 0), (1, 0), (2, 0)]);
+        let mut detector = CycleDetector::new(8);
+        run(&mut detector, &mut blinker, 10).unwrap();
+
+        blinker.toggle(I64Vec2::new(10, 10));
+        detector.observe(&blinker);
+        assert_eq!(detector.cycle(), None);
+
+        blinker.toggle(I64Vec2::new(10, 10));
+        blinker.set_generation(0);
+        detector.observe(&blinker);
+        blinker.step();
+        assert_eq!(detector.observe(&blinker), None);
+        blinker.step();
+        assert_eq!(detector.observe(&blinker).map(|cycle| cycle.period), Some(2));
+    }
+}
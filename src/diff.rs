@@ -0,0 +1,69 @@
+use std::collections::HashSet;
+use std::fmt;
+use glam::I64Vec2;
+
+/// Where two boards differ, for checking one engine's result against another's.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoardDiff {
+    pub only_a: HashSet<I64Vec2>,
+    pub only_b: HashSet<I64Vec2>,
+    pub both: HashSet<I64Vec2>,
+}
+
+impl BoardDiff {
+    pub fn between(a: &HashSet<I64Vec2>, b: &HashSet<I64Vec2>) -> Self {
+        Self {
+            only_a: a.difference(b).copied().collect(),
+            only_b: b.difference(a).copied().collect(),
+            both: a.intersection(b).copied().collect(),
+        }
+    }
+
+    /// Whether the boards have exactly the same live cells.
+    pub fn is_identical(&self) -> bool {
+        self.only_a.is_empty() && self.only_b.is_empty()
+    }
+
+    /// The inclusive `(min, max)` corners of every cell on either board, or `None` when both
+    /// are empty.
+    pub fn bounds(&self) -> Option<(I64Vec2, I64Vec2)> {
+        let mut cells = self.only_a.iter().chain(&self.only_b).chain(&self.both).copied();
+        let first = cells.next()?;
+        Some(cells.fold((first, first), |(min, max), cell| (min.min(cell), max.max(cell))))
+    }
+}
+
+impl fmt::Display for BoardDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} only in A, {} only in B, {} in both", self.only_a.len(), self.only_b.len(), self.both.len())?;
+        if self.is_identical() {
+            write!(f, " (identical)")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(points: &[(i64, i64)]) -> HashSet<I64Vec2> {
+        points.iter().map(|&(x, y)| I64Vec2::new(x, y)).collect()
+    }
+
+    #[test]
+    fn sorts_cells_by_which_board_has_them() {
+        let a = cells(&[(0, 0), (1, 0), (5, -2)]);
+        let b = cells(&[(1, 0), (-3, 4)]);
+        let diff = BoardDiff::between(&a, &b);
+        assert_eq!(diff.only_a, cells(&[(0, 0), (5, -2)]));
+        assert_eq!(diff.only_b, cells(&[(-3, 4)]));
+        assert_eq!(diff.both, cells(&[(1, 0)]));
+        assert_eq!(diff.bounds(), Some((I64Vec2::new(-3, -2), I64Vec2::new(5, 4))));
+        assert_eq!(diff.to_string(), "2 only in A, 1 only in B, 1 in both");
+
+        let same = BoardDiff::between(&a, &a);
+        assert_eq!(same.to_string(), "0 only in A, 0 only in B, 3 in both (identical)");
+        assert_eq!(BoardDiff::between(&HashSet::new(), &HashSet::new()).bounds(), None);
+    }
+}
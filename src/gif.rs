@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Gray levels appended after the 6x6x6 colour cube so UI text and decaying cells keep their
+/// shades.
+const GRAY_LEVELS: usize = 40;
+const CUBE_LEVELS: usize = 6;
+const MIN_CODE_SIZE: u8 = 8;
+const MAX_CODE: u16 = 4095;
+const MAX_SUB_BLOCK: usize = 255;
+
+/// Streams an animated, looping GIF89a to `writer`, one RGBA frame at a time.
+///
+/// Frames are mapped onto a fixed 256-colour palette and compressed as they are added, so
+/// nothing but the output grows with the recording.
+#[derive(Debug)]
+pub struct GifEncoder<W: Write> {
+    writer: W,
+    width: u16,
+    height: u16,
+    frames: usize,
+}
+
+impl<W: Write> GifEncoder<W> {
+    pub fn new(mut writer: W, width: u16, height: u16) -> io::Result<Self> {
+        writer.write_all(b"GIF89a")?;
+        writer.write_all(&width.to_le_bytes())?;
+        writer.write_all(&height.to_le_bytes())?;
+        // Global colour table of 2^(7 + 1) entries with 8 bits per channel.
+        writer.write_all(&[0xF7, 0, 0])?;
+        writer.write_all(&palette())?;
+        // NETSCAPE2.0 application extension: loop forever.
+        writer.write_all(&[0x21, 0xFF, 0x0B])?;
+        writer.write_all(b"NETSCAPE2.0")?;
+        writer.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])?;
+
+        Ok(Self { writer, width, height, frames: 0 })
+    }
+
+    /// Appends a frame of `width * height` RGBA pixels shown for `delay` hundredths of a second.
+    pub fn add_frame(&mut self, pixels: &[[u8; 4]], delay: u16) -> io::Result<()> {
+        assert_eq!(pixels.len(), self.width as usize * self.height as usize, "frame size mismatch");
+
+        // Graphic control extension with no transparency or disposal.
+        self.writer.write_all(&[0x21, 0xF9, 0x04, 0x00])?;
+        self.writer.write_all(&delay.to_le_bytes())?;
+        self.writer.write_all(&[0x00, 0x00])?;
+
+        self.writer.write_all(&[0x2C, 0, 0, 0, 0])?;
+        self.writer.write_all(&self.width.to_le_bytes())?;
+        self.writer.write_all(&self.height.to_le_bytes())?;
+        self.writer.write_all(&[0x00, MIN_CODE_SIZE])?;
+
+        let indices: Vec<u8> = pixels.iter().map(|&[r, g, b, _]| palette_index(r, g, b)).collect();
+        for block in lzw_encode(&indices).chunks(MAX_SUB_BLOCK) {
+            self.writer.write_all(&[block.len() as u8])?;
+            self.writer.write_all(block)?;
+        }
+        self.writer.write_all(&[0x00])?;
+
+        self.frames += 1;
+        Ok(())
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames
+    }
+
+    /// Writes the trailer and hands back the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.write_all(&[0x3B])?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+fn palette() -> Vec<u8> {
+    let cube = (0..CUBE_LEVELS * CUBE_LEVELS * CUBE_LEVELS).flat_map(|index| {
+        let level = |channel: usize| (channel * 255 / (CUBE_LEVELS - 1)) as u8;
+        [
+            level(index / (CUBE_LEVELS * CUBE_LEVELS)),
+            level(index / CUBE_LEVELS % CUBE_LEVELS),
+            level(index % CUBE_LEVELS),
+        ]
+    });
+    let grays = (0..GRAY_LEVELS).flat_map(|index| [(index * 255 / (GRAY_LEVELS - 1)) as u8; 3]);
+    cube.chain(grays).collect()
+}
+
+fn palette_index(r: u8, g: u8, b: u8) -> u8 {
+    let (min, max) = (r.min(g).min(b), r.max(g).max(b));
+    if max - min < 8 {
+        let gray = (r as usize + g as usize + b as usize) / 3;
+        return (CUBE_LEVELS.pow(3) + (gray * (GRAY_LEVELS - 1) + 127) / 255) as u8;
+    }
+    let level = |channel: u8| (channel as usize * (CUBE_LEVELS - 1) + 127) / 255;
+    (level(r) * CUBE_LEVELS * CUBE_LEVELS + level(g) * CUBE_LEVELS + level(b)) as u8
+}
+
+/// Packs variable-width codes least significant bit first.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u8) {
+        self.buffer |= (code as u32) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// GIF-flavoured LZW: starts with a clear code, grows codes up to 12 bits and clears the
+/// table whenever it fills.
+fn lzw_encode(indices: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << MIN_CODE_SIZE;
+    let end = clear + 1;
+    let mut output = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = end + 1;
+    let mut width = MIN_CODE_SIZE + 1;
+
+    output.write(clear, width);
+    let Some((&first, rest)) = indices.split_first() else {
+        output.write(end, width);
+        return output.finish();
+    };
+
+    let mut prefix = first as u16;
+    for &index in rest {
+        if let Some(&code) = table.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+
+        output.write(prefix, width);
+        if next_code <= MAX_CODE {
+            table.insert((prefix, index), next_code);
+            next_code += 1;
+            // The decoder adds its entries a code later, so it widens one code after us.
+            if next_code > 1 << width && width < 12 {
+                width += 1;
+            }
+        } else {
+            output.write(clear, width);
+            table.clear();
+            next_code = end + 1;
+            width = MIN_CODE_SIZE + 1;
+        }
+        prefix = index as u16;
+    }
+
+    output.write(prefix, width);
+    output.write(end, width);
+    output.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A plain GIF LZW decoder to check the encoder against.
+    fn lzw_decode(data: &[u8]) -> Vec<u8> {
+        let clear = 1usize << MIN_CODE_SIZE;
+        let end = clear + 1;
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let reset = |table: &mut Vec<Vec<u8>>| {
+            *table = (0..=255u8).map(|index| vec![index]).collect();
+            table.extend([Vec::new(), Vec::new()]);
+        };
+        reset(&mut table);
+
+        let (mut width, mut bit, mut previous): (usize, usize, Option<usize>) = (9, 0, None);
+        let mut output = Vec::new();
+        loop {
+            let code = (0..width).fold(0, |code, offset| {
+                let position = bit + offset;
+                code | (((data[position / 8] >> (position % 8)) & 1) as usize) << offset
+            });
+            bit += width;
+
+            if code == clear {
+                reset(&mut table);
+                width = 9;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return output;
+            }
+
+            let entry = match (table.get(code), previous) {
+                (Some(entry), _) => entry.clone(),
+                (None, Some(previous)) => {
+                    let mut entry = table[previous].clone();
+                    entry.push(table[previous][0]);
+                    entry
+                }
+                (None, None) => panic!("invalid first code {code}"),
+            };
+            if let Some(previous) = previous
+                && table.len() <= MAX_CODE as usize
+            {
+                let mut new_entry = table[previous].clone();
+                new_entry.push(entry[0]);
+                table.push(new_entry);
+                if table.len() == 1 << width && width < 12 {
+                    width += 1;
+                }
+            }
+            output.extend_from_slice(&entry);
+            previous = Some(code);
+        }
+    }
+
+    #[test]
+    fn lzw_round_trips() {
+        let mut state = 1u32;
+        let noise: Vec<u8> = (0..20_000)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (state >> 16) as u8
+            })
+            .collect();
+        let runs: Vec<u8> = (0..50_000).map(|index| (index / 700 % 3) as u8).collect();
+
+        for indices in [vec![], vec![7], vec![0; 10_000], noise, runs] {
+            assert_eq!(lzw_decode(&lzw_encode(&indices)), indices);
+        }
+    }
+
+    #[test]
+    fn maps_pure_colours_exactly() {
+        let palette = palette();
+        assert_eq!(palette.len(), 256 * 3);
+
+        for [r, g, b] in [[0, 0, 0], [255, 255, 255], [255, 0, 0], [0, 121, 241], [130, 130, 130]] {
+            let index = palette_index(r, g, b) as usize;
+            let entry = &palette[index * 3..index * 3 + 3];
+            assert!(entry.iter().zip([r, g, b]).all(|(&p, c)| p.abs_diff(c) <= 26), "{r} {g} {b} -> {entry:?}");
+        }
+        assert_eq!(&palette[palette_index(0, 0, 0) as usize * 3..][..3], &[0, 0, 0]);
+        assert_eq!(&palette[palette_index(255, 255, 255) as usize * 3..][..3], &[255, 255, 255]);
+    }
+
+    #[test]
+    fn writes_well_formed_animation() {
+        let mut encoder = GifEncoder::new(Vec::new(), 2, 2).unwrap();
+        encoder.add_frame(&[[0, 0, 0, 255]; 4], 5).unwrap();
+        encoder.add_frame(&[[255, 255, 255, 255]; 4], 5).unwrap();
+        assert_eq!(encoder.frame_count(), 2);
+        let gif = encoder.finish().unwrap();
+
+        assert!(gif.starts_with(b"GIF89a\x02\x00\x02\x00"));
+        assert_eq!(gif.last(), Some(&0x3B));
+        assert_eq!(gif.windows(11).filter(|window| *window == b"NETSCAPE2.0").count(), 1);
+        assert_eq!(gif.windows(4).filter(|window| *window == [0x21, 0xF9, 0x04, 0x00]).count(), 2);
+    }
+}
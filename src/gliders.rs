@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, HashSet};
+use glam::I64Vec2;
+use itertools::Itertools;
+use crate::life::Life;
+use crate::patterns::glider;
+
+/// A glider on the board, in one of its phases, and the diagonal it travels along.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glider {
+    pub cells: Vec<I64Vec2>,
+    /// One cell along each axis per period, with y pointing down.
+    pub direction: I64Vec2,
+}
+
+/// Each phase of the glider in each orientation, with its cells sorted and starting at the
+/// origin, and the direction that way round travels.
+fn masks() -> Vec<(Vec<I64Vec2>, I64Vec2)> {
+    let mut life = Life::from_cells(glider().into_iter().collect());
+    let start = life.live_cells().iter().copied().reduce(I64Vec2::min).unwrap();
+    let phases: Vec<Vec<I64Vec2>> = (0..4)
+        .map(|_| {
+            let phase = life.live_cells().into_iter().collect();
+            life.step();
+            phase
+        })
+        .collect();
+    let direction = life.live_cells().iter().copied().reduce(I64Vec2::min).unwrap() - start;
+
+    // The eight rotations and reflections, as where they take x and y.
+    let orientations = [(1, 0, 0, 1), (0, -1, 1, 0), (-1, 0, 0, -1), (0, 1, -1, 0)]
+        .into_iter()
+        .flat_map(|(a, b, c, d)| [(a, b, c, d), (-a, -b, c, d)]);
+    orientations
+        .cartesian_product(&phases)
+        .map(|((a, b, c, d), phase)| {
+            let turn = |cell: I64Vec2| I64Vec2::new(a * cell.x + c * cell.y, b * cell.x + d * cell.y);
+            let mut cells: Vec<I64Vec2> = phase.iter().map(|&cell| turn(cell)).collect();
+            let min = cells.iter().copied().reduce(I64Vec2::min).unwrap();
+            cells = cells.into_iter().map(|cell| cell - min).sorted_by_key(|cell| (cell.y, cell.x)).collect();
+            (cells, turn(direction))
+        })
+        .unique()
+        .collect()
+}
+
+/// Finds every glider standing on its own, in any phase and orientation, with no other live
+/// cell touching the box around it. A glider in the middle of a collision isn't counted.
+/// Gliders are listed by their top-left cell, top to bottom.
+pub fn find_gliders(cells: &HashSet<I64Vec2>) -> Vec<Glider> {
+    let masks = masks();
+    let mut gliders: Vec<Glider> = cells
+        .iter()
+        .flat_map(|&cell| {
+            masks.iter().filter_map(move |(mask, direction)| {
+                // Masks start with their top-left cell, so each glider is matched from one cell.
+                let offset = cell - mask[0];
+                let found: Vec<I64Vec2> = mask.iter().map(|&part| part + offset).collect();
+                let max = mask.iter().copied().reduce(I64Vec2::max)? + offset;
+                let is_alone = (offset.y - 1..=max.y + 1)
+                    .cartesian_product(offset.x - 1..=max.x + 1)
+                    .map(|(y, x)| I64Vec2::new(x, y))
+                    .all(|near| cells.contains(&near) == found.contains(&near));
+                is_alone.then_some(Glider { cells: found, direction: *direction })
+            })
+        })
+        .collect();
+    gliders.sort_by_key(|glider| (glider.cells[0].y, glider.cells[0].x));
+    gliders
+}
+
+/// The compass direction a glider heads in, with north up the screen.
+pub fn direction_name(direction: I64Vec2) -> &'static str {
+    match (direction.x > 0, direction.y > 0) {
+        (true, true) => "south-east",
+        (true, false) => "north-east",
+        (false, true) => "south-west",
+        (false, false) => "north-west",
+    }
+}
+
+/// Sums up the gliders found, such as `3 gliders: 2 south-east, 1 north-west`.
+pub fn describe_gliders(gliders: &[Glider]) -> String {
+    let mut by_direction: BTreeMap<&str, usize> = BTreeMap::new();
+    for glider in gliders {
+        *by_direction.entry(direction_name(glider.direction)).or_default() += 1;
+    }
+    if gliders.is_empty() {
+        return "No gliders".to_string();
+    }
+    let noun = if gliders.len() == 1 { "glider" } else { "gliders" };
+    let counts = by_direction.iter().map(|(name, count)| format!("{count} {name}")).join(", ");
+    format!("{} {noun}: {counts}", gliders.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transform::{flip_horizontal, rotate_clockwise};
+
+    #[test]
+    fn knows_every_phase_and_orientation() {
+        let masks = masks();
+        assert_eq!(masks.len(), 16);
+        assert_eq!(masks.iter().filter(|(_, direction)| *direction == I64Vec2::ONE).count(), 4);
+    }
+
+    #[test]
+    fn finds_gliders_heading_each_way() {
+        let south_east = glider();
+        let south_west = flip_horizontal(&glider());
+        let north_east = rotate_clockwise(&rotate_clockwise(&south_west));
+        let mut life = Life::new();
+        for (offset, cells) in [(0, &south_east), (20, &south_west), (40, &north_east)] {
+            life.extend(cells.iter().map(|&cell| cell + I64Vec2::new(offset, 0)));
+        }
+        // A phase later, they are still found, heading the same ways. The one heading north-east
+        // has the highest top row.
+        life.step();
+        let gliders = find_gliders(&life.live_cells());
+        let directions: Vec<&str> = gliders.iter().map(|glider| direction_name(glider.direction)).collect();
+        assert_eq!(directions, ["north-east", "south-east", "south-west"]);
+        assert_eq!(describe_gliders(&gliders), "3 gliders: 1 north-east, 1 south-east, 1 south-west");
+
+        // A block touching a glider hides it, and a lone block is no glider.
+        let block = [I64Vec2::new(3, 0), I64Vec2::new(4, 0), I64Vec2::new(3, 1), I64Vec2::new(4, 1)];
+        let crowded: HashSet<I64Vec2> = south_east.iter().copied().chain(block).collect();
+        assert_eq!(find_gliders(&crowded), []);
+        assert_eq!(describe_gliders(&find_gliders(&block.into_iter().collect())), "No gliders");
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use glam::I64Vec2;
+use crate::rule::Rule;
+
+type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+const MIN_ROOT_LEVEL: u8 = 3;
+/// Once the node table grows past this, the universe is rebuilt from its live cells to drop
+/// nodes and cached results that are no longer reachable.
+const MAX_NODES: usize = 1 << 22;
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    /// Quadrants in `[nw, ne, sw, se]` order. Unused for the two leaf nodes.
+    children: [NodeId; 4],
+    level: u8,
+    population: u64,
+}
+
+/// A HashLife universe: a hash-consed quadtree of macrocells whose memoized results let it
+/// advance regular patterns by huge powers of two at once.
+///
+/// Only two-state rules are supported; Generations rules need the naive engine.
+#[derive(Clone)]
+pub struct HashLife {
+    nodes: Vec<Node>,
+    index: HashMap<[NodeId; 4], NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    empty: Vec<NodeId>,
+    root: NodeId,
+    /// World coordinate of the root's top-left cell.
+    origin: I64Vec2,
+    rule: Rule,
+}
+
+impl HashLife {
+    pub fn new(rule: Rule) -> Self {
+        let leaf = |population| Node { children: [DEAD; 4], level: 0, population };
+        let mut hashlife = Self {
+            nodes: vec![leaf(0), leaf(1)],
+            index: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD],
+            root: DEAD,
+            origin: I64Vec2::ZERO,
+            rule,
+        };
+        hashlife.root = hashlife.empty(MIN_ROOT_LEVEL);
+        hashlife
+    }
+
+    pub fn from_cells(cells: &HashSet<I64Vec2>, rule: Rule) -> Self {
+        let mut hashlife = Self::new(rule);
+        let Some(min) = cells.iter().copied().reduce(I64Vec2::min) else {
+            return hashlife;
+        };
+        let max = cells.iter().copied().reduce(I64Vec2::max).unwrap();
+
+        let size = (max - min).max_element() as u64 + 1;
+        let level = (size.next_power_of_two().trailing_zeros() as u8).max(MIN_ROOT_LEVEL);
+        let cells: Vec<I64Vec2> = cells.iter().copied().collect();
+
+        hashlife.root = hashlife.build(level, min, &cells);
+        hashlife.origin = min;
+        hashlife
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    pub fn population(&self) -> u64 {
+        self.nodes[self.root as usize].population
+    }
+
+    pub fn cells(&self) -> HashSet<I64Vec2> {
+        let mut cells = HashSet::with_capacity(self.population() as usize);
+        self.collect(self.root, self.origin, &mut cells);
+        cells
+    }
+
+    /// Advances the universe by `generations`, one power-of-two jump per set bit.
+    pub fn advance(&mut self, generations: u64) {
+        for j in 0..u64::BITS as u8 {
+            if generations >> j == 0 {
+                break;
+            }
+            if generations >> j & 1 == 1 {
+                self.advance_pow2(j);
+            }
+        }
+    }
+
+    fn advance_pow2(&mut self, j: u8) {
+        // The pattern must sit in the root's central quarter with at least 2^j cells of
+        // margin, so nothing can escape the central half returned by `result`.
+        while self.level(self.root) < j + 3 || !self.is_centred() {
+            self.expand();
+        }
+
+        let level = self.level(self.root);
+        self.root = self.result(self.root, j);
+        self.origin += I64Vec2::splat(1 << (level - 2));
+
+        if self.nodes.len() > MAX_NODES {
+            *self = Self::from_cells(&self.cells(), self.rule);
+        }
+    }
+
+    fn level(&self, id: NodeId) -> u8 {
+        self.nodes[id as usize].level
+    }
+
+    fn children(&self, id: NodeId) -> [NodeId; 4] {
+        self.nodes[id as usize].children
+    }
+
+    fn join(&mut self, children: [NodeId; 4]) -> NodeId {
+        if let Some(&id) = self.index.get(&children) {
+            return id;
+        }
+        let level = self.level(children[0]) + 1;
+        let population = children.iter().map(|&child| self.nodes[child as usize].population).sum();
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(Node { children, level, population });
+        self.index.insert(children, id);
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let below = *self.empty.last().unwrap();
+            let node = self.join([below; 4]);
+            self.empty.push(node);
+        }
+        self.empty[level as usize]
+    }
+
+    fn build(&mut self, level: u8, origin: I64Vec2, cells: &[I64Vec2]) -> NodeId {
+        if cells.is_empty() {
+            return self.empty(level);
+        }
+        if level == 0 {
+            return ALIVE;
+        }
+
+        let half = 1i64 << (level - 1);
+        let mut quadrants: [Vec<I64Vec2>; 4] = Default::default();
+        for &cell in cells {
+            let east = (cell.x >= origin.x + half) as usize;
+            let south = (cell.y >= origin.y + half) as usize;
+            quadrants[south * 2 + east].push(cell);
+        }
+
+        let mut children = [DEAD; 4];
+        for (index, quadrant) in quadrants.iter().enumerate() {
+            let offset = I64Vec2::new((index % 2) as i64, (index / 2) as i64) * half;
+            children[index] = self.build(level - 1, origin + offset, quadrant);
+        }
+        self.join(children)
+    }
+
+    fn collect(&self, id: NodeId, origin: I64Vec2, cells: &mut HashSet<I64Vec2>) {
+        let node = self.nodes[id as usize];
+        if node.population == 0 {
+            return;
+        }
+        if node.level == 0 {
+            cells.insert(origin);
+            return;
+        }
+
+        let half = 1i64 << (node.level - 1);
+        for (index, &child) in node.children.iter().enumerate() {
+            let offset = I64Vec2::new((index % 2) as i64, (index / 2) as i64) * half;
+            self.collect(child, origin + offset, cells);
+        }
+    }
+
+    fn is_centred(&self) -> bool {
+        let [nw, ne, sw, se] = self.children(self.root);
+        let inner = [
+            self.children(self.children(nw)[3])[3],
+            self.children(self.children(ne)[2])[2],
+            self.children(self.children(sw)[1])[1],
+            self.children(self.children(se)[0])[0],
+        ];
+        let inner_population: u64 = inner.iter().map(|&id| self.nodes[id as usize].population).sum();
+        inner_population == self.population()
+    }
+
+    fn expand(&mut self) {
+        let level = self.level(self.root);
+        let e = self.empty(level - 1);
+        let [nw, ne, sw, se] = self.children(self.root);
+
+        let children = [
+            self.join([e, e, e, nw]),
+            self.join([e, e, ne, e]),
+            self.join([e, sw, e, e]),
+            self.join([se, e, e, e]),
+        ];
+        self.root = self.join(children);
+        self.origin -= I64Vec2::splat(1 << (level - 1));
+    }
+
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.children(id);
+        self.join([self.children(nw)[3], self.children(ne)[2], self.children(sw)[1], self.children(se)[0]])
+    }
+
+    /// Returns the centre of a level-`k` node advanced by `2^j` generations, for `j <= k - 2`.
+    fn result(&mut self, id: NodeId, j: u8) -> NodeId {
+        let node = self.nodes[id as usize];
+        debug_assert!(node.level >= 2 && j <= node.level - 2);
+
+        if node.population == 0 {
+            return self.empty(node.level - 1);
+        }
+        if let Some(&result) = self.results.get(&(id, j)) {
+            return result;
+        }
+
+        let result = if node.level == 2 {
+            self.base_case(id)
+        } else {
+            let [nw, ne, sw, se] = node.children;
+            let [_, nw_ne, nw_sw, nw_se] = self.children(nw);
+            let [ne_nw, _, ne_sw, ne_se] = self.children(ne);
+            let [sw_nw, sw_ne, _, sw_se] = self.children(sw);
+            let [se_nw, se_ne, se_sw, _] = self.children(se);
+
+            let nine = [
+                nw,
+                self.join([nw_ne, ne_nw, nw_se, ne_sw]),
+                ne,
+                self.join([nw_sw, nw_se, sw_nw, sw_ne]),
+                self.join([nw_se, ne_sw, sw_ne, se_nw]),
+                self.join([ne_sw, ne_se, se_nw, se_ne]),
+                sw,
+                self.join([sw_ne, se_nw, sw_se, se_sw]),
+                se,
+            ];
+
+            // At full speed each of the two rounds advances by 2^(j-1); otherwise the first
+            // round only recentres and the second does all the stepping.
+            let full_speed = j == node.level - 2;
+            let mut inner = [DEAD; 9];
+            for (index, &sub) in nine.iter().enumerate() {
+                inner[index] = if full_speed { self.result(sub, j - 1) } else { self.centre(sub) };
+            }
+
+            let second_j = if full_speed { j - 1 } else { j };
+            let mut quadrants = [DEAD; 4];
+            for (index, quadrant) in quadrants.iter_mut().enumerate() {
+                let (row, column) = (index / 2, index % 2);
+                let at = |r: usize, c: usize| inner[(row + r) * 3 + column + c];
+                let joined = self.join([at(0, 0), at(0, 1), at(1, 0), at(1, 1)]);
+                *quadrant = self.result(joined, second_j);
+            }
+            self.join(quadrants)
+        };
+
+        self.results.insert((id, j), result);
+        result
+    }
+
+    /// Steps the centre 2x2 of a 4x4 node by one generation.
+    fn base_case(&mut self, id: NodeId) -> NodeId {
+        let mut grid = [[false; 4]; 4];
+        for (quadrant, &child) in self.children(id).iter().enumerate() {
+            for (index, &leaf) in self.children(child).iter().enumerate() {
+                let x = (quadrant % 2) * 2 + index % 2;
+                let y = (quadrant / 2) * 2 + index / 2;
+                grid[y][x] = leaf == ALIVE;
+            }
+        }
+
+        let mut leaves = [DEAD; 4];
+        for (index, leaf) in leaves.iter_mut().enumerate() {
+            let (x, y) = (1 + index % 2, 1 + index / 2);
+            let neighbours = (y - 1..=y + 1)
+                .flat_map(|ny| (x - 1..=x + 1).map(move |nx| (nx, ny)))
+                .filter(|&(nx, ny)| (nx, ny) != (x, y) && grid[ny][nx])
+                .count();
+            let alive = if grid[y][x] { self.rule.survival[neighbours] } else { self.rule.birth[neighbours] };
+            *leaf = if alive { ALIVE } else { DEAD };
+        }
+        self.join(leaves)
+    }
+}
+
+impl fmt::Debug for HashLife {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HashLife")
+            .field("population", &self.population())
+            .field("level", &self.level(self.root))
+            .field("origin", &self.origin)
+            .field("nodes", &self.nodes.len())
+            .field("rule", &self.rule)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+    use crate::life::Life;
+    use crate::patterns::{glider, gosper_glider_gun, pulsar};
+
+    fn naive(cells: &[I64Vec2], rule: Rule, generations: u64) -> HashSet<I64Vec2> {
+        let mut life = Life::from_cells(cells.iter().copied().collect());
+        life.set_rule(rule);
+        for _ in 0..generations {
+            life.step();
+        }
+        life.live_cells()
+    }
+
+    fn hashlife(cells: &[I64Vec2], rule: Rule, generations: u64) -> HashSet<I64Vec2> {
+        let mut hashlife = HashLife::from_cells(&cells.iter().copied().collect(), rule);
+        hashlife.advance(generations);
+        hashlife.cells()
+    }
+
+    #[test]
+    fn round_trips_cells() {
+        let cells: HashSet<I64Vec2> = pulsar().into_iter().map(|c| c - I64Vec2::new(100, -7)).collect();
+        let hashlife = HashLife::from_cells(&cells, Rule::CONWAY);
+        assert_eq!(hashlife.population(), 48);
+        assert_eq!(hashlife.cells(), cells);
+
+        assert!(HashLife::from_cells(&HashSet::new(), Rule::CONWAY).cells().is_empty());
+    }
+
+    #[test]
+    fn matches_naive_engine_on_glider_and_pulsar() {
+        for generations in [0, 1, 2, 3, 5, 8, 13, 64, 100] {
+            assert_eq!(
+                hashlife(&glider(), Rule::CONWAY, generations),
+                naive(&glider(), Rule::CONWAY, generations),
+                "glider after {generations} generations",
+            );
+            assert_eq!(
+                hashlife(&pulsar(), Rule::CONWAY, generations),
+                naive(&pulsar(), Rule::CONWAY, generations),
+                "pulsar after {generations} generations",
+            );
+        }
+    }
+
+    #[test]
+    fn repeated_single_steps_match_naive_engine() {
+        let rule = Rule::parse("B36/S23").unwrap();
+        let mut universe = HashLife::from_cells(&glider().into_iter().collect(), rule);
+        let mut life = Life::from_cells(glider().into_iter().collect());
+        life.set_rule(rule);
+
+        for _ in 0..40 {
+            universe.advance(1);
+            life.step();
+            assert_eq!(universe.cells(), life.live_cells());
+        }
+    }
+
+    #[test]
+    fn fast_forwards_gosper_gun_faster_than_naive() {
+        let start = Instant::now();
+        let fast = hashlife(&gosper_glider_gun(), Rule::CONWAY, 1024);
+        let hashlife_time = start.elapsed();
+
+        let start = Instant::now();
+        let slow = naive(&gosper_glider_gun(), Rule::CONWAY, 1024);
+        let naive_time = start.elapsed();
+
+        assert_eq!(fast, slow);
+        assert!(hashlife_time < naive_time, "hashlife {hashlife_time:?} vs naive {naive_time:?}");
+    }
+}
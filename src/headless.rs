@@ -0,0 +1,218 @@
+use std::convert::Infallible;
+use std::fmt;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use glam::I64Vec2;
+use crate::life::{bounding_box, Engine, Life};
+
+/// How many generations `--headless` runs unless `--steps` says otherwise.
+pub const DEFAULT_HEADLESS_STEPS: u64 = 1000;
+
+/// Where a headless run ended up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Report {
+    pub generation: u64,
+    pub population: usize,
+    pub bounding_box: Option<(I64Vec2, I64Vec2)>,
+    /// Whether the run stopped early because a step left the board unchanged.
+    pub stabilized: bool,
+}
+
+/// Steps the board up to `steps` times with the incremental engine, stopping as soon as a
+/// step changes nothing.
+pub fn simulate(life: &mut Life, steps: u64) -> Report {
+    let Ok(report) = simulate_each(life, steps, |_| Ok::<_, Infallible>(()));
+    report
+}
+
+/// The header of the table [`simulate_csv`] writes.
+pub const CSV_HEADER: &str = "generation,population,births,deaths";
+
+/// Like [`simulate`], but writes a CSV row for the starting board and each step after it to
+/// `out`, flushed as it goes so a live plotter can read along.
+pub fn simulate_csv(life: &mut Life, steps: u64, out: &mut impl Write) -> io::Result<Report> {
+    writeln!(out, "{CSV_HEADER}")?;
+    let mut write_row = |life: &Life| {
+        let stats = life.last_step();
+        writeln!(out, "{},{},{},{}", life.generation(), life.population(), stats.births, stats.deaths)?;
+        out.flush()
+    };
+    write_row(life)?;
+    simulate_each(life, steps, write_row)
+}
+
+/// Runs [`simulate`], calling `on_step` after every step and giving up on its first error.
+fn simulate_each<E>(
+    life: &mut Life,
+    steps: u64,
+    mut on_step: impl FnMut(&Life) -> Result<(), E>,
+) -> Result<Report, E> {
+    life.set_engine(Engine::Incremental);
+    let mut stabilized = false;
+    for _ in 0..steps {
+        life.step();
+        on_step(life)?;
+        if life.is_stable() {
+            stabilized = true;
+            break;
+        }
+    }
+
+    Ok(Report {
+        generation: life.generation(),
+        population: life.population(),
+        bounding_box: bounding_box(&life.live_cells()),
+        stabilized,
+    })
+}
+
+/// A jump many generations ahead, run a slice at a time so the window can show progress and
+/// cancel it part way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jump {
+    pub from: u64,
+    pub to: u64,
+}
+
+impl Jump {
+    pub fn new(life: &Life, generations: u64) -> Self {
+        Self { from: life.generation(), to: life.generation().saturating_add(generations) }
+    }
+
+    /// Steps the board towards the end of the jump for about `budget`, returning whether the
+    /// jump is over, either arrived or cut short by the board no longer changing. `stepped` sees
+    /// the board after each generation, except that HashLife covers the whole jump at once.
+    pub fn run(&self, life: &mut Life, budget: Duration, mut stepped: impl FnMut(&Life)) -> bool {
+        if life.uses_hashlife() {
+            life.advance(self.to.saturating_sub(life.generation()));
+            stepped(life);
+            return true;
+        }
+        let started = Instant::now();
+        while life.generation() < self.to {
+            life.step();
+            stepped(life);
+            if life.is_stable() {
+                return true;
+            }
+            if started.elapsed() >= budget {
+                break;
+            }
+        }
+        life.generation() >= self.to
+    }
+
+    /// How far through the jump the board is, from 0 to 1. A board stepped back to before the
+    /// jump started is at 0.
+    pub fn progress(&self, life: &Life) -> f64 {
+        life.generation().saturating_sub(self.from) as f64 / (self.to - self.from).max(1) as f64
+    }
+}
+
+/// Parses the generation to run to from `current`: either an absolute generation, or a number
+/// of steps prefixed with `+`. Only generations still ahead count.
+pub fn parse_target(text: &str, current: u64) -> Option<u64> {
+    let text = text.trim();
+    let target = match text.strip_prefix('+') {
+        Some(steps) => current.checked_add(steps.trim().parse().ok()?)?,
+        None => text.parse().ok()?,
+    };
+    (target > current).then_some(target)
+}
+
+/// Writes one `key: value` line per field, for scripts to read.
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "generation: {}", self.generation)?;
+        writeln!(f, "population: {}", self.population)?;
+        match self.bounding_box {
+            Some((min, max)) => writeln!(f, "bounding box: ({}, {}) to ({}, {})", min.x, min.y, max.x, max.y)?,
+            None => writeln!(f, "bounding box: none")?,
+        }
+        write!(f, "stabilized: {}", self.stabilized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::GenerationHistory;
+    use crate::patterns::glider;
+
+    #[test]
+    fn runs_the_requested_steps() {
+        let mut life = Life::from_cells(glider().into_iter().collect());
+        let report = simulate(&mut life, 8);
+        assert_eq!(report.generation, 8);
+        assert_eq!(report.population, 5);
+        assert!(!report.stabilized);
+        assert_eq!(report, simulate(&mut life, 0));
+    }
+
+    #[test]
+    fn stops_once_stable() {
+        // Three corners of a block fill in the fourth, and the next step confirms it is still.
+        let cells = [I64Vec2::new(0, 0), I64Vec2::new(1, 0), I64Vec2::new(0, 1)];
+        let mut life = Life::from_cells(cells.into_iter().collect());
+        let report = simulate(&mut life, 100);
+        assert_eq!(report.generation, 2);
+        assert!(report.stabilized);
+        assert_eq!(report.bounding_box, Some((I64Vec2::ZERO, I64Vec2::ONE)));
+        assert_eq!(
+            report.to_string(),
+            "generation: 2\npopulation: 4\nbounding box: (0, 0) to (1, 1)\nstabilized: true"
+        );
+    }
+
+    #[test]
+    fn writes_a_csv_row_per_step() {
+        let mut blinker = Life::from_cells([I64Vec2::new(-1, 0), I64Vec2::ZERO, I64Vec2::X].into_iter().collect());
+        let mut out = Vec::new();
+        let report = simulate_csv(&mut blinker, 2, &mut out).unwrap();
+        assert_eq!(report.generation, 2);
+        assert_eq!(String::from_utf8(out).unwrap(), format!("{CSV_HEADER}\n0,3,0,0\n1,3,2,2\n2,3,2,2\n"));
+    }
+
+    #[test]
+    fn parses_absolute_and_relative_targets() {
+        assert_eq!(parse_target("1000", 10), Some(1000));
+        assert_eq!(parse_target(" +50 ", 10), Some(60));
+        assert_eq!(parse_target("10", 10), None);
+        assert_eq!(parse_target("+0", 10), None);
+        assert_eq!(parse_target(&format!("+{}", u64::MAX), 10), None);
+        for malformed in ["", "+", "-5", "ten", "1e3"] {
+            assert_eq!(parse_target(malformed, 0), None, "{malformed:?}");
+        }
+    }
+
+    #[test]
+    fn jumps_in_slices() {
+        let mut life = Life::from_cells(glider().into_iter().collect());
+        life.set_engine(Engine::Incremental);
+        let jump = Jump::new(&life, 40);
+        let mut seen = Vec::new();
+        while !jump.run(&mut life, Duration::ZERO, |life| seen.push(life.generation())) {
+            assert!(jump.progress(&life) < 1.0);
+        }
+        assert_eq!(life.generation(), 40);
+        assert_eq!(seen, (1..=40).collect::<Vec<_>>());
+        assert_eq!(jump.progress(&life), 1.0);
+
+        let mut hashlife = Life::from_cells(glider().into_iter().collect());
+        hashlife.set_engine(Engine::HashLife);
+        assert!(Jump::new(&hashlife, 40).run(&mut hashlife, Duration::ZERO, |_| {}));
+        assert_eq!(hashlife.generation(), 40);
+        assert_eq!(hashlife.live_cells(), life.live_cells());
+    }
+
+    #[test]
+    fn progress_stays_at_zero_before_the_jump() {
+        let mut life = Life::from_cells(glider().into_iter().collect());
+        let mut generations = GenerationHistory::new(10);
+        generations.step(&mut life);
+        let jump = Jump::new(&life, 40);
+        generations.step_back(&mut life);
+        assert_eq!(life.generation(), 0);
+        assert_eq!(jump.progress(&life), 0.0);
+    }
+}
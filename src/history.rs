@@ -0,0 +1,576 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use glam::I64Vec2;
+use crate::life::{Life, ALIVE};
+use crate::selection::CellRect;
+
+/// A manual edit, storing enough of the previous board to reverse it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit {
+    Toggle { cell: I64Vec2, previous: u8 },
+    /// The live cells are kept with their ages, as clearing also starts the generations over.
+    Clear { previous: HashMap<I64Vec2, u8>, generation: u64, ages: Vec<(I64Vec2, u32)> },
+    Paste { previous: Vec<(I64Vec2, u8)> },
+    Replace { previous: Vec<(I64Vec2, u8)>, next: Vec<(I64Vec2, u8)> },
+}
+
+impl Edit {
+    fn apply(&self, life: &mut Life) {
+        match self {
+            Edit::Toggle { cell, .. } => life.toggle(*cell),
+            Edit::Clear { .. } => life.clear(),
+            Edit::Paste { previous } => life.extend(previous.iter().map(|&(cell, _)| cell)),
+            Edit::Replace { next, .. } => {
+                for &(cell, state) in next {
+                    life.set_state(cell, state);
+                }
+            }
+        }
+    }
+
+    fn revert(&self, life: &mut Life) {
+        match self {
+            Edit::Toggle { cell, previous } => life.set_state(*cell, *previous),
+            Edit::Clear { previous, generation, ages } => {
+                life.set_generation(*generation);
+                for (&cell, &state) in previous {
+                    life.set_state(cell, state);
+                }
+                for &(cell, age) in ages {
+                    life.set_age(cell, age);
+                }
+            }
+            Edit::Paste { previous } | Edit::Replace { previous, .. } => {
+                for &(cell, state) in previous {
+                    life.set_state(cell, state);
+                }
+            }
+        }
+    }
+
+    /// The cells the edit may change.
+    fn cells(&self) -> Vec<I64Vec2> {
+        match self {
+            Edit::Toggle { cell, .. } => vec![*cell],
+            Edit::Clear { previous, .. } => previous.keys().copied().collect(),
+            Edit::Paste { previous } | Edit::Replace { previous, .. } => {
+                previous.iter().map(|&(cell, _)| cell).collect()
+            }
+        }
+    }
+}
+
+/// An edit with the colours its live cells had before and after it under a colouring, as
+/// cells an edit places always get the paint colour.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Recorded {
+    edit: Edit,
+    before: HashMap<I64Vec2, u8>,
+    after: HashMap<I64Vec2, u8>,
+}
+
+impl Recorded {
+    fn apply(&self, life: &mut Life) {
+        self.edit.apply(life);
+        repaint(life, &self.after);
+    }
+
+    fn revert(&self, life: &mut Life) {
+        self.edit.revert(life);
+        repaint(life, &self.before);
+    }
+}
+
+/// The colours of those of the cells that are alive, or none without a colouring.
+fn colours(life: &Life, cells: impl IntoIterator<Item = I64Vec2>) -> HashMap<I64Vec2, u8> {
+    if life.colouring().is_none() {
+        return HashMap::new();
+    }
+    cells.into_iter().filter(|&cell| life.is_alive(cell)).map(|cell| (cell, life.colour(cell))).collect()
+}
+
+fn repaint(life: &mut Life, colours: &HashMap<I64Vec2, u8>) {
+    for (&cell, &colour) in colours {
+        life.set_colour(cell, colour);
+    }
+}
+
+/// Undo and redo stacks for manual edits. Simulation steps are not recorded.
+///
+/// Edits go through the history so they are applied and recorded together. Once more than
+/// `capacity` edits are recorded the oldest is forgotten.
+#[derive(Debug, Clone)]
+pub struct EditHistory {
+    undo: VecDeque<Recorded>,
+    redo: Vec<Recorded>,
+    capacity: usize,
+    /// The cells of the stroke still being drawn, when the latest edit is one that further
+    /// strokes extend.
+    open_stroke: Option<HashSet<I64Vec2>>,
+}
+
+impl EditHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self { undo: VecDeque::new(), redo: Vec::new(), capacity, open_stroke: None }
+    }
+
+    pub fn toggle(&mut self, life: &mut Life, cell: I64Vec2) {
+        self.record(life, Edit::Toggle { cell, previous: life.state(cell) });
+    }
+
+    /// Clears the whole board as a single undoable edit.
+    pub fn clear(&mut self, life: &mut Life) {
+        if !life.cells().is_empty() {
+            let ages = life.live_cells().into_iter().map(|cell| (cell, life.age(cell))).collect();
+            self.record(life, Edit::Clear { previous: life.cells().clone(), generation: life.generation(), ages });
+        }
+    }
+
+    /// Brings the cells to life on top of the existing board.
+    pub fn paste(&mut self, life: &mut Life, cells: impl IntoIterator<Item = I64Vec2>) {
+        let mut previous: Vec<(I64Vec2, u8)> = cells.into_iter().map(|cell| (cell, life.state(cell))).collect();
+        previous.sort_by_key(|&(cell, _)| (cell.y, cell.x));
+        previous.dedup_by_key(|&mut (cell, _)| cell);
+        if !previous.is_empty() {
+            self.record(life, Edit::Paste { previous });
+        }
+    }
+
+    /// Kills the `removed` cells and brings the `added` ones to life as a single edit, so a
+    /// region can be rewritten in place.
+    pub fn replace(&mut self, life: &mut Life, removed: &[I64Vec2], added: &[I64Vec2]) {
+        let mut next: HashMap<I64Vec2, u8> = removed.iter().map(|&cell| (cell, 0)).collect();
+        next.extend(added.iter().map(|&cell| (cell, ALIVE)));
+
+        let mut next: Vec<(I64Vec2, u8)> =
+            next.into_iter().filter(|&(cell, state)| life.state(cell) != state).collect();
+        next.sort_by_key(|&(cell, _)| (cell.y, cell.x));
+        if !next.is_empty() {
+            let previous = next.iter().map(|&(cell, _)| (cell, life.state(cell))).collect();
+            self.record(life, Edit::Replace { previous, next });
+        }
+    }
+
+    /// Kills every non-dead cell inside the box as a single edit, leaving the rest of the
+    /// board alone.
+    pub fn erase(&mut self, life: &mut Life, rect: &CellRect) {
+        let removed: Vec<I64Vec2> = life.cells().keys().copied().filter(|&cell| rect.contains(cell)).collect();
+        self.replace(life, &removed, &[]);
+    }
+
+    /// Flips every cell on the board inside the box as a single edit, so non-dead cells die
+    /// and dead cells come alive. Every cell of the box is visited, so callers should keep
+    /// it to a sensible size.
+    pub fn invert(&mut self, life: &mut Life, rect: &CellRect) {
+        let (removed, added): (Vec<I64Vec2>, Vec<I64Vec2>) = (rect.min.y..=rect.max.y)
+            .flat_map(|y| (rect.min.x..=rect.max.x).map(move |x| I64Vec2::new(x, y)))
+            .filter(|&cell| life.contains(cell))
+            .partition(|&cell| life.state(cell) != 0);
+        self.replace(life, &removed, &added);
+    }
+
+    /// Sets the cells to `state` as part of the open stroke, so a whole mouse drag undoes as
+    /// one edit. The stroke stays open until [`EditHistory::end_stroke`] or any other edit.
+    pub fn stroke(&mut self, life: &mut Life, cells: &[I64Vec2], state: u8) {
+        let mut seen = HashSet::new();
+        let changes: Vec<I64Vec2> = cells
+            .iter()
+            .copied()
+            .filter(|&cell| life.contains(cell) && life.state(cell) != state && seen.insert(cell))
+            .collect();
+        if changes.is_empty() {
+            return;
+        }
+
+        if let Some(painted) = &mut self.open_stroke
+            && let Some(Recorded { edit: Edit::Replace { previous, next }, before, after }) = self.undo.back_mut()
+        {
+            for cell in changes {
+                if painted.insert(cell) {
+                    previous.push((cell, life.state(cell)));
+                    next.push((cell, state));
+                    before.extend(colours(life, [cell]));
+                } else if let Some(entry) = next.iter_mut().find(|(painted, _)| *painted == cell) {
+                    // The simulation or another button changed a cell this stroke already painted.
+                    entry.1 = state;
+                }
+                life.set_state(cell, state);
+                after.remove(&cell);
+                after.extend(colours(life, [cell]));
+            }
+            return;
+        }
+
+        let previous = changes.iter().map(|&cell| (cell, life.state(cell))).collect();
+        let next = changes.iter().map(|&cell| (cell, state)).collect();
+        self.record(life, Edit::Replace { previous, next });
+        self.open_stroke = Some(seen);
+    }
+
+    pub fn end_stroke(&mut self) {
+        self.open_stroke = None;
+    }
+
+    /// Reverts the latest edit, returning whether there was one.
+    pub fn undo(&mut self, life: &mut Life) -> bool {
+        self.open_stroke = None;
+        let Some(edit) = self.undo.pop_back() else {
+            return false;
+        };
+        edit.revert(life);
+        self.redo.push(edit);
+        true
+    }
+
+    /// Reapplies the latest undone edit, returning whether there was one.
+    pub fn redo(&mut self, life: &mut Life) -> bool {
+        let Some(edit) = self.redo.pop() else {
+            return false;
+        };
+        edit.apply(life);
+        self.undo.push_back(edit);
+        true
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    fn record(&mut self, life: &mut Life, edit: Edit) {
+        self.open_stroke = None;
+        let cells = edit.cells();
+        let before = colours(life, cells.iter().copied());
+        edit.apply(life);
+        let after = colours(life, cells);
+        self.redo.clear();
+        self.undo.push_back(Recorded { edit, before, after });
+        while self.undo.len() > self.capacity {
+            self.undo.pop_front();
+        }
+    }
+}
+
+/// A board state packed into exact-size slices, without the hash table's spare capacity.
+/// Decaying cells are kept apart so two-state rules only pay for positions, and colours are
+/// only kept under a colouring.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    generation: u64,
+    alive: Box<[I64Vec2]>,
+    decaying: Box<[(I64Vec2, u8)]>,
+    colours: Box<[(I64Vec2, u8)]>,
+}
+
+impl Snapshot {
+    pub fn capture(life: &Life) -> Self {
+        let (alive, decaying): (Vec<_>, Vec<_>) = life.cells().iter().partition(|&(_, &state)| state == ALIVE);
+        Self {
+            generation: life.generation(),
+            alive: alive.into_iter().map(|(&cell, _)| cell).collect(),
+            decaying: decaying.into_iter().map(|(&cell, &state)| (cell, state)).collect(),
+            colours: colours(life, life.cells().keys().copied()).into_iter().collect(),
+        }
+    }
+
+    /// Puts the cells, their colours and the generation back, keeping the board's rule, engine,
+    /// torus and colouring.
+    pub fn restore(&self, life: &mut Life) {
+        life.clear();
+        life.extend(self.alive.iter().copied());
+        for &(cell, state) in &self.decaying {
+            life.set_state(cell, state);
+        }
+        for &(cell, colour) in &self.colours {
+            life.set_colour(cell, colour);
+        }
+        life.set_generation(self.generation);
+    }
+}
+
+/// A ring buffer of the boards before recent generations, for stepping backwards.
+///
+/// Only the past is kept: stepping forward again after going back records a new past, so the
+/// abandoned future is simply recomputed.
+#[derive(Debug, Clone)]
+pub struct GenerationHistory {
+    snapshots: VecDeque<Snapshot>,
+    depth: usize,
+}
+
+impl GenerationHistory {
+    pub fn new(depth: usize) -> Self {
+        Self { snapshots: VecDeque::with_capacity(depth), depth }
+    }
+
+    /// Steps the board forward one generation, remembering the state it came from.
+    pub fn step(&mut self, life: &mut Life) {
+        self.record(life);
+        life.step();
+    }
+
+    /// Remembers the board as the state before a step made elsewhere, such as on a worker thread.
+    pub fn record(&mut self, life: &Life) {
+        if self.depth > 0 {
+            if self.snapshots.len() == self.depth {
+                self.snapshots.pop_front();
+            }
+            self.snapshots.push_back(Snapshot::capture(life));
+        }
+    }
+
+    /// Restores the board from before the latest recorded step, returning whether there was one.
+    pub fn step_back(&mut self, life: &mut Life) -> bool {
+        let Some(snapshot) = self.snapshots.pop_back() else {
+            return false;
+        };
+        snapshot.restore(life);
+        true
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::Colouring;
+    use crate::rule::Rule;
+
+    fn cells(points: &[(i64, i64)]) -> Vec<I64Vec2> {
+        points.iter().map(|&(x, y)| I64Vec2::new(x, y)).collect()
+    }
+
+    #[test]
+    fn undoes_and_redoes_toggles() {
+        let mut life = Life::new();
+        let mut history = EditHistory::new(10);
+        let cell = I64Vec2::new(2, 3);
+
+        history.toggle(&mut life, cell);
+        assert!(life.is_alive(cell));
+        assert!(history.undo(&mut life));
+        assert!(!life.is_alive(cell));
+        assert!(!history.undo(&mut life));
+
+        assert!(history.redo(&mut life));
+        assert!(life.is_alive(cell));
+        assert!(!history.redo(&mut life));
+    }
+
+    #[test]
+    fn undoing_a_clear_restores_every_cell() {
+        let mut life = Life::from_cells(cells(&[(0, 0), (1, 0), (2, 0)]).into_iter().collect());
+        life.set_rule(Rule::parse("B3/S23/C3").unwrap());
+        life.set_state(I64Vec2::new(5, 5), 2);
+        let before = life.clone();
+        let mut history = EditHistory::new(10);
+
+        history.clear(&mut life);
+        assert!(life.cells().is_empty());
+        history.undo(&mut life);
+        assert_eq!(life, before);
+    }
+
+    #[test]
+    fn undoing_a_clear_restores_the_generation_and_ages() {
+        let mut life = Life::from_cells(cells(&[(0, 0), (1, 0), (0, 1), (1, 1)]).into_iter().collect());
+        for _ in 0..500 {
+            life.step();
+        }
+        life.insert(I64Vec2::new(9, 9));
+        let mut history = EditHistory::new(10);
+
+        history.clear(&mut life);
+        assert_eq!(life.generation(), 0);
+        history.undo(&mut life);
+        assert_eq!(life.generation(), 500);
+        assert_eq!(life.age(I64Vec2::ZERO), 501);
+        assert_eq!(life.age(I64Vec2::new(9, 9)), 1);
+        history.redo(&mut life);
+        assert!(life.cells().is_empty());
+    }
+
+    #[test]
+    fn paste_unions_and_undo_keeps_existing_cells() {
+        let mut life = Life::from_cells(cells(&[(0, 0)]).into_iter().collect());
+        let before = life.clone();
+        let mut history = EditHistory::new(10);
+
+        history.paste(&mut life, cells(&[(0, 0), (1, 0)]));
+        assert_eq!(life.population(), 2);
+        history.undo(&mut life);
+        assert_eq!(life, before);
+    }
+
+    #[test]
+    fn replace_moves_cells_as_one_edit() {
+        let mut life = Life::from_cells(cells(&[(0, 0), (1, 0), (5, 5)]).into_iter().collect());
+        let before = life.clone();
+        let mut history = EditHistory::new(10);
+
+        history.replace(&mut life, &cells(&[(0, 0), (1, 0)]), &cells(&[(0, 0), (0, 1)]));
+        assert_eq!(life.live_cells(), cells(&[(0, 0), (0, 1), (5, 5)]).into_iter().collect());
+
+        history.undo(&mut life);
+        assert_eq!(life, before);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn erase_only_clears_inside_the_box() {
+        let mut life = Life::from_cells(cells(&[(0, 0), (2, 2), (3, 0)]).into_iter().collect());
+        life.set_rule(Rule::parse("B3/S23/C3").unwrap());
+        life.set_state(I64Vec2::new(1, 1), 2);
+        let before = life.clone();
+        let mut history = EditHistory::new(10);
+
+        history.erase(&mut life, &CellRect::from_corners(I64Vec2::ZERO, I64Vec2::new(2, 2)));
+        assert_eq!(life.cells().keys().copied().collect::<Vec<_>>(), cells(&[(3, 0)]));
+        history.undo(&mut life);
+        assert_eq!(life, before);
+
+        history.erase(&mut life, &CellRect::from_corners(I64Vec2::splat(10), I64Vec2::splat(20)));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn invert_flips_the_box_and_undoes() {
+        let mut life = Life::from_cells(cells(&[(0, 0), (1, 1), (9, 9)]).into_iter().collect());
+        let before = life.clone();
+        let mut history = EditHistory::new(10);
+
+        history.invert(&mut life, &CellRect::from_corners(I64Vec2::ZERO, I64Vec2::ONE));
+        assert_eq!(life.live_cells(), cells(&[(1, 0), (0, 1), (9, 9)]).into_iter().collect());
+        history.undo(&mut life);
+        assert_eq!(life, before);
+    }
+
+    #[test]
+    fn a_stroke_undoes_as_one_edit() {
+        let mut life = Life::from_cells(cells(&[(9, 9)]).into_iter().collect());
+        let before = life.clone();
+        let mut history = EditHistory::new(10);
+
+        history.stroke(&mut life, &cells(&[(0, 0), (1, 0), (1, 0)]), ALIVE);
+        history.stroke(&mut life, &cells(&[(1, 0), (2, 1)]), ALIVE);
+        history.stroke(&mut life, &cells(&[(9, 9), (0, 0)]), 0);
+        assert_eq!(life.live_cells(), cells(&[(1, 0), (2, 1)]).into_iter().collect());
+        history.end_stroke();
+        history.stroke(&mut life, &cells(&[(5, 5)]), ALIVE);
+
+        assert!(history.undo(&mut life));
+        assert_eq!(life.live_cells(), cells(&[(1, 0), (2, 1)]).into_iter().collect());
+        assert!(history.undo(&mut life));
+        assert_eq!(life, before);
+        assert!(!history.undo(&mut life));
+        history.redo(&mut life);
+        assert_eq!(life.live_cells(), cells(&[(1, 0), (2, 1)]).into_iter().collect());
+    }
+
+    #[test]
+    fn steps_back_to_earlier_generations() {
+        let glider = Life::from_cells(cells(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]).into_iter().collect());
+        let mut life = glider.clone();
+        let mut history = GenerationHistory::new(10);
+
+        history.step(&mut life);
+        let first = life.clone();
+        history.step(&mut life);
+        assert_eq!(life.generation(), 2);
+
+        assert!(history.step_back(&mut life));
+        assert_eq!(life, first);
+        assert_eq!(life.generation(), 1);
+        assert!(history.step_back(&mut life));
+        assert_eq!(life, glider);
+        assert_eq!(life.generation(), 0);
+        assert!(!history.step_back(&mut life));
+    }
+
+    #[test]
+    fn generation_history_is_bounded_and_keeps_decaying_cells() {
+        let mut life = Life::from_cells(cells(&[(0, 0), (1, 0)]).into_iter().collect());
+        life.set_rule(Rule::parse("B2/S/C3").unwrap());
+        let mut history = GenerationHistory::new(2);
+
+        for _ in 0..5 {
+            history.step(&mut life);
+        }
+        assert_eq!(history.len(), 2);
+
+        let mut replay = life.clone();
+        history.step_back(&mut replay);
+        history.step(&mut replay);
+        assert_eq!(replay, life);
+        assert!(!GenerationHistory::new(0).step_back(&mut life));
+
+        let mut decaying = Life::from_cells(cells(&[(0, 0), (1, 0)]).into_iter().collect());
+        decaying.set_rule(Rule::parse("B2/S/C3").unwrap());
+        decaying.step();
+        let before = decaying.clone();
+        history.step(&mut decaying);
+        history.step_back(&mut decaying);
+        assert_eq!(decaying, before);
+    }
+
+    #[test]
+    fn stepping_back_and_undoing_keep_colours() {
+        let mut life = Life::new();
+        life.set_colouring(Some(Colouring::Immigration));
+        life.extend(cells(&[(0, 0), (1, 0)]));
+        life.set_paint_colour(2);
+        life.insert(I64Vec2::new(2, 0));
+        let colours = |life: &Life| -> Vec<u8> {
+            cells(&[(0, 0), (1, 0), (2, 0)]).into_iter().map(|cell| life.colour(cell)).collect()
+        };
+        let mut generations = GenerationHistory::new(10);
+
+        generations.step(&mut life);
+        assert_eq!(life.colour(I64Vec2::new(1, 1)), 1);
+        assert!(generations.step_back(&mut life));
+        assert_eq!(colours(&life), vec![1, 1, 2]);
+
+        let mut history = EditHistory::new(10);
+        life.set_paint_colour(1);
+        history.toggle(&mut life, I64Vec2::new(2, 0));
+        history.clear(&mut life);
+        history.undo(&mut life);
+        history.undo(&mut life);
+        assert_eq!(colours(&life), vec![1, 1, 2]);
+
+        life.set_paint_colour(2);
+        history.stroke(&mut life, &cells(&[(3, 0)]), ALIVE);
+        history.end_stroke();
+        life.set_paint_colour(1);
+        history.undo(&mut life);
+        history.redo(&mut life);
+        assert_eq!(life.colour(I64Vec2::new(3, 0)), 2);
+    }
+
+    #[test]
+    fn new_edits_discard_redo_and_capacity_drops_oldest() {
+        let mut life = Life::new();
+        let mut history = EditHistory::new(2);
+
+        for x in 0..3 {
+            history.toggle(&mut life, I64Vec2::new(x, 0));
+        }
+        assert!(history.undo(&mut life));
+        assert!(history.undo(&mut life));
+        assert!(!history.undo(&mut life));
+        assert_eq!(life.live_cells(), cells(&[(0, 0)]).into_iter().collect());
+
+        history.redo(&mut life);
+        history.toggle(&mut life, I64Vec2::new(9, 9));
+        assert!(!history.can_redo());
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::{HashMap, HashSet};
+use std::iter;
+use glam::I64Vec2;
+use crate::kernel::Kernel;
+use crate::life::{convolve, next_state, table_bytes, ALIVE};
+use crate::rule::Rule;
+use crate::torus::Torus;
+
+/// Live neighbour counts kept up to date between generations.
+///
+/// A cell can only change if it or one of its neighbours changed in the step before, so each
+/// step revisits just those cells and adjusts the counts around the ones that were born or
+/// died. The work follows the active frontier rather than the whole population.
+#[derive(Debug, Clone)]
+pub struct NeighbourCounts {
+    counts: HashMap<I64Vec2, usize>,
+    /// The cells whose state changed in the last step, or `None` before the first one.
+    changed: Option<Vec<I64Vec2>>,
+    visited: usize,
+}
+
+impl NeighbourCounts {
+    pub fn new(cells: &HashMap<I64Vec2, u8>, kernel: &Kernel, torus: Option<&Torus>) -> Self {
+        Self { counts: convolve(cells, kernel, torus), changed: None, visited: 0 }
+    }
+
+    /// Roughly how much memory the counts take.
+    pub fn memory_bytes(&self) -> usize {
+        table_bytes(&self.counts)
+    }
+
+    /// Advances `cells` by one generation in place, returning the cells whose state changed.
+    ///
+    /// The cells must be the ones these counts were built from or last stepped, and the rule
+    /// and torus must stay the same between steps. The rule's kernel must be the one the counts
+    /// were built with.
+    pub fn step(&mut self, cells: &mut HashMap<I64Vec2, u8>, rule: &Rule, torus: Option<&Torus>) -> &[I64Vec2] {
+        let kernel = rule.kernel();
+        let deltas = || kernel.entries().iter().map(|&(delta, _)| delta);
+        // Neighbours past a wall don't exist, so they're neither counted nor visited.
+        let neighbour =
+            |cell: I64Vec2, delta: I64Vec2| torus.map_or(Some(cell + delta), |torus| torus.neighbour(cell + delta));
+
+        let candidates: HashSet<I64Vec2> = match &self.changed {
+            None => cells.keys().chain(self.counts.keys()).copied().collect(),
+            Some(changed) => changed
+                .iter()
+                .flat_map(|&cell| iter::once(cell).chain(deltas().filter_map(move |delta| neighbour(cell, delta))))
+                .collect(),
+        };
+        self.visited = candidates.len();
+
+        let transitions: Vec<(I64Vec2, u8)> = candidates
+            .into_iter()
+            .filter_map(|cell| {
+                let state = cells.get(&cell).copied().unwrap_or(0);
+                let next = next_state(rule, state, self.counts.get(&cell).copied().unwrap_or(0));
+                (next != state).then_some((cell, next))
+            })
+            .collect();
+
+        for &(cell, next) in &transitions {
+            let previous = if next == 0 { cells.remove(&cell) } else { cells.insert(cell, next) };
+            let was_alive = previous == Some(ALIVE);
+            if was_alive == (next == ALIVE) {
+                continue;
+            }
+            for &(delta, weight) in kernel.entries() {
+                let Some(neighbour) = neighbour(cell, delta) else { continue };
+                if !was_alive {
+                    *self.counts.entry(neighbour).or_insert(0) += weight;
+                } else if let Some(count) = self.counts.get_mut(&neighbour) {
+                    *count -= weight;
+                    if *count == 0 {
+                        self.counts.remove(&neighbour);
+                    }
+                }
+            }
+        }
+
+        self.changed.insert(transitions.into_iter().map(|(cell, _)| cell).collect())
+    }
+
+    /// How many cells the last step looked at.
+    pub fn visited(&self) -> usize {
+        self.visited
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::process_cells;
+    use crate::patterns::{glider, random_soup};
+    use crate::rng::Rng;
+
+    fn board(cells: impl IntoIterator<Item = I64Vec2>) -> HashMap<I64Vec2, u8> {
+        cells.into_iter().map(|cell| (cell, ALIVE)).collect()
+    }
+
+    #[test]
+    fn matches_full_recompute() {
+        let soup = random_soup(&mut Rng::seed_from_u64(3), I64Vec2::ZERO, I64Vec2::splat(40), 0.4);
+        let torus = Torus::new(I64Vec2::new(40, 30));
+        let walled = Torus::walled(I64Vec2::new(30, 40));
+        let rules = [
+            ("B3/S23", None),
+            ("B36/S23", None),
+            ("B2/S/C3", None),
+            ("B3/S23/C4", torus.as_ref()),
+            ("B1/S1V", None),
+            ("B2/S2/C3V", torus.as_ref()),
+            ("R2,C0,M1,S6..9,B5..7,NM", None),
+            ("R1,C0,M0,S3..5,B4,6,NW121202121", torus.as_ref()),
+            ("B3/S23", walled.as_ref()),
+            ("B2/S2/C3V", walled.as_ref()),
+        ];
+        for (rule, torus) in rules {
+            let rule = Rule::parse(rule).unwrap();
+            let mut full = board(soup.iter().copied().filter(|&cell| torus.is_none_or(|torus| torus.contains(cell))));
+            let mut incremental = full.clone();
+            let mut counts = NeighbourCounts::new(&incremental, &rule.kernel(), torus);
+
+            for generation in 0..60 {
+                full = process_cells(&full, &rule, torus);
+                counts.step(&mut incremental, &rule, torus);
+                assert_eq!(incremental, full, "{rule} diverged at generation {generation}");
+            }
+        }
+    }
+
+    /// Steps a glider for a while and returns how many cells each step visited.
+    fn glider_work(extra: impl IntoIterator<Item = I64Vec2>) -> Vec<usize> {
+        let rule = Rule::CONWAY;
+        let mut cells = board(glider().into_iter().chain(extra));
+        let mut counts = NeighbourCounts::new(&cells, &rule.kernel(), None);
+        (0..40)
+            .map(|_| {
+                counts.step(&mut cells, &rule, None);
+                counts.visited()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn work_follows_the_glider_not_the_population() {
+        let alone = glider_work([]);
+        assert!(alone[1..].iter().all(|&visited| visited <= 60), "{alone:?}");
+
+        // A field of blocks far away never changes, so after the first full step it costs nothing.
+        let blocks = (0..50).flat_map(|index| {
+            let corner = I64Vec2::new(1000 + index * 4, 1000);
+            [I64Vec2::ZERO, I64Vec2::X, I64Vec2::Y, I64Vec2::ONE].map(|offset| corner + offset)
+        });
+        let with_blocks = glider_work(blocks);
+        assert!(with_blocks[0] > alone[0] + 200);
+        assert_eq!(with_blocks[1..], alone[1..]);
+    }
+}
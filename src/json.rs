@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A parsed JSON value. Integers are kept apart from other numbers so 64-bit coordinates
+/// survive a round trip exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(BTreeMap<String, Json>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonError {
+    /// Byte offset into the input where parsing failed.
+    pub offset: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at byte {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for JsonError {}
+
+impl Json {
+    pub fn parse(input: &str) -> Result<Json, JsonError> {
+        let mut parser = Parser { input: input.as_bytes(), offset: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.offset < input.len() {
+            return Err(parser.error("unexpected trailing characters"));
+        }
+        Ok(value)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Json::Bool(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Json::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// Writes compact JSON.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(value) => write!(f, "{value}"),
+            Json::Int(value) => write!(f, "{value}"),
+            Json::Float(value) if value.is_finite() => write!(f, "{value:?}"),
+            Json::Float(_) => write!(f, "null"),
+            Json::String(value) => write_string(f, value),
+            Json::Array(values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(fields) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in fields.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{value}")?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for ch in value.chars() {
+        match ch {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            ch if ch < ' ' => write!(f, "\\u{:04x}", ch as u32)?,
+            ch => write!(f, "{ch}")?,
+        }
+    }
+    write!(f, "\"")
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    offset: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> JsonError {
+        JsonError { offset: self.offset, message }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.input.get(self.offset).is_some_and(u8::is_ascii_whitespace) {
+            self.offset += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.input.get(self.offset).copied()
+    }
+
+    fn expect(&mut self, byte: u8, message: &'static str) -> Result<(), JsonError> {
+        if self.peek() != Some(byte) {
+            return Err(self.error(message));
+        }
+        self.offset += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Json) -> Result<Json, JsonError> {
+        if !self.input[self.offset..].starts_with(keyword.as_bytes()) {
+            return Err(self.error("unexpected character"));
+        }
+        self.offset += keyword.len();
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        match self.peek() {
+            None => Err(self.error("unexpected end of input")),
+            Some(b'n') => self.keyword("null", Json::Null),
+            Some(b't') => self.keyword("true", Json::Bool(true)),
+            Some(b'f') => self.keyword("false", Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'[') => self.array(),
+            Some(b'{') => self.object(),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+        }
+    }
+
+    /// Sequences of `item` separated by commas, up to the closing byte.
+    fn list(&mut self, close: u8, mut item: impl FnMut(&mut Self) -> Result<(), JsonError>) -> Result<(), JsonError> {
+        self.offset += 1;
+        if self.peek() == Some(close) {
+            self.offset += 1;
+            return Ok(());
+        }
+        loop {
+            item(self)?;
+            match self.peek() {
+                Some(b',') => self.offset += 1,
+                Some(byte) if byte == close => {
+                    self.offset += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.error("expected a comma or closing bracket")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        let mut values = Vec::new();
+        self.list(b']', |parser| {
+            values.push(parser.value()?);
+            Ok(())
+        })?;
+        Ok(Json::Array(values))
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        let mut fields = BTreeMap::new();
+        self.list(b'}', |parser| {
+            if parser.peek() != Some(b'"') {
+                return Err(parser.error("expected a string key"));
+            }
+            let key = parser.string()?;
+            parser.expect(b':', "expected a colon after the key")?;
+            fields.insert(key, parser.value()?);
+            Ok(())
+        })?;
+        Ok(Json::Object(fields))
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.offset += 1;
+        let mut value = String::new();
+        loop {
+            let start = self.offset;
+            while self.input.get(self.offset).is_some_and(|&byte| byte != b'"' && byte != b'\\') {
+                self.offset += 1;
+            }
+            // The input came from a `&str` and the run stops at ASCII, so it is valid UTF-8.
+            value.push_str(std::str::from_utf8(&self.input[start..self.offset]).unwrap_or_default());
+
+            match self.input.get(self.offset) {
+                None => return Err(self.error("unterminated string")),
+                Some(b'"') => {
+                    self.offset += 1;
+                    return Ok(value);
+                }
+                Some(_) => {
+                    let escape = self.input.get(self.offset + 1).copied();
+                    self.offset += 2;
+                    value.push(match escape {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    });
+                }
+            }
+        }
+    }
+
+    /// The four hex digits after `\u`. Surrogate pairs aren't combined, so those become U+FFFD.
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let digits = self.input.get(self.offset..self.offset + 4).ok_or(self.error("invalid escape"))?;
+        let code = std::str::from_utf8(digits)
+            .ok()
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or(self.error("invalid escape"))?;
+        self.offset += 4;
+        Ok(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    fn number(&mut self) -> Result<Json, JsonError> {
+        let start = self.offset;
+        while self
+            .input
+            .get(self.offset)
+            .is_some_and(|&byte| matches!(byte, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+        {
+            self.offset += 1;
+        }
+        let text = std::str::from_utf8(&self.input[start..self.offset]).unwrap_or_default();
+        if let Ok(value) = text.parse() {
+            return Ok(Json::Int(value));
+        }
+        text.parse().map(Json::Float).map_err(|_| JsonError { offset: start, message: "invalid number" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_values() {
+        let input = r#"{"a":[1,-9223372036854775808,2.5,true,null],"b":"say \"hi\"\n","c":{}}"#;
+        let value = Json::parse(input).unwrap();
+        assert_eq!(value.get("a").and_then(Json::as_array).map(|a| a[1].as_i64()), Some(Some(i64::MIN)));
+        assert_eq!(value.get("b").and_then(Json::as_str), Some("say \"hi\"\n"));
+        assert_eq!(value.to_string(), input);
+        assert_eq!(Json::parse(" [ 1 , 2 ] ").unwrap(), Json::Array(vec![Json::Int(1), Json::Int(2)]));
+        assert_eq!(Json::parse(r#""é\/""#).unwrap(), Json::String("é/".to_string()));
+    }
+
+    #[test]
+    fn reports_where_parsing_failed() {
+        let cases = [("", 0), ("[1,]", 3), ("{\"a\" 1}", 5), ("[1 2]", 3), ("\"open", 5), ("1 x", 2), ("-", 0)];
+        for (input, offset) in cases {
+            assert_eq!(Json::parse(input).map_err(|err| err.offset), Err(offset), "{input:?}");
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::borrow::Cow;
+use std::fmt;
+use glam::I64Vec2;
+use itertools::Itertools;
+use crate::rule::{Neighbourhood, MAX_RANGE};
+
+/// Cells along a side of the largest weighted box.
+const SIDE: usize = 2 * MAX_RANGE as usize + 1;
+
+/// How much each cell in the box around a cell adds to its neighbour count, for rules where
+/// neighbours count differently by position. The centre weight counts the cell itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Weights {
+    range: u8,
+    /// Row by row over the largest box, with the cell itself in the middle. Weights are single
+    /// hex digits, so two share each byte.
+    values: [u8; (SIDE * SIDE).div_ceil(2)],
+}
+
+impl Weights {
+    /// Parses one hex digit per cell of a square box, row by row from the top-left, as in
+    /// Golly's `NW` neighbourhoods. `111101111` is the Moore neighbourhood.
+    pub fn parse(text: &str) -> Option<Weights> {
+        let digits: Vec<u8> = text.chars().map(|ch| ch.to_digit(16).map(|digit| digit as u8)).collect::<Option<_>>()?;
+        let side = (3..=SIDE).step_by(2).find(|side| side * side == digits.len())?;
+        let range = (side / 2) as i64;
+        let mut weights = Weights { range: range as u8, values: [0; (SIDE * SIDE).div_ceil(2)] };
+        for (index, weight) in digits.into_iter().enumerate() {
+            let offset = I64Vec2::new((index % side) as i64 - range, (index / side) as i64 - range);
+            let index = Self::index(offset);
+            weights.values[index / 2] |= weight << (index % 2 * 4);
+        }
+        Some(weights)
+    }
+
+    /// How many cells out the weights reach.
+    pub fn range(&self) -> u8 {
+        self.range
+    }
+
+    /// The weight of the cell `offset` from the middle, which is 0 past the weights' range.
+    pub fn get(&self, offset: I64Vec2) -> u8 {
+        if offset.abs().max_element() > self.range as i64 {
+            return 0;
+        }
+        let index = Self::index(offset);
+        self.values[index / 2] >> (index % 2 * 4) & 0xf
+    }
+
+    /// The highest count a cell can have, with every weighted cell alive.
+    pub fn total(&self) -> usize {
+        Weights::offsets(self.range as i64).map(|offset| self.get(offset) as usize).sum()
+    }
+
+    fn offsets(range: i64) -> impl Iterator<Item = I64Vec2> {
+        (-range..=range).cartesian_product(-range..=range).map(|(y, x)| I64Vec2::new(x, y))
+    }
+
+    fn index(offset: I64Vec2) -> usize {
+        let centre = MAX_RANGE as i64;
+        ((offset.y + centre) as usize) * SIDE + (offset.x + centre) as usize
+    }
+}
+
+impl fmt::Display for Weights {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for offset in Weights::offsets(self.range as i64) {
+            write!(f, "{:x}", self.get(offset))?;
+        }
+        Ok(())
+    }
+}
+
+/// What a live cell adds to the counts around it, as offsets from the live cell and the weight
+/// added there. [`convolve`](crate::life::convolve) sums these into each cell's count.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Kernel(Cow<'static, [(I64Vec2, usize)]>);
+
+impl Kernel {
+    /// The all-ones kernel over the eight cells around a cell, as in Conway's Life.
+    pub const MOORE: Kernel = Kernel(Cow::Borrowed(&[
+        (I64Vec2::new(-1, -1), 1),
+        (I64Vec2::NEG_X, 1),
+        (I64Vec2::new(-1, 1), 1),
+        (I64Vec2::NEG_Y, 1),
+        (I64Vec2::Y, 1),
+        (I64Vec2::new(1, -1), 1),
+        (I64Vec2::X, 1),
+        (I64Vec2::ONE, 1),
+    ]));
+
+    pub const VON_NEUMANN: Kernel =
+        Kernel(Cow::Borrowed(&[(I64Vec2::NEG_Y, 1), (I64Vec2::NEG_X, 1), (I64Vec2::X, 1), (I64Vec2::Y, 1)]));
+
+    /// The kernel for a neighbourhood reaching `range` cells out. Range 1 Moore and von Neumann
+    /// kernels are constants, so counting with them allocates nothing.
+    pub fn new(neighbourhood: Neighbourhood, range: u8) -> Kernel {
+        let range = range as i64;
+        let weight = |offset: I64Vec2| match neighbourhood {
+            Neighbourhood::Weighted(weights) => weights.get(offset),
+            _ if offset == I64Vec2::ZERO => 0,
+            Neighbourhood::Moore => 1,
+            Neighbourhood::VonNeumann => u8::from(offset.x.abs() + offset.y.abs() <= range),
+        };
+        match (neighbourhood, range) {
+            (Neighbourhood::Moore, 1) => Kernel::MOORE,
+            (Neighbourhood::VonNeumann, 1) => Kernel::VON_NEUMANN,
+            // A neighbour up and to the left of a cell counts towards it, so a live cell adds to
+            // the cell down and to the right of it.
+            _ => Kernel(
+                Weights::offsets(range)
+                    .filter(|&offset| weight(offset) > 0)
+                    .map(|offset| (-offset, weight(offset) as usize))
+                    .collect(),
+            ),
+        }
+    }
+
+    pub fn entries(&self) -> &[(I64Vec2, usize)] {
+        &self.0
+    }
+}
+
+impl Default for Kernel {
+    fn default() -> Self {
+        Kernel::MOORE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+    use crate::life::{convolve, ALIVE};
+
+    #[test]
+    fn parses_and_displays_weights() {
+        let weights = Weights::parse("121202121").unwrap();
+        assert_eq!((weights.range(), weights.total()), (1, 12));
+        assert_eq!(weights.get(I64Vec2::new(-1, -1)), 1);
+        assert_eq!(weights.get(I64Vec2::NEG_Y), 2);
+        assert_eq!(weights.get(I64Vec2::new(2, 0)), 0);
+        assert_eq!(weights.to_string(), "121202121");
+        assert_eq!(Weights::parse("0000f00000000000000000000").unwrap().to_string(), "0000f00000000000000000000");
+
+        assert_eq!(Weights::parse("1111"), None);
+        assert_eq!(Weights::parse("1"), None);
+        assert_eq!(Weights::parse("11110111g"), None);
+    }
+
+    #[test]
+    fn uniform_weights_match_the_standard_kernels() {
+        let sorted = |kernel: Kernel| kernel.entries().iter().copied().sorted_by_key(|&(delta, _)| (delta.y, delta.x));
+        let moore = Neighbourhood::Weighted(Weights::parse("111101111").unwrap());
+        assert!(sorted(Kernel::new(moore, 1)).eq(sorted(Kernel::default())));
+        assert_eq!(Kernel::new(Neighbourhood::Moore, 2).entries().len(), 24);
+        assert_eq!(Kernel::new(Neighbourhood::VonNeumann, 2).entries().len(), 12);
+    }
+
+    #[test]
+    fn asymmetric_weights_count_in_their_direction() {
+        // Only the cell above counts, twice over.
+        let above = Kernel::new(Neighbourhood::Weighted(Weights::parse("020000000").unwrap()), 1);
+        let cells = HashMap::from([(I64Vec2::ZERO, ALIVE)]);
+        assert_eq!(convolve(&cells, &above, None), HashMap::from([(I64Vec2::Y, 2)]));
+        let counted: HashSet<I64Vec2> = convolve(&cells, &Kernel::default(), None).into_keys().collect();
+        assert_eq!(counted.len(), 8);
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::{fmt, fs, io};
+use raylib::consts::KeyboardKey;
+use raylib::core::input::key_from_i32;
+use raylib::RaylibHandle;
+use crate::json::{Json, JsonError};
+
+/// Where key bindings are read from when `--keys` isn't given.
+pub const DEFAULT_KEYS_PATH: &str = "keys.json";
+
+/// The actions that can be bound to keys. Modifier shortcuts such as Ctrl+Z stay fixed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    ToggleRun,
+    ResetToStart,
+    TakeSnapshot,
+    RunTo,
+    Jump,
+    Step,
+    StepBack,
+    SpeedUp,
+    SlowDown,
+    Clear,
+    CycleRule,
+    CycleEngine,
+    RandomFill,
+    FillSeed,
+    ExportRle,
+    ExportCells,
+    ExportImage,
+    Screenshot,
+    RecordGif,
+    ResetView,
+    FitView,
+    Follow,
+    ToggleHelp,
+    CycleTheme,
+    PopulationGraph,
+    Torus,
+    PauseOnCycle,
+    DrawMode,
+    ClickToggles,
+    Colouring,
+    PaintColour,
+    LineTool,
+    RectangleTool,
+    Symmetry,
+    Minimap,
+    HighlightChanges,
+    Trails,
+    Heatmap,
+    Grid,
+    Neighbourhood,
+    DeleteSelection,
+    InvertSelection,
+    OldestCells,
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+}
+
+impl Action {
+    pub const ALL: [Action; 47] = [
+        Action::ToggleRun,
+        Action::ResetToStart,
+        Action::TakeSnapshot,
+        Action::RunTo,
+        Action::Jump,
+        Action::Step,
+        Action::StepBack,
+        Action::SpeedUp,
+        Action::SlowDown,
+        Action::Clear,
+        Action::CycleRule,
+        Action::CycleEngine,
+        Action::RandomFill,
+        Action::FillSeed,
+        Action::ExportRle,
+        Action::ExportCells,
+        Action::ExportImage,
+        Action::Screenshot,
+        Action::RecordGif,
+        Action::ResetView,
+        Action::FitView,
+        Action::Follow,
+        Action::ToggleHelp,
+        Action::CycleTheme,
+        Action::PopulationGraph,
+        Action::Torus,
+        Action::PauseOnCycle,
+        Action::DrawMode,
+        Action::ClickToggles,
+        Action::Colouring,
+        Action::PaintColour,
+        Action::LineTool,
+        Action::RectangleTool,
+        Action::Symmetry,
+        Action::Minimap,
+        Action::HighlightChanges,
+        Action::Trails,
+        Action::Heatmap,
+        Action::Grid,
+        Action::Neighbourhood,
+        Action::DeleteSelection,
+        Action::InvertSelection,
+        Action::OldestCells,
+        Action::PanUp,
+        Action::PanDown,
+        Action::PanLeft,
+        Action::PanRight,
+    ];
+
+    /// The action's name in a key bindings file.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::ToggleRun => "toggle-run",
+            Action::ResetToStart => "reset-to-start",
+            Action::TakeSnapshot => "take-snapshot",
+            Action::RunTo => "run-to",
+            Action::Jump => "jump",
+            Action::Step => "step",
+            Action::StepBack => "step-back",
+            Action::SpeedUp => "speed-up",
+            Action::SlowDown => "slow-down",
+            Action::Clear => "clear",
+            Action::CycleRule => "cycle-rule",
+            Action::CycleEngine => "cycle-engine",
+            Action::RandomFill => "random-fill",
+            Action::FillSeed => "fill-seed",
+            Action::ExportRle => "export-rle",
+            Action::ExportCells => "export-cells",
+            Action::ExportImage => "export-image",
+            Action::Screenshot => "screenshot",
+            Action::RecordGif => "record-gif",
+            Action::ResetView => "reset-view",
+            Action::FitView => "fit-view",
+            Action::Follow => "follow",
+            Action::ToggleHelp => "toggle-help",
+            Action::CycleTheme => "cycle-theme",
+            Action::PopulationGraph => "population-graph",
+            Action::Torus => "torus",
+            Action::PauseOnCycle => "pause-on-cycle",
+            Action::DrawMode => "draw-mode",
+            Action::ClickToggles => "click-toggles",
+            Action::Colouring => "colouring",
+            Action::PaintColour => "paint-colour",
+            Action::LineTool => "line-tool",
+            Action::RectangleTool => "rectangle-tool",
+            Action::Symmetry => "symmetry",
+            Action::Minimap => "minimap",
+            Action::HighlightChanges => "highlight-changes",
+            Action::Trails => "trails",
+            Action::Heatmap => "heatmap",
+            Action::Grid => "grid",
+            Action::Neighbourhood => "neighbourhood",
+            Action::DeleteSelection => "delete-selection",
+            Action::InvertSelection => "invert-selection",
+            Action::OldestCells => "oldest-cells",
+            Action::PanUp => "pan-up",
+            Action::PanDown => "pan-down",
+            Action::PanLeft => "pan-left",
+            Action::PanRight => "pan-right",
+        }
+    }
+
+    fn default_keys(self) -> &'static [KeyboardKey] {
+        use KeyboardKey::*;
+        match self {
+            Action::ToggleRun => &[KEY_SPACE],
+            Action::ResetToStart => &[KEY_F5],
+            Action::TakeSnapshot => &[KEY_F6],
+            Action::RunTo => &[KEY_F7],
+            Action::Jump => &[KEY_F8],
+            Action::Step => &[KEY_N],
+            Action::StepBack => &[KEY_P],
+            Action::SpeedUp => &[KEY_EQUAL, KEY_KP_ADD],
+            Action::SlowDown => &[KEY_MINUS, KEY_KP_SUBTRACT],
+            Action::Clear => &[KEY_C],
+            Action::CycleRule => &[KEY_R],
+            Action::CycleEngine => &[KEY_K],
+            Action::RandomFill => &[KEY_F],
+            Action::FillSeed => &[KEY_F9],
+            Action::ExportRle => &[KEY_S],
+            Action::ExportCells => &[KEY_E],
+            Action::ExportImage => &[KEY_X],
+            Action::Screenshot => &[KEY_F12],
+            Action::RecordGif => &[KEY_G],
+            Action::ResetView => &[KEY_HOME],
+            Action::FitView => &[KEY_B],
+            Action::Follow => &[KEY_END],
+            Action::ToggleHelp => &[KEY_F1],
+            Action::CycleTheme => &[KEY_T],
+            Action::PopulationGraph => &[KEY_O],
+            Action::Torus => &[KEY_W],
+            Action::PauseOnCycle => &[KEY_A],
+            Action::DrawMode => &[KEY_D],
+            Action::ClickToggles => &[KEY_F2],
+            Action::Colouring => &[KEY_F3],
+            Action::PaintColour => &[KEY_F4],
+            Action::LineTool => &[KEY_L],
+            Action::RectangleTool => &[KEY_Q],
+            Action::Symmetry => &[KEY_M],
+            Action::Minimap => &[KEY_I],
+            Action::HighlightChanges => &[KEY_Z],
+            Action::Trails => &[KEY_F11],
+            Action::Heatmap => &[KEY_TAB],
+            Action::Grid => &[KEY_Y],
+            Action::Neighbourhood => &[KEY_J],
+            Action::DeleteSelection => &[KEY_DELETE, KEY_BACKSPACE],
+            Action::InvertSelection => &[KEY_U],
+            Action::OldestCells => &[KEY_F10],
+            Action::PanUp => &[KEY_UP],
+            Action::PanDown => &[KEY_DOWN],
+            Action::PanLeft => &[KEY_LEFT],
+            Action::PanRight => &[KEY_RIGHT],
+        }
+    }
+}
+
+/// The keys that trigger each action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    keys: HashMap<Action, Vec<KeyboardKey>>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self { keys: Action::ALL.iter().map(|&action| (action, action.default_keys().to_vec())).collect() }
+    }
+}
+
+impl KeyBindings {
+    /// Reads a JSON object mapping action names to a key name or a list of them, such as
+    /// `{"toggle-run": "ENTER", "step": ["N", "RIGHT"]}`. Key names are raylib's without the
+    /// `KEY_` prefix, in any case.
+    ///
+    /// Anything that can't be read is reported and left at its default, so a broken file never
+    /// leaves the app without controls.
+    pub fn parse(input: &str) -> (KeyBindings, Vec<KeyBindingError>) {
+        let mut bindings = KeyBindings::default();
+        let mut errors = Vec::new();
+        let fields = match Json::parse(input) {
+            Ok(Json::Object(fields)) => fields,
+            Ok(_) => return (bindings, vec![KeyBindingError::NotAnObject]),
+            Err(err) => return (bindings, vec![KeyBindingError::Json(err)]),
+        };
+
+        for (name, value) in fields {
+            let Some(action) = Action::ALL.into_iter().find(|action| action.name() == name) else {
+                errors.push(KeyBindingError::UnknownAction(name));
+                continue;
+            };
+            let names = match &value {
+                Json::String(_) => vec![value.clone()],
+                Json::Array(names) => names.clone(),
+                _ => Vec::new(),
+            };
+            let keys: Option<Vec<KeyboardKey>> = names.iter().map(|name| key_from_name(name.as_str()?)).collect();
+            match keys {
+                Some(keys) if !keys.is_empty() => {
+                    bindings.keys.insert(action, keys);
+                }
+                _ => errors.push(KeyBindingError::InvalidKey { action: action.name(), value: value.to_string() }),
+            }
+        }
+
+        (bindings, errors)
+    }
+
+    pub fn load(path: &Path) -> (KeyBindings, Vec<KeyBindingError>) {
+        match fs::read_to_string(path) {
+            Ok(input) => KeyBindings::parse(&input),
+            Err(err) => (KeyBindings::default(), vec![KeyBindingError::Io(err)]),
+        }
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeyboardKey] {
+        self.keys.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether any key bound to the action was pressed this frame.
+    pub fn is_pressed(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.keys(action).iter().any(|&key| rl.is_key_pressed(key))
+    }
+
+    /// Like [`KeyBindings::is_pressed`], but also true for the repeats of a held key.
+    pub fn is_repeated(&self, rl: &RaylibHandle, action: Action) -> bool {
+        self.keys(action).iter().any(|&key| rl.is_key_pressed(key) || rl.is_key_pressed_repeat(key))
+    }
+}
+
+/// Looks a key up by its raylib name without the `KEY_` prefix, ignoring case.
+pub fn key_from_name(name: &str) -> Option<KeyboardKey> {
+    let name = name.trim();
+    (0..=400)
+        .filter_map(key_from_i32)
+        .filter(|&key| key != KeyboardKey::KEY_NULL)
+        .find(|key| format!("{key:?}").strip_prefix("KEY_").is_some_and(|known| known.eq_ignore_ascii_case(name)))
+}
+
+#[derive(Debug)]
+pub enum KeyBindingError {
+    Io(io::Error),
+    Json(JsonError),
+    NotAnObject,
+    UnknownAction(String),
+    InvalidKey { action: &'static str, value: String },
+}
+
+impl fmt::Display for KeyBindingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KeyBindingError::Io(err) => write!(f, "{err}"),
+            KeyBindingError::Json(err) => write!(f, "invalid JSON: {err}"),
+            KeyBindingError::NotAnObject => write!(f, "expected an object of action names to keys"),
+            KeyBindingError::UnknownAction(name) => write!(f, "unknown action {name:?}"),
+            KeyBindingError::InvalidKey { action, value } => write!(f, "invalid key {value} for {action:?}"),
+        }
+    }
+}
+
+impl std::error::Error for KeyBindingError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_keys_like_raylib() {
+        assert_eq!(key_from_name("SPACE"), Some(KeyboardKey::KEY_SPACE));
+        assert_eq!(key_from_name("kp_add"), Some(KeyboardKey::KEY_KP_ADD));
+        assert_eq!(key_from_name(" f12 "), Some(KeyboardKey::KEY_F12));
+        for unknown in ["", "NULL", "KEY_SPACE", "spacebar"] {
+            assert_eq!(key_from_name(unknown), None, "{unknown:?}");
+        }
+    }
+
+    #[test]
+    fn overrides_only_the_bound_actions() {
+        let (bindings, errors) = KeyBindings::parse(r#"{"toggle-run": "enter", "step": ["J", "DOWN"]}"#);
+        assert!(errors.is_empty(), "{errors:?}");
+        assert_eq!(bindings.keys(Action::ToggleRun), [KeyboardKey::KEY_ENTER]);
+        assert_eq!(bindings.keys(Action::Step), [KeyboardKey::KEY_J, KeyboardKey::KEY_DOWN]);
+        assert_eq!(bindings.keys(Action::Clear), [KeyboardKey::KEY_C]);
+        assert!(Action::ALL.iter().all(|&action| !KeyBindings::default().keys(action).is_empty()));
+    }
+
+    #[test]
+    fn falls_back_to_defaults_for_bad_bindings() {
+        let (bindings, errors) = KeyBindings::parse(r#"{"clear": "NOPE", "fly": "X", "step": [], "torus": 3}"#);
+        assert_eq!(bindings, KeyBindings::default());
+        assert_eq!(errors.len(), 4);
+        assert!(matches!(&errors[1], KeyBindingError::UnknownAction(name) if name == "fly"));
+
+        for malformed in ["{", "[]"] {
+            let (bindings, errors) = KeyBindings::parse(malformed);
+            assert_eq!(bindings, KeyBindings::default());
+            assert_eq!(errors.len(), 1);
+        }
+        assert!(matches!(&KeyBindings::load(Path::new("does/not/exist.json")).1[..], [KeyBindingError::Io(_)]));
+    }
+}
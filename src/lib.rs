@@ -0,0 +1,38 @@
+pub mod brush;
+pub mod camera;
+pub mod chunked;
+pub mod cli;
+pub mod config;
+pub mod cycle;
+pub mod diff;
+pub mod gif;
+pub mod gliders;
+pub mod hashlife;
+pub mod headless;
+pub mod history;
+pub mod incremental;
+pub mod json;
+pub mod kernel;
+pub mod keys;
+pub mod lif;
+pub mod life;
+pub mod minimap;
+pub mod noise;
+pub mod pattern_file;
+pub mod patterns;
+pub mod pixel_art;
+pub mod plaintext;
+pub mod population;
+pub mod rle;
+pub mod rng;
+pub mod rule;
+pub mod ruler;
+pub mod selection;
+pub mod session;
+pub mod theme;
+pub mod timing;
+pub mod timestamp;
+pub mod torus;
+pub mod trails;
+pub mod transform;
+pub mod worker;
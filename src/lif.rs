@@ -0,0 +1,134 @@
+use std::collections::HashSet;
+use std::fmt;
+use glam::I64Vec2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifError {
+    MissingHeader,
+    InvalidLine { line: usize },
+}
+
+impl fmt::Display for LifError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LifError::MissingHeader => write!(f, "missing #Life 1.05 or #Life 1.06 header"),
+            LifError::InvalidLine { line } => write!(f, "invalid Life data on line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for LifError {}
+
+/// Parses a pattern in either the Life 1.05 or Life 1.06 format.
+///
+/// Life 1.06 lists one `x y` coordinate per line. Life 1.05 is made of `#P x y` blocks of
+/// `.`/`*` rows, each block offset by its `#P` position (or (0, 0) when omitted).
+pub fn parse_lif(input: &str) -> Result<HashSet<I64Vec2>, LifError> {
+    let mut lines = input.lines().enumerate();
+    let header = lines
+        .by_ref()
+        .map(|(_, line)| line.trim())
+        .find(|line| !line.is_empty())
+        .ok_or(LifError::MissingHeader)?;
+
+    match header {
+        "#Life 1.06" => parse_106(lines),
+        "#Life 1.05" => parse_105(lines),
+        _ => Err(LifError::MissingHeader),
+    }
+}
+
+fn parse_106<'a>(lines: impl Iterator<Item = (usize, &'a str)>) -> Result<HashSet<I64Vec2>, LifError> {
+    let mut cells = HashSet::new();
+
+    for (index, line) in lines {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        cells.insert(parse_coordinates(line).ok_or(LifError::InvalidLine { line: index + 1 })?);
+    }
+
+    Ok(cells)
+}
+
+fn parse_105<'a>(lines: impl Iterator<Item = (usize, &'a str)>) -> Result<HashSet<I64Vec2>, LifError> {
+    let mut cells = HashSet::new();
+    let mut block_origin = I64Vec2::ZERO;
+    let mut row = 0;
+
+    for (index, line) in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(position) = line.strip_prefix("#P") {
+            block_origin = parse_coordinates(position).ok_or(LifError::InvalidLine { line: index + 1 })?;
+            row = 0;
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+
+        for (x, ch) in line.chars().enumerate() {
+            match ch {
+                '*' => {
+                    cells.insert(block_origin + I64Vec2::new(x as i64, row));
+                }
+                '.' => {}
+                _ => return Err(LifError::InvalidLine { line: index + 1 }),
+            }
+        }
+        row += 1;
+    }
+
+    Ok(cells)
+}
+
+fn parse_coordinates(text: &str) -> Option<I64Vec2> {
+    let mut parts = text.split_whitespace().map(|part| part.parse::<i64>());
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(x)), Some(Ok(y)), None) => Some(I64Vec2::new(x, y)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glider_at(offset: I64Vec2) -> HashSet<I64Vec2> {
+        [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+            .iter()
+            .map(|&(x, y)| I64Vec2::new(x, y) + offset)
+            .collect()
+    }
+
+    #[test]
+    fn parses_life_106() {
+        let input = "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n";
+        assert_eq!(parse_lif(input), Ok(glider_at(I64Vec2::ZERO)));
+
+        let input = "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+        assert_eq!(parse_lif(input), Ok(glider_at(I64Vec2::new(-1, -1))));
+    }
+
+    #[test]
+    fn parses_life_105_blocks() {
+        let input = "#Life 1.05\n#D A glider\n#N\n#P -1 -1\n.*.\n..*\n***\n";
+        assert_eq!(parse_lif(input), Ok(glider_at(I64Vec2::new(-1, -1))));
+
+        let input = "#Life 1.05\n#P 0 0\n*\n#P 10 5\n.*\n";
+        let expected = [I64Vec2::new(0, 0), I64Vec2::new(11, 5)].into_iter().collect();
+        assert_eq!(parse_lif(input), Ok(expected));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(parse_lif("1 0\n"), Err(LifError::MissingHeader));
+        assert_eq!(parse_lif(""), Err(LifError::MissingHeader));
+        assert_eq!(parse_lif("#Life 1.06\n1 x\n"), Err(LifError::InvalidLine { line: 2 }));
+        assert_eq!(parse_lif("#Life 1.05\n.O.\n"), Err(LifError::InvalidLine { line: 2 }));
+    }
+}
@@ -0,0 +1,1262 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::{mem, thread};
+use glam::I64Vec2;
+use itertools::Itertools;
+use crate::hashlife::HashLife;
+use crate::incremental::NeighbourCounts;
+use crate::kernel::Kernel;
+use crate::noise::Noise;
+use crate::rule::{Neighbourhood, Rule};
+use crate::torus::Torus;
+
+/// The state of a live cell. Higher states are decaying cells of a Generations rule.
+pub const ALIVE: u8 = 1;
+
+/// How the board computes the next generation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Engine {
+    /// Counts the neighbours of every live cell each generation.
+    #[default]
+    Naive,
+    /// Keeps neighbour counts between generations and only revisits the cells around the last
+    /// step's changes.
+    Incremental,
+    /// Advances a memoized quadtree. Falls back to the naive engine for Generations rules, the
+    /// von Neumann neighbourhood, weighted kernels, Larger than Life ranges, toroidal boards,
+    /// the Immigration and QuadLife colourings and noise.
+    HashLife,
+}
+
+/// How many cells came alive and how many stopped being alive in a step. A live cell that
+/// starts decaying under a Generations rule counts as a death.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StepStats {
+    pub births: usize,
+    pub deaths: usize,
+}
+
+impl StepStats {
+    fn between(previous: &HashMap<I64Vec2, u8>, next: &HashMap<I64Vec2, u8>) -> Self {
+        let newly_alive = |from: &HashMap<I64Vec2, u8>, to: &HashMap<I64Vec2, u8>| {
+            live_cells(to).filter(|cell| from.get(cell) != Some(&ALIVE)).count()
+        };
+        Self { births: newly_alive(previous, next), deaths: newly_alive(next, previous) }
+    }
+}
+
+/// Maps the naive engine reuses from step to step, so it stops allocating once the board's
+/// size settles.
+#[derive(Debug, Clone, Default)]
+struct StepBuffers {
+    counts: HashMap<I64Vec2, usize>,
+    /// The board from before the last step, cleared and refilled with the next one.
+    next: HashMap<I64Vec2, u8>,
+}
+
+/// A revision number no other board in the process has had, so a cache keyed on one can't
+/// mistake a replaced board for the one it was built from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Revision(u64);
+
+impl Revision {
+    fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Revision(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for Revision {
+    fn default() -> Self {
+        Revision::next()
+    }
+}
+
+/// The most colours a [`Colouring`] gives live cells.
+pub const MAX_COLOURS: u8 = 4;
+
+/// A variant of Life whose live cells each carry a colour, which newborns take from their
+/// parents. Which cells live and die is unchanged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Colouring {
+    /// Two colours, with a newborn taking the colour most of its parents have.
+    Immigration,
+    /// Four colours, with a newborn whose parents all differ taking the colour none has.
+    QuadLife,
+}
+
+impl Colouring {
+    pub fn colours(self) -> u8 {
+        match self {
+            Colouring::Immigration => 2,
+            Colouring::QuadLife => MAX_COLOURS,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Colouring::Immigration => "Immigration",
+            Colouring::QuadLife => "QuadLife",
+        }
+    }
+
+    /// The colouring with this [`Colouring::name`], ignoring case.
+    pub fn parse(text: &str) -> Option<Self> {
+        [Colouring::Immigration, Colouring::QuadLife]
+            .into_iter()
+            .find(|colouring| colouring.name().eq_ignore_ascii_case(text.trim()))
+    }
+
+    /// Steps through the colourings and back to plain cells.
+    pub fn next(colouring: Option<Self>) -> Option<Self> {
+        match colouring {
+            None => Some(Colouring::Immigration),
+            Some(Colouring::Immigration) => Some(Colouring::QuadLife),
+            Some(Colouring::QuadLife) => None,
+        }
+    }
+
+    /// The colour of a newborn whose parents weigh `weights` by colour, from colour 1. The most
+    /// weighted colour wins. Immigration breaks a tie with colour 1, and QuadLife with the least
+    /// weighted colour, so three parents of different colours give the fourth.
+    fn newborn_colour(self, weights: &[usize]) -> u8 {
+        let most = weights.iter().copied().max().unwrap_or(0);
+        let mut leading = (1..).zip(weights).filter(|&(_, &weight)| weight == most);
+        let first = leading.next().map_or(1, |(colour, _)| colour);
+        match self {
+            Colouring::QuadLife if leading.next().is_some() => {
+                let least = weights.iter().copied().min().unwrap_or(0);
+                (1..).zip(weights).find(|&(_, &weight)| weight == least).map_or(1, |(colour, _)| colour)
+            }
+            _ => first,
+        }
+    }
+}
+
+/// A Game of Life board that stores only the non-dead cells and their states. It is unbounded
+/// unless wrapped onto a torus.
+#[derive(Debug, Clone, Default)]
+pub struct Life {
+    cells: HashMap<I64Vec2, u8>,
+    rule: Rule,
+    engine: Engine,
+    generation: u64,
+    torus: Option<Torus>,
+    /// The generation each live cell was born or placed at, so ages need no updating while
+    /// cells survive.
+    births: HashMap<I64Vec2, u64>,
+    /// The coloured variant live cells follow, if any.
+    colouring: Option<Colouring>,
+    /// Each live cell's colour, from 1, under a colouring. Cells missing from it have colour 1.
+    colours: HashMap<I64Vec2, u8>,
+    /// The colour cells placed by edits get under a colouring.
+    paint_colour: u8,
+    /// Chances that make births and survivals random, if any.
+    noise: Option<Noise>,
+    /// The HashLife universe, kept between steps and dropped whenever the board is edited.
+    hashlife: Option<HashLife>,
+    /// The incremental engine's neighbour counts, kept and dropped like the HashLife universe.
+    incremental: Option<NeighbourCounts>,
+    buffers: StepBuffers,
+    /// Whether the last generation stepped left every cell as it was.
+    stable: bool,
+    last_step: StepStats,
+    revision: Revision,
+}
+
+impl Life {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_cells(cells: HashSet<I64Vec2>) -> Self {
+        let mut life = Self::default();
+        life.extend(cells);
+        life
+    }
+
+    /// Advances the board by one generation.
+    pub fn step(&mut self) {
+        self.advance(1);
+    }
+
+    /// Advances the board by `generations`. HashLife does this in one jump, so across a jump
+    /// any cell alive at both ends is aged as if it had lived throughout. Under a colouring or
+    /// noise the naive engine runs whatever the engine, as colouring newborns and rolling for
+    /// each cell need each whole generation.
+    pub fn advance(&mut self, generations: u64) {
+        self.revision = Revision::next();
+        if self.engine == Engine::Incremental && self.colouring.is_none() && self.noise.is_none() {
+            for _ in 0..generations {
+                self.generation += 1;
+                let counts = self.incremental.get_or_insert_with(|| {
+                    NeighbourCounts::new(&self.cells, &self.rule.kernel(), self.torus.as_ref())
+                });
+                let changed = counts.step(&mut self.cells, &self.rule, self.torus.as_ref());
+                self.stable = changed.is_empty();
+                self.last_step = StepStats::default();
+                for &cell in changed {
+                    // Only live cells have births, so a removed one was alive until now.
+                    if self.cells.get(&cell) == Some(&ALIVE) {
+                        self.births.insert(cell, self.generation);
+                        self.last_step.births += 1;
+                    } else if self.births.remove(&cell).is_some() {
+                        self.last_step.deaths += 1;
+                    }
+                }
+            }
+            return;
+        }
+
+        if !self.uses_hashlife() {
+            for _ in 0..generations {
+                self.generation += 1;
+                let mut next = mem::take(&mut self.buffers.next);
+                let noise = self.noise.map(|noise| noise.for_generation(self.generation));
+                let (counts, torus) = (&mut self.buffers.counts, self.torus.as_ref());
+                process_cells_into(&self.cells, &self.rule, torus, noise.as_ref(), counts, &mut next);
+                self.stable = is_unchanged(&self.cells, &next);
+                let previous = mem::replace(&mut self.cells, next);
+                self.update_births(&previous);
+                if let Some(colouring) = self.colouring {
+                    self.update_colours(&previous, colouring);
+                }
+                self.buffers.next = previous;
+            }
+            return;
+        }
+
+        self.generation += generations;
+        let hashlife = self
+            .hashlife
+            .get_or_insert_with(|| HashLife::from_cells(&live_cells(&self.cells).collect(), self.rule));
+        hashlife.advance(generations);
+        let next = hashlife.cells().into_iter().map(|cell| (cell, ALIVE)).collect();
+        // A longer jump can land an oscillator back where it started, so only single steps count.
+        self.stable = generations == 1 && is_unchanged(&self.cells, &next);
+        let previous = mem::replace(&mut self.cells, next);
+        self.update_births(&previous);
+    }
+
+    /// Keeps the births of cells that stayed alive and dates new cells to this generation.
+    fn update_births(&mut self, previous: &HashMap<I64Vec2, u8>) {
+        self.last_step = StepStats::between(previous, &self.cells);
+        let generation = self.generation;
+        let cells = &self.cells;
+        self.births.retain(|cell, _| cells.get(cell) == Some(&ALIVE) && previous.get(cell) == Some(&ALIVE));
+        for cell in live_cells(cells) {
+            self.births.entry(cell).or_insert(generation);
+        }
+    }
+
+    /// Gives each newborn cell the colour most of its parents, the live neighbours that brought
+    /// it to life, had and forgets the colours of cells that died. How a tie is broken depends
+    /// on the colouring.
+    fn update_colours(&mut self, previous: &HashMap<I64Vec2, u8>, colouring: Colouring) {
+        let kernel = self.rule.kernel();
+        let newborn: Vec<(I64Vec2, u8)> = live_cells(&self.cells)
+            .filter(|cell| previous.get(cell) != Some(&ALIVE))
+            .map(|cell| {
+                let mut weights = [0; MAX_COLOURS as usize];
+                for &(delta, weight) in kernel.entries() {
+                    let parent = self.torus.map_or(Some(cell - delta), |torus| torus.neighbour(cell - delta));
+                    if let Some(parent) = parent
+                        && previous.get(&parent) == Some(&ALIVE)
+                    {
+                        weights[self.colours.get(&parent).map_or(0, |&colour| colour as usize - 1)] += weight;
+                    }
+                }
+                (cell, colouring.newborn_colour(&weights[..colouring.colours() as usize]))
+            })
+            .collect();
+
+        let cells = &self.cells;
+        self.colours.retain(|cell, _| cells.get(cell) == Some(&ALIVE));
+        self.colours.extend(newborn);
+    }
+
+    /// The chances births and survivals happen with, if they are random.
+    pub fn noise(&self) -> Option<Noise> {
+        self.noise
+    }
+
+    /// Makes births and survivals random, or certain again with `None`.
+    pub fn set_noise(&mut self, noise: Option<Noise>) {
+        self.noise = noise;
+        self.edited();
+    }
+
+    /// The coloured variant live cells follow, if any.
+    pub fn colouring(&self) -> Option<Colouring> {
+        self.colouring
+    }
+
+    /// Switches to a coloured variant, or back to plain cells with `None`. Either way every
+    /// live cell starts out with colour 1, and a paint colour the variant lacks goes back to 1.
+    pub fn set_colouring(&mut self, colouring: Option<Colouring>) {
+        self.colouring = colouring;
+        self.colours.clear();
+        if colouring.is_some_and(|colouring| self.paint_colour > colouring.colours()) {
+            self.paint_colour = 1;
+        }
+        self.edited();
+    }
+
+    /// The colour of a live cell under a colouring, from 1, or 0 for a cell that isn't alive.
+    pub fn colour(&self, cell: I64Vec2) -> u8 {
+        if self.is_alive(cell) { self.colours.get(&cell).copied().unwrap_or(1) } else { 0 }
+    }
+
+    pub fn paint_colour(&self) -> u8 {
+        self.paint_colour.max(1)
+    }
+
+    /// Sets the colour that edits give the cells they place under a colouring, clamped to
+    /// those the colouring has.
+    pub fn set_paint_colour(&mut self, colour: u8) {
+        self.paint_colour = colour.clamp(1, self.colouring.map_or(1, Colouring::colours));
+    }
+
+    /// Gives a live cell a colour, clamped to those the colouring has, so a saved board can be
+    /// put back as it was. Does nothing to other cells or without a colouring.
+    pub fn set_colour(&mut self, cell: I64Vec2, colour: u8) {
+        if let Some(colouring) = self.colouring
+            && self.is_alive(cell)
+        {
+            self.colours.insert(cell, colour.clamp(1, colouring.colours()));
+            self.revision = Revision::next();
+        }
+    }
+
+    /// Records the paint colour for a cell an edit just placed.
+    fn painted(&mut self, cell: I64Vec2) {
+        if self.colouring.is_some() {
+            self.colours.insert(cell, self.paint_colour());
+        }
+    }
+
+    /// How many consecutive generations the cell has been alive, or 0 if it isn't.
+    pub fn age(&self, cell: I64Vec2) -> u32 {
+        if !self.is_alive(cell) {
+            return 0;
+        }
+        let birth = self.births.get(&cell).copied().unwrap_or(self.generation);
+        u32::try_from(self.generation - birth + 1).unwrap_or(u32::MAX)
+    }
+
+    /// Dates a live cell's birth so it is `age` generations old, as [`Life::age`] counts them, so
+    /// a cleared board can be put back as it was. Does nothing to other cells.
+    pub fn set_age(&mut self, cell: I64Vec2, age: u32) {
+        if self.is_alive(cell) {
+            self.births.insert(cell, (self.generation + 1).saturating_sub(age.max(1) as u64));
+            self.revision = Revision::next();
+        }
+    }
+
+    /// Up to `count` of the cells that have been alive the longest, oldest first, with their
+    /// ages as [`Life::age`] counts them. Ties go to the topmost, then leftmost, cell.
+    pub fn oldest_cells(&self, count: usize) -> Vec<(I64Vec2, u64)> {
+        live_cells(&self.cells)
+            .map(|cell| (cell, self.generation - self.births.get(&cell).copied().unwrap_or(self.generation) + 1))
+            .k_smallest_by_key(count, |&(cell, age)| (Reverse(age), cell.y, cell.x))
+            .collect()
+    }
+
+    /// Whether the last step left the board unchanged, as for a still life or an empty board.
+    /// Any edit since then resets it.
+    pub fn is_stable(&self) -> bool {
+        self.stable
+    }
+
+    /// The births and deaths of the last step, or of the whole jump when HashLife advanced
+    /// several generations at once. Any edit since then resets it.
+    pub fn last_step(&self) -> StepStats {
+        self.last_step
+    }
+
+    /// Roughly how much memory the board's hash tables take: the cells, their ages and colours,
+    /// the step buffers and the incremental engine's counts. HashLife's nodes aren't counted.
+    pub fn memory_bytes(&self) -> usize {
+        table_bytes(&self.cells)
+            + table_bytes(&self.births)
+            + table_bytes(&self.colours)
+            + table_bytes(&self.buffers.counts)
+            + table_bytes(&self.buffers.next)
+            + self.incremental.as_ref().map_or(0, NeighbourCounts::memory_bytes)
+    }
+
+    /// Changes whenever the board is stepped or edited, so callers can tell when to redraw it.
+    /// No two boards share a revision unless one is a copy of the other.
+    pub fn revision(&self) -> u64 {
+        self.revision.0
+    }
+
+    /// How many generations have run since the board was created or last cleared.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Renumbers the current generation, keeping every cell's age.
+    pub fn set_generation(&mut self, generation: u64) {
+        for birth in self.births.values_mut() {
+            *birth = generation.saturating_sub(self.generation - *birth);
+        }
+        self.generation = generation;
+    }
+
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
+
+    pub fn set_engine(&mut self, engine: Engine) {
+        self.engine = engine;
+        self.hashlife = None;
+        self.incremental = None;
+    }
+
+    /// Whether steps actually go through HashLife, which only supports two-state Moore rules of
+    /// range 1 on an unbounded board.
+    pub fn uses_hashlife(&self) -> bool {
+        self.engine == Engine::HashLife
+            && self.rule.states == 2
+            && self.rule.neighbourhood == Neighbourhood::Moore
+            && self.rule.range == 1
+            && self.torus.is_none()
+            && self.colouring.is_none()
+            && self.noise.is_none()
+    }
+
+    pub fn torus(&self) -> Option<Torus> {
+        self.torus
+    }
+
+    /// Wraps the board onto a torus, removing the cells outside it, or unwraps it with `None`.
+    pub fn set_torus(&mut self, torus: Option<Torus>) {
+        if let Some(torus) = torus {
+            self.cells.retain(|&cell, _| torus.contains(cell));
+            self.births.retain(|&cell, _| torus.contains(cell));
+            self.colours.retain(|&cell, _| torus.contains(cell));
+        }
+        self.torus = torus;
+        self.edited();
+    }
+
+    /// Whether the cell is on the board, which is only ever false outside a torus.
+    pub fn contains(&self, cell: I64Vec2) -> bool {
+        self.torus.is_none_or(|torus| torus.contains(cell))
+    }
+
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Switches to a new rule, dropping decaying states the new rule doesn't have.
+    pub fn set_rule(&mut self, rule: Rule) {
+        self.cells.retain(|_, state| *state < rule.states);
+        self.rule = rule;
+        self.edited();
+    }
+
+    /// Kills any non-dead cell, decaying or not, and brings a dead cell to life. Edits to cells off
+    /// the board are ignored.
+    pub fn toggle(&mut self, cell: I64Vec2) {
+        if !self.contains(cell) {
+            return;
+        }
+        self.births.remove(&cell);
+        if self.cells.remove(&cell).is_none() {
+            self.cells.insert(cell, ALIVE);
+            self.births.insert(cell, self.generation);
+            self.painted(cell);
+        }
+        self.edited();
+    }
+
+    pub fn insert(&mut self, cell: I64Vec2) {
+        if !self.contains(cell) {
+            return;
+        }
+        self.cells.insert(cell, ALIVE);
+        self.births.entry(cell).or_insert(self.generation);
+        self.painted(cell);
+        self.edited();
+    }
+
+    /// Sets the cell's state directly, where 0 removes it.
+    pub fn set_state(&mut self, cell: I64Vec2, state: u8) {
+        if !self.contains(cell) {
+            return;
+        }
+        if state == 0 {
+            self.cells.remove(&cell);
+        } else {
+            self.cells.insert(cell, state);
+        }
+        if state == ALIVE {
+            self.births.insert(cell, self.generation);
+            self.painted(cell);
+        } else {
+            self.births.remove(&cell);
+        }
+        self.edited();
+    }
+
+    pub fn is_alive(&self, cell: I64Vec2) -> bool {
+        self.state(cell) == ALIVE
+    }
+
+    /// Returns the cell's state, where 0 is dead and 1 is alive.
+    pub fn state(&self, cell: I64Vec2) -> u8 {
+        self.cells.get(&cell).copied().unwrap_or(0)
+    }
+
+    pub fn population(&self) -> usize {
+        self.cells.values().filter(|&&state| state == ALIVE).count()
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.births.clear();
+        self.colours.clear();
+        self.generation = 0;
+        self.edited();
+    }
+
+    /// All non-dead cells, including decaying ones, with their states.
+    pub fn cells(&self) -> &HashMap<I64Vec2, u8> {
+        &self.cells
+    }
+
+    pub fn live_cells(&self) -> HashSet<I64Vec2> {
+        live_cells(&self.cells).collect()
+    }
+
+    /// A copy of the board, ages and revision included, that leaves out the engines' caches to
+    /// be rebuilt if the copy is ever stepped itself.
+    pub fn without_caches(&self) -> Life {
+        Life {
+            cells: self.cells.clone(),
+            rule: self.rule,
+            engine: self.engine,
+            generation: self.generation,
+            torus: self.torus,
+            births: self.births.clone(),
+            colouring: self.colouring,
+            colours: self.colours.clone(),
+            paint_colour: self.paint_colour,
+            noise: self.noise,
+            hashlife: None,
+            incremental: None,
+            buffers: StepBuffers::default(),
+            stable: self.stable,
+            last_step: self.last_step,
+            revision: self.revision,
+        }
+    }
+
+    /// Forgets what was derived from the last step once the board changes outside of one.
+    fn edited(&mut self) {
+        self.revision = Revision::next();
+        self.hashlife = None;
+        self.incremental = None;
+        self.stable = false;
+        self.last_step = StepStats::default();
+    }
+}
+
+/// Boards are equal when they have the same cells and rule, whichever engine they use and
+/// however many generations they have run.
+impl PartialEq for Life {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells == other.cells && self.rule == other.rule
+    }
+}
+
+impl Eq for Life {}
+
+impl Extend<I64Vec2> for Life {
+    fn extend<T: IntoIterator<Item = I64Vec2>>(&mut self, cells: T) {
+        for cell in cells {
+            if self.contains(cell) {
+                self.cells.insert(cell, ALIVE);
+                self.births.entry(cell).or_insert(self.generation);
+                self.painted(cell);
+            }
+        }
+        self.edited();
+    }
+}
+
+/// Compares the populations first, so the full comparison only runs on likely matches.
+fn is_unchanged(previous: &HashMap<I64Vec2, u8>, next: &HashMap<I64Vec2, u8>) -> bool {
+    previous.len() == next.len() && previous == next
+}
+
+/// Returns the inclusive `(min, max)` corners of the live cells, or `None` for an empty board.
+pub fn bounding_box(cells: &HashSet<I64Vec2>) -> Option<(I64Vec2, I64Vec2)> {
+    let mut iter = cells.iter();
+    let first = *iter.next()?;
+    Some(iter.fold((first, first), |(min, max), &cell| (min.min(cell), max.max(cell))))
+}
+
+/// Returns the average position of the cells, rounded down, or `None` when there are none.
+pub fn centroid(cells: impl IntoIterator<Item = I64Vec2>) -> Option<I64Vec2> {
+    let (count, sum) = cells.into_iter().fold((0i128, [0i128; 2]), |(count, [x, y]), cell| {
+        (count + 1, [x + cell.x as i128, y + cell.y as i128])
+    });
+    let mean = |total: i128| total.div_euclid(count) as i64;
+    (count > 0).then(|| I64Vec2::new(mean(sum[0]), mean(sum[1])))
+}
+
+/// Applies a B/S/C rule: live cells that fail survival start decaying, decaying cells age
+/// until they reach the state count, and only dead cells can be born.
+pub fn process_cells(cells: &HashMap<I64Vec2, u8>, rule: &Rule, torus: Option<&Torus>) -> HashMap<I64Vec2, u8> {
+    let mut next = HashMap::new();
+    process_cells_into(cells, rule, torus, None, &mut HashMap::new(), &mut next);
+    next
+}
+
+/// [`process_cells`] into `next`, counting neighbours in `counts`. Both are cleared first, so
+/// their allocations can be reused from step to step. Under `noise`, a birth or survival the
+/// rule allows only happens if the cell's roll says so; a live cell that fails to survive
+/// starts decaying as if the rule had failed it.
+pub fn process_cells_into(
+    cells: &HashMap<I64Vec2, u8>,
+    rule: &Rule,
+    torus: Option<&Torus>,
+    noise: Option<&Noise>,
+    counts: &mut HashMap<I64Vec2, usize>,
+    next: &mut HashMap<I64Vec2, u8>,
+) {
+    convolve_into(cells, &rule.kernel(), torus, counts);
+
+    let aged = cells.iter().filter_map(|(&cell, &state)| {
+        let neighbours = counts.get(&cell).copied().unwrap_or(0);
+        let mut next = next_state(rule, state, neighbours);
+        if next == ALIVE && state == ALIVE && noise.is_some_and(|noise| !noise.allows_survival(cell)) {
+            // Starts decaying, or dies outright under a two-state rule.
+            next = if ALIVE + 1 < rule.states { ALIVE + 1 } else { 0 };
+        }
+        (next != 0).then_some((cell, next))
+    });
+
+    let births = counts
+        .iter()
+        .filter(|&(cell, &count)| rule.birth[count] && !cells.contains_key(cell))
+        .filter(|&(&cell, _)| noise.is_none_or(|noise| noise.allows_birth(cell)))
+        .map(|(&cell, _)| (cell, ALIVE));
+
+    next.clear();
+    next.extend(aged.chain(births));
+}
+
+/// The state a cell with `neighbours` live neighbours moves to, where 0 is dead.
+pub(crate) fn next_state(rule: &Rule, state: u8, neighbours: usize) -> u8 {
+    match state {
+        0 if rule.birth[neighbours] => ALIVE,
+        0 => 0,
+        ALIVE if rule.survival[neighbours] => ALIVE,
+        _ if state + 1 < rule.states => state + 1,
+        _ => 0,
+    }
+}
+
+/// Boards with at least this many cells count their neighbours on several threads.
+pub const PARALLEL_THRESHOLD: usize = 100_000;
+
+/// Sums the kernel's weights over the live cells around every cell near a live cell. Decaying
+/// cells don't count. On a torus, neighbours past an edge are counted on the opposite edge,
+/// and past a wall they aren't counted at all.
+pub fn convolve(cells: &HashMap<I64Vec2, u8>, kernel: &Kernel, torus: Option<&Torus>) -> HashMap<I64Vec2, usize> {
+    let mut counts = HashMap::new();
+    convolve_into(cells, kernel, torus, &mut counts);
+    counts
+}
+
+/// [`convolve`] into `counts`, which is cleared first. Small boards reuse its allocation;
+/// large ones are counted on several threads into new maps anyway.
+pub fn convolve_into(
+    cells: &HashMap<I64Vec2, u8>,
+    kernel: &Kernel,
+    torus: Option<&Torus>,
+    counts: &mut HashMap<I64Vec2, usize>,
+) {
+    counts.clear();
+    if cells.len() >= PARALLEL_THRESHOLD {
+        *counts = convolve_parallel(cells, kernel);
+    } else {
+        count_neighbours(live_cells(cells), kernel, counts);
+    }
+
+    if let Some(torus) = torus {
+        let outside = counts.keys().copied().filter(|&cell| !torus.contains(cell)).collect_vec();
+        for cell in outside {
+            let count = counts.remove(&cell).unwrap_or(0);
+            if let Some(cell) = torus.neighbour(cell) {
+                *counts.entry(cell).or_insert(0) += count;
+            }
+        }
+    }
+}
+
+pub fn convolve_serial(cells: &HashMap<I64Vec2, u8>, kernel: &Kernel) -> HashMap<I64Vec2, usize> {
+    let mut counts = HashMap::new();
+    count_neighbours(live_cells(cells), kernel, &mut counts);
+    counts
+}
+
+/// Adds each of the kernel's weights to the count at its offset from every cell.
+fn count_neighbours(cells: impl Iterator<Item = I64Vec2>, kernel: &Kernel, counts: &mut HashMap<I64Vec2, usize>) {
+    for cell in cells {
+        for &(delta, weight) in kernel.entries() {
+            *counts.entry(cell + delta).or_insert(0) += weight;
+        }
+    }
+}
+
+/// Splits the live cells across the available cores, counts each share into its own map and
+/// merges the maps into the first one.
+pub fn convolve_parallel(cells: &HashMap<I64Vec2, u8>, kernel: &Kernel) -> HashMap<I64Vec2, usize> {
+    let live = live_cells(cells).collect_vec();
+    let threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let chunk_size = live.len().div_ceil(threads).max(1);
+
+    let partial_counts = thread::scope(|scope| {
+        let handles = live
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut counts = HashMap::new();
+                    count_neighbours(chunk.iter().copied(), kernel, &mut counts);
+                    counts
+                })
+            })
+            .collect_vec();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect_vec()
+    });
+
+    let mut partial_counts = partial_counts.into_iter();
+    let mut counts = partial_counts.next().unwrap_or_default();
+    for partial in partial_counts {
+        for (cell, count) in partial {
+            *counts.entry(cell).or_insert(0) += count;
+        }
+    }
+    counts
+}
+
+/// Roughly how much memory a hash table takes, counting a control byte per slot as the
+/// standard hash table does.
+pub(crate) fn table_bytes<K, V>(table: &HashMap<K, V>) -> usize {
+    table.capacity() * (mem::size_of::<(K, V)>() + 1)
+}
+
+fn live_cells(cells: &HashMap<I64Vec2, u8>) -> impl Iterator<Item = I64Vec2> + '_ {
+    cells.iter().filter(|&(_, &state)| state == ALIVE).map(|(&cell, _)| cell)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::patterns::{glider, random_soup};
+    use crate::rng::Rng;
+
+    fn life_from(cells: &[(i64, i64)]) -> Life {
+        let mut life = Life::new();
+        for &(x, y) in cells {
+            life.toggle(I64Vec2::new(x, y));
+        }
+        life
+    }
+
+    #[test]
+    fn blinker_oscillates() {
+        let horizontal = life_from(&[(-1, 0), (0, 0), (1, 0)]);
+        let vertical = life_from(&[(0, -1), (0, 0), (0, 1)]);
+
+        let mut life = horizontal.clone();
+        life.step();
+        assert_eq!(life, vertical);
+        life.step();
+        assert_eq!(life, horizontal);
+    }
+
+    #[test]
+    fn block_is_stable() {
+        let block = life_from(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+
+        let mut life = block.clone();
+        for _ in 0..4 {
+            life.step();
+            assert_eq!(life, block);
+        }
+    }
+
+    #[test]
+    fn counts_births_and_deaths_with_every_engine() {
+        for engine in [Engine::Naive, Engine::Incremental, Engine::HashLife] {
+            let mut blinker = life_from(&[(-1, 0), (0, 0), (1, 0)]);
+            blinker.set_engine(engine);
+            blinker.step();
+            assert_eq!(blinker.last_step(), StepStats { births: 2, deaths: 2 }, "{engine:?}");
+            blinker.toggle(I64Vec2::new(5, 5));
+            assert_eq!(blinker.last_step(), StepStats::default());
+        }
+
+        // The dying cell of a Generations rule stops counting as alive when it starts to decay.
+        let mut life = life_from(&[(0, 0)]);
+        life.set_rule(Rule::parse("B3/S23/C3").unwrap());
+        life.step();
+        assert_eq!(life.last_step(), StepStats { births: 0, deaths: 1 });
+        life.step();
+        assert_eq!(life.last_step(), StepStats::default());
+    }
+
+    #[test]
+    fn only_unchanging_boards_are_stable() {
+        let mut block = life_from(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+        assert!(!block.is_stable());
+        block.step();
+        assert!(block.is_stable());
+        block.toggle(I64Vec2::new(5, 5));
+        assert!(!block.is_stable());
+
+        let mut blinker = life_from(&[(-1, 0), (0, 0), (1, 0)]);
+        blinker.set_engine(Engine::HashLife);
+        for _ in 0..3 {
+            blinker.step();
+            assert!(!blinker.is_stable());
+        }
+        blinker.advance(2);
+        assert!(!blinker.is_stable());
+
+        let mut empty = Life::new();
+        empty.step();
+        assert!(empty.is_stable());
+    }
+
+    #[test]
+    fn revision_changes_with_the_board() {
+        let mut life = life_from(&[(0, 0)]);
+        let placed = life.revision();
+        life.set_engine(Engine::HashLife);
+        life.set_generation(5);
+        assert_eq!(life.revision(), placed);
+
+        life.step();
+        let stepped = life.revision();
+        assert_ne!(stepped, placed);
+        life.toggle(I64Vec2::ONE);
+        assert_ne!(life.revision(), stepped);
+        assert_ne!(Life::new().revision(), Life::new().revision());
+        assert_eq!(life.clone().revision(), life.revision());
+    }
+
+    #[test]
+    fn toggle_adds_and_removes() {
+        let mut life = Life::new();
+        let cell = I64Vec2::new(3, -7);
+
+        life.toggle(cell);
+        assert!(life.is_alive(cell));
+        assert_eq!(life.population(), 1);
+
+        life.toggle(cell);
+        assert!(!life.is_alive(cell));
+        assert_eq!(life.population(), 0);
+    }
+
+    #[test]
+    fn counts_generations_until_cleared() {
+        let mut life = life_from(&[(0, 0), (1, 0), (2, 0)]);
+        assert_eq!(life.generation(), 0);
+
+        life.step();
+        life.advance(9);
+        assert_eq!(life.generation(), 10);
+        life.toggle(I64Vec2::new(5, 5));
+        assert_eq!(life.generation(), 10);
+
+        life.clear();
+        assert_eq!(life.generation(), 0);
+    }
+
+    #[test]
+    fn tracks_consecutive_generations_alive() {
+        let mut life = life_from(&[(-1, 0), (0, 0), (1, 0)]);
+        assert_eq!(life.age(I64Vec2::ZERO), 1);
+
+        life.step();
+        life.step();
+        assert_eq!(life.age(I64Vec2::ZERO), 3);
+        assert_eq!(life.age(I64Vec2::new(-1, 0)), 1);
+        assert_eq!(life.age(I64Vec2::new(0, -1)), 0);
+        assert!(life.live_cells().iter().all(|&cell| life.age(cell) > 0));
+
+        life.toggle(I64Vec2::ZERO);
+        life.toggle(I64Vec2::ZERO);
+        assert_eq!(life.age(I64Vec2::ZERO), 1);
+
+        life.clear();
+        life.toggle(I64Vec2::new(-1, 0));
+        assert_eq!(life.age(I64Vec2::new(-1, 0)), 1);
+    }
+
+    #[test]
+    fn lists_the_oldest_cells_first() {
+        // A block beside a blinker: the block and the blinker's middle outlive its ends.
+        let mut life = life_from(&[(0, 0), (1, 0), (0, 1), (1, 1), (5, 0), (6, 0), (7, 0)]);
+        life.step();
+        life.step();
+        let oldest = life.oldest_cells(6);
+        let expected = [(0, 0), (1, 0), (6, 0), (0, 1), (1, 1)].map(|(x, y)| (I64Vec2::new(x, y), 3));
+        assert_eq!(oldest[..5], expected);
+        assert_eq!(oldest[5], (I64Vec2::new(5, 0), 1));
+        assert_eq!(life.oldest_cells(0), []);
+        assert_eq!(life.oldest_cells(100).len(), life.population());
+    }
+
+    #[test]
+    fn hashlife_ages_cells_across_jumps() {
+        let mut life = life_from(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+        life.set_engine(Engine::HashLife);
+        life.advance(10);
+        life.step();
+        assert_eq!(life.age(I64Vec2::ZERO), 12);
+    }
+
+    #[test]
+    fn highlife_births_on_six() {
+        let mut conway = life_from(&[(-1, -1), (0, -1), (1, -1), (-1, 1), (0, 1), (1, 1)]);
+        let mut highlife = conway.clone();
+        highlife.set_rule(Rule::parse("B36/S23").unwrap());
+
+        conway.step();
+        highlife.step();
+        assert!(!conway.is_alive(I64Vec2::ZERO));
+        assert!(highlife.is_alive(I64Vec2::ZERO));
+    }
+
+    #[test]
+    fn von_neumann_ignores_diagonal_neighbours() {
+        // Under B1/S, a lone cell gives birth to each neighbour it has.
+        for (rule, expected) in [("B1/S", 8), ("B1/SV", 4)] {
+            let mut life = life_from(&[(0, 0)]);
+            life.set_rule(Rule::parse(rule).unwrap());
+            for engine in [Engine::Naive, Engine::HashLife] {
+                let mut stepped = life.clone();
+                stepped.set_engine(engine);
+                stepped.step();
+                assert_eq!(stepped.population(), expected, "{rule} {engine:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn generations_cells_decay_before_dying() {
+        let mut life = life_from(&[(0, 0)]);
+        life.set_rule(Rule::parse("B3/S23/C4").unwrap());
+
+        life.step();
+        assert_eq!(life.state(I64Vec2::ZERO), 2);
+        assert_eq!(life.population(), 0);
+        life.step();
+        assert_eq!(life.state(I64Vec2::ZERO), 3);
+        life.step();
+        assert_eq!(life.state(I64Vec2::ZERO), 0);
+        assert!(life.cells().is_empty());
+    }
+
+    #[test]
+    fn decaying_cells_block_births_and_do_not_count_as_neighbours() {
+        // Brian's Brain: a dying cell neither counts as a neighbour nor can be reborn.
+        let mut life = life_from(&[(0, 0), (1, 0)]);
+        life.set_rule(Rule::parse("B2/S/C3").unwrap());
+
+        life.step();
+        assert_eq!(life.state(I64Vec2::new(0, 0)), 2);
+        assert_eq!(life.state(I64Vec2::new(1, 0)), 2);
+        assert_eq!(life.population(), 4);
+
+        life.step();
+        assert_eq!(life.state(I64Vec2::new(0, 0)), 0);
+        assert!(!life.is_alive(I64Vec2::new(0, 0)));
+    }
+
+    #[test]
+    fn steps_without_wrapping_past_i32_bounds() {
+        let edge = i32::MAX as i64;
+        let mut life = life_from(&[(edge - 1, 0), (edge, 0), (edge + 1, 0)]);
+        life.step();
+
+        let expected = life_from(&[(edge, -1), (edge, 0), (edge, 1)]);
+        assert_eq!(life, expected);
+        assert!(life.live_cells().iter().all(|cell| cell.x == edge));
+
+        let counts = convolve(expected.cells(), &Kernel::default(), None);
+        assert_eq!(counts.get(&I64Vec2::new(edge + 1, 0)), Some(&3));
+        assert_eq!(counts.get(&I64Vec2::new(i32::MIN as i64, 0)), None);
+    }
+
+    #[test]
+    fn parallel_convolve_matches_serial() {
+        let mut rng = Rng::seed_from_u64(1);
+        let soup = random_soup(&mut rng, I64Vec2::ZERO, I64Vec2::splat(200), 0.5);
+        let mut life = Life::from_cells(soup);
+        life.set_rule(Rule::parse("B3/S23/C3").unwrap());
+        life.step();
+
+        for neighbourhood in [Neighbourhood::Moore, Neighbourhood::VonNeumann] {
+            for range in [1, 3] {
+                let kernel = Kernel::new(neighbourhood, range);
+                assert_eq!(convolve_parallel(life.cells(), &kernel), convolve_serial(life.cells(), &kernel));
+            }
+        }
+        assert_eq!(convolve_parallel(&HashMap::new(), &Kernel::default()), HashMap::new());
+    }
+
+    #[test]
+    fn one_colour_steps_like_life() {
+        let soup = random_soup(&mut Rng::seed_from_u64(5), I64Vec2::ZERO, I64Vec2::splat(60), 0.4);
+        let mut conway = Life::from_cells(soup.clone());
+        let mut immigration = Life::from_cells(soup.clone());
+        immigration.set_colouring(Some(Colouring::Immigration));
+        immigration.set_engine(Engine::HashLife);
+        // A QuadLife region all of one colour besides 1 stays that colour.
+        let mut quad_life = Life::new();
+        quad_life.set_colouring(Some(Colouring::QuadLife));
+        quad_life.set_paint_colour(3);
+        quad_life.extend(soup);
+        for _ in 0..50 {
+            conway.step();
+            immigration.step();
+            quad_life.step();
+        }
+        assert_eq!(immigration, conway);
+        assert_eq!(quad_life, conway);
+        assert!(immigration.live_cells().iter().all(|&cell| immigration.colour(cell) == 1));
+        assert!(quad_life.live_cells().iter().all(|&cell| quad_life.colour(cell) == 3));
+    }
+
+    #[test]
+    fn newborns_take_their_parents_majority_colour() {
+        let mut life = Life::new();
+        life.set_colouring(Some(Colouring::Immigration));
+        life.set_paint_colour(2);
+        life.extend([I64Vec2::NEG_X, I64Vec2::ZERO]);
+        life.set_paint_colour(1);
+        life.insert(I64Vec2::X);
+
+        life.step();
+        assert_eq!(life.live_cells(), [I64Vec2::NEG_Y, I64Vec2::ZERO, I64Vec2::Y].into());
+        assert_eq!([life.colour(I64Vec2::NEG_Y), life.colour(I64Vec2::ZERO), life.colour(I64Vec2::Y)], [2, 2, 2]);
+        assert_eq!(life.colour(I64Vec2::X), 0);
+
+        life.step();
+        assert_eq!(life.colour(I64Vec2::NEG_X), 2);
+        life.set_colouring(None);
+        life.set_colouring(Some(Colouring::Immigration));
+        assert_eq!(life.colour(I64Vec2::NEG_X), 1);
+    }
+
+    #[test]
+    fn quad_life_newborns_of_three_colours_take_the_fourth() {
+        let mut life = Life::new();
+        life.set_colouring(Some(Colouring::QuadLife));
+        for (colour, cell) in [(1, I64Vec2::NEG_X), (2, I64Vec2::ZERO), (4, I64Vec2::X)] {
+            life.set_paint_colour(colour);
+            life.insert(cell);
+        }
+        life.step();
+        assert_eq!([life.colour(I64Vec2::NEG_Y), life.colour(I64Vec2::Y)], [3, 3]);
+        // The centre survives with its own colour.
+        assert_eq!(life.colour(I64Vec2::ZERO), 2);
+
+        assert_eq!(Colouring::QuadLife.newborn_colour(&[0, 2, 1, 0]), 2);
+        assert_eq!(Colouring::QuadLife.newborn_colour(&[1, 1, 0, 1]), 3);
+        assert_eq!(Colouring::Immigration.newborn_colour(&[1, 1]), 1);
+        life.set_colouring(Some(Colouring::Immigration));
+        life.set_paint_colour(4);
+        assert_eq!(life.paint_colour(), 2);
+    }
+
+    #[test]
+    fn noise_is_reproducible_and_certain_noise_changes_nothing() {
+        let soup = random_soup(&mut Rng::seed_from_u64(4), I64Vec2::ZERO, I64Vec2::splat(60), 0.4);
+        let run = |noise: Option<Noise>| {
+            let mut life = Life::from_cells(soup.clone());
+            life.set_noise(noise);
+            for _ in 0..20 {
+                life.step();
+            }
+            life.live_cells()
+        };
+        let plain = run(None);
+        assert_eq!(run(Some(Noise::default())), plain);
+
+        let noisy = Noise { birth: 0.8, survival: 0.9, seed: 11 };
+        assert_eq!(run(Some(noisy)), run(Some(noisy)));
+        assert_ne!(run(Some(noisy)), plain);
+        assert_ne!(run(Some(Noise { seed: 12, ..noisy })), run(Some(noisy)));
+
+        // No births at all leaves only survivors of the first board.
+        let mut life = Life::from_cells(soup.clone());
+        life.set_noise(Some(Noise { birth: 0.0, ..Noise::default() }));
+        life.set_rule(Rule::parse("B3/S23/C3").unwrap());
+        life.step();
+        assert!(life.live_cells().is_subset(&soup));
+        assert!(life.cells().values().any(|&state| state == 2));
+    }
+
+    #[test]
+    fn centroid_is_the_mean_cell() {
+        assert_eq!(centroid([]), None);
+        assert_eq!(centroid(glider()), Some(I64Vec2::new(1, 1)));
+        assert_eq!(centroid([I64Vec2::new(-3, 0), I64Vec2::new(0, 1)]), Some(I64Vec2::new(-2, 0)));
+        let far = I64Vec2::splat(i64::MAX);
+        assert_eq!(centroid([far, far, far - 2]), Some(far - 1));
+    }
+
+    #[test]
+    fn memory_grows_with_the_board() {
+        assert_eq!(Life::new().memory_bytes(), 0);
+        let soup = random_soup(&mut Rng::seed_from_u64(2), I64Vec2::ZERO, I64Vec2::splat(100), 0.5);
+        let mut life = Life::from_cells(soup);
+        let before = life.memory_bytes();
+        assert!(before >= life.population() * mem::size_of::<(I64Vec2, u8)>());
+        life.set_engine(Engine::Incremental);
+        life.step();
+        assert!(life.memory_bytes() > before);
+    }
+
+    #[test]
+    fn hashlife_engine_matches_naive_across_edits() {
+        let mut naive = life_from(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        let mut hashlife = naive.clone();
+        hashlife.set_engine(Engine::HashLife);
+        assert!(hashlife.uses_hashlife());
+
+        hashlife.advance(37);
+        naive.advance(37);
+        assert_eq!(hashlife, naive);
+
+        hashlife.toggle(I64Vec2::new(20, 20));
+        naive.toggle(I64Vec2::new(20, 20));
+        hashlife.step();
+        naive.step();
+        assert_eq!(hashlife, naive);
+
+        hashlife.set_rule(Rule::parse("B3/S23/C3").unwrap());
+        assert!(!hashlife.uses_hashlife());
+    }
+
+    #[test]
+    fn reused_buffers_step_like_fresh_maps() {
+        let soup = random_soup(&mut Rng::seed_from_u64(5), I64Vec2::ZERO, I64Vec2::splat(24), 0.4);
+        let mut life = Life::from_cells(soup);
+        life.set_rule(Rule::parse("B2/S/C3").unwrap());
+        life.set_torus(Torus::new(I64Vec2::splat(20)));
+        let mut fresh = life.cells().clone();
+        for _ in 0..20 {
+            life.step();
+            fresh = process_cells(&fresh, life.rule(), life.torus().as_ref());
+            assert_eq!(life.cells(), &fresh);
+        }
+    }
+
+    #[test]
+    fn glider_wraps_around_the_torus() {
+        let glider = life_from(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        let mut life = glider.clone();
+        life.set_torus(Torus::new(I64Vec2::new(8, 6)));
+        life.set_engine(Engine::HashLife);
+        assert!(!life.uses_hashlife());
+
+        life.advance(24);
+        let crossed = life.live_cells();
+        assert!(crossed.iter().all(|&cell| life.torus().unwrap().contains(cell)));
+        assert!(crossed.contains(&I64Vec2::new(0, 1)) && crossed.contains(&I64Vec2::new(7, 0)), "{crossed:?}");
+        assert_eq!(crossed.len(), 5);
+
+        // 48 generations move the glider 12 cells, two laps down and one and a half across.
+        life.advance(24);
+        let mut expected = glider.clone();
+        expected.advance(48);
+        let expected: HashSet<I64Vec2> =
+            expected.live_cells().iter().map(|&cell| life.torus().unwrap().wrap(cell)).collect();
+        assert_eq!(life.live_cells(), expected);
+
+        life.advance(48);
+        assert_eq!(life.live_cells(), glider.live_cells());
+    }
+
+    #[test]
+    fn glider_stops_at_a_wall_instead_of_wrapping() {
+        let glider = life_from(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        let mut wrapped = glider.clone();
+        wrapped.set_torus(Torus::new(I64Vec2::new(8, 6)));
+        let mut walled = glider.clone();
+        walled.set_torus(Torus::walled(I64Vec2::new(8, 6)));
+        let mut incremental = walled.clone();
+        incremental.set_engine(Engine::Incremental);
+
+        for _ in 0..96 {
+            wrapped.step();
+            walled.step();
+            incremental.step();
+        }
+        assert_eq!(wrapped.live_cells(), glider.live_cells());
+        // Hitting the bottom wall leaves a block resting against it.
+        let block: HashSet<I64Vec2> = [(4, 4), (5, 4), (4, 5), (5, 5)].map(|(x, y)| I64Vec2::new(x, y)).into();
+        assert_eq!(walled.live_cells(), block);
+        assert!(walled.is_stable());
+        assert_eq!(incremental.live_cells(), block);
+    }
+
+    #[test]
+    fn torus_ignores_cells_off_the_board() {
+        let mut life = life_from(&[(0, 0), (5, 5), (-1, 2)]);
+        life.set_torus(Torus::new(I64Vec2::new(4, 4)));
+        assert_eq!(life.live_cells(), HashSet::from([I64Vec2::ZERO]));
+
+        life.toggle(I64Vec2::new(4, 0));
+        life.set_state(I64Vec2::new(-1, 0), ALIVE);
+        life.extend([I64Vec2::new(0, 9), I64Vec2::new(3, 3)]);
+        assert_eq!(life.live_cells(), HashSet::from([I64Vec2::ZERO, I64Vec2::new(3, 3)]));
+
+        life.set_torus(None);
+        life.toggle(I64Vec2::new(-1, 0));
+        assert!(life.is_alive(I64Vec2::new(-1, 0)));
+    }
+
+    #[test]
+    fn incremental_engine_matches_naive_across_edits() {
+        let mut naive = life_from(&[(0, 0), (1, 0), (2, 0), (1, 2), (2, 3)]);
+        naive.set_rule(Rule::parse("B3/S23/C3").unwrap());
+        let mut incremental = naive.clone();
+        incremental.set_engine(Engine::Incremental);
+
+        for edit in [I64Vec2::new(4, 4), I64Vec2::new(1, 1), I64Vec2::new(-2, 0)] {
+            naive.advance(7);
+            incremental.advance(7);
+            assert_eq!(incremental, naive);
+            assert_eq!(incremental.is_stable(), naive.is_stable());
+            assert!(naive.live_cells().iter().all(|&cell| incremental.age(cell) == naive.age(cell)));
+
+            naive.toggle(edit);
+            incremental.toggle(edit);
+        }
+    }
+
+    #[test]
+    fn bounding_box_covers_all_cells() {
+        assert_eq!(bounding_box(&HashSet::new()), None);
+
+        let life = life_from(&[(2, -1), (-3, 4), (0, 0)]);
+        assert_eq!(
+            bounding_box(&life.live_cells()),
+            Some((I64Vec2::new(-3, -1), I64Vec2::new(2, 4))),
+        );
+    }
+}
@@ -1,39 +1,383 @@
 use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use std::{env, fs, process};
 use raylib::prelude::*;
-use glam::IVec2;
-use itertools::Itertools;
+use glam::{I64Vec2, IVec2, Vec2};
+use rust_game_of_life::brush::{line, Brush, ShapeTool, Symmetry};
+use rust_game_of_life::camera::{
+    cell_at, cell_inset, centre_cell, centre_on, drag_cells, fit_box, follow_step, is_drag, pan_step, parse_cell,
+    scale_drag, snap_to_grid, zoom_at, Bookmark, SmoothZoom, BOOKMARK_SLOTS, DEFAULT_CELL_SIZE, DRAG_THRESHOLD,
+    MAX_CELL_SIZE, MAX_DRAG_THRESHOLD, MAX_PAN_SENSITIVITY, MIN_CELL_SIZE, MIN_PAN_SENSITIVITY,
+};
+use rust_game_of_life::chunked::ChunkedWorld;
+use rust_game_of_life::cli::{Args, USAGE};
+use rust_game_of_life::config::{config_dir, Config, CONFIG_FILE_NAME};
+use rust_game_of_life::cycle::{Cycle, CycleDetector};
+use rust_game_of_life::diff::BoardDiff;
+use rust_game_of_life::gif::GifEncoder;
+use rust_game_of_life::gliders::{describe_gliders, direction_name, find_gliders, Glider};
+use rust_game_of_life::headless::{parse_target, simulate, simulate_csv, Jump};
+use rust_game_of_life::history::{EditHistory, GenerationHistory, Snapshot};
+use rust_game_of_life::kernel::Kernel;
+use rust_game_of_life::keys::{Action, KeyBindings, DEFAULT_KEYS_PATH};
+use rust_game_of_life::life::{
+    bounding_box, centroid, convolve, convolve_parallel, convolve_serial, Colouring, Engine, Life, StepStats, ALIVE,
+};
+use rust_game_of_life::minimap::Minimap;
+use rust_game_of_life::noise::{Noise, DEFAULT_NOISE_CHANCE};
+use rust_game_of_life::pattern_file::{load_pattern, load_pattern_with_metadata};
+use rust_game_of_life::patterns::{gosper_glider_gun, random_soup, PatternPalette};
+use rust_game_of_life::pixel_art::{load_image_cells, render_cells, ExportError};
+use rust_game_of_life::plaintext::to_cells;
+use rust_game_of_life::population::PopulationHistory;
+use rust_game_of_life::rle::{parse_rle, to_rle_with_rule, to_rle_with_view};
+use rust_game_of_life::rng::Rng;
+use rust_game_of_life::rule::{Neighbourhood, RulePreset};
+use rust_game_of_life::ruler::Measurement;
+use rust_game_of_life::selection::CellRect;
+use rust_game_of_life::session::Session;
+use rust_game_of_life::theme::Theme;
+use rust_game_of_life::timestamp::timestamp;
+use rust_game_of_life::timing::StepTimes;
+use rust_game_of_life::torus::Edges;
+use rust_game_of_life::trails::{Trails, TRAIL_DURATIONS};
+use rust_game_of_life::transform::{
+    flip_horizontal, flip_vertical, rotate_about, rotate_clockwise, rotate_counterclockwise, scale_down, scale_up,
+};
+use rust_game_of_life::worker::Worker;
 
-const SIMULATION_STEPS_PER_SECOND: u32 = 50;
-const DRAG_THRESHOLD: i32 = 5;
+/// Selectable simulation speeds in steps per second, where `None` steps once every frame.
+const SPEEDS: [Option<u32>; 8] = [Some(1), Some(2), Some(5), Some(10), Some(20), Some(50), Some(100), None];
+const DEFAULT_SPEED_INDEX: usize = 5;
+/// The most steps run in one frame to catch up with the speed, so a slow board can't snowball.
+const MAX_STEPS_PER_FRAME: u32 = 10;
+/// How long a run to a target generation may step for each frame, keeping the window responsive.
+const RUN_TO_FRAME_BUDGET: Duration = Duration::from_millis(12);
+/// A jump ahead only draws between slices of this long, skipping the intermediate generations.
+const JUMP_FRAME_BUDGET: Duration = Duration::from_millis(50);
+/// Grid lines are only drawn from this cell size up.
+const MIN_GRID_CELL_SIZE: i32 = 6;
+/// Holding Shift multiplies the arrow key pan step by this much.
+const FAST_PAN_MULTIPLIER: i64 = 5;
+const FIT_MARGIN: i64 = 2;
+/// Live cells shade from the theme's cell colour to its old cell colour over this many generations.
+const AGE_RAMP_GENERATIONS: u32 = 100;
+/// How many of the longest-lived cells F10 lists.
+const OLDEST_CELLS_LISTED: usize = 10;
+const PROMPT_MAX_LENGTH: usize = 48;
+const HISTORY_CAPACITY: usize = 256;
+/// The most cells inverting or scaling up a selection may visit or bring to life.
+const MAX_EDIT_CELLS: u64 = 1_000_000;
+/// Shift+[ and Shift+] shrink and grow the selection by this factor.
+const SCALE_FACTOR: i64 = 2;
+const DASH_LENGTH: i32 = 6;
+const UI_LINE_HEIGHT: i32 = 30;
+/// Generations shown by the population graph, and its size in pixels.
+const POPULATION_SAMPLES: usize = 200;
+/// The step time shown is the average over this many steps.
+const STEP_TIME_SAMPLES: usize = 30;
+const POPULATION_GRAPH_SIZE: IVec2 = IVec2::new(200, 80);
+/// How thick the walls of a walled board are drawn, in pixels.
+const WALL_THICKNESS: f32 = 4.0;
+/// The minimap's size in pixels, and the least time between refits to a changed board.
+const MINIMAP_SIZE: IVec2 = IVec2::new(160, 160);
+const MINIMAP_REFRESH_INTERVAL: f64 = 0.25;
+/// Seconds between estimates of the board's memory use.
+const MEMORY_REFRESH_INTERVAL: f64 = 0.5;
+const HELP_LINES: [&str; 17] = [
+    "Space: Play/Pause (Shift: Fixed Step), N/P: Step Forward/Back, F7/F8: Run to/Jump by Generations",
+    "F5/F6: Reset/Set Start, +/-: Speed, C: Clear, R: Rule (Shift: Noise), J: Moore/von Neumann, K: Engine",
+    "A: Pause on Cycle, F3: Immigration/QuadLife Colours, F4: Next Colour Painted, F11: Trails (Shift: Fade Time)",
+    "Left Click: Add Cells, Right Click/Drag: Erase, F2: Clicks Toggle, Left/Middle Drag: Pan, Wheel: Zoom",
+    "Shift+Drag: Select, Ctrl+Drag: Zoom to Box, Esc: Deselect/Cancel Paste or Shape",
+    "L: Line Tool (Shift: Ruler), Q: Rectangle Tool (Again: Filled), Click Start Then End",
+    "M: Cycle Symmetry, Shift+M: Set Symmetry Centre, Ctrl+A: Select Visible Cells (Shift: All Live Cells)",
+    "Ctrl+Z/Y: Undo/Redo, Ctrl+C/X/V: Copy/Cut/Paste, Ctrl+G: Snap Pastes, Delete: Clear Selection, U: Invert It",
+    "[/]: Rotate, H/V: Flip, Shift+[/]: Scale Down/Up, for Selection or Paste, Alt: Pin Paste and Set Pivot",
+    "1-4: Place Pattern, Ctrl+1-9: Save Bookmark, 5-9 or Shift+1-9: Go to Bookmark, F: Random Fill (Shift: Replace)",
+    ",/.: Brush Size, /: Brush Shape, Shift+,/.: Fill Density, Ctrl+,/.: Pan Sensitivity (Shift: Drag Threshold)",
+    "Arrows: Pan (Shift: Faster), Home: Reset View, End: Follow, B: Fit Pattern (Shift: Selection), :: Go To x,y",
+    "D: Draw Mode (Drag: Paint, Right Drag: Erase), W: Torus/Walls/Infinite, F10: Print Oldest Cells (Shift: Gliders)",
+    "S/E/X: Save RLE/Cells/Whole Board PNG, F12: Screenshot, G: Record GIF (Shift: Numbered PNGs)",
+    "Ctrl+S/O: Save/Restore Session, Ctrl+Shift+C/V: Copy/Paste RLE on the System Clipboard, F9: Seed the Next Fill",
+    "T: Cycle Theme, O: Population Graph, I: Minimap (Click: Go There), Z: Highlight Changes, Tab: Neighbour Counts",
+    "Y: Grid Lines (Shift: Axes), F1: Hide Controls. Default keys shown; rebind them in keys.json or --keys <file>",
+];
+const RLE_EXPORT_PATH: &str = "export.rle";
+const CELLS_EXPORT_PATH: &str = "export.cells";
+const PNG_EXPORT_PATH: &str = "export.png";
+const SESSION_PATH: &str = "session.json";
+/// Seconds the markers on cells that just died take to fade out.
+const CHANGE_FADE_DURATION: f64 = 0.5;
+/// Seconds a notice stays on screen.
+const NOTICE_DURATION: f64 = 4.0;
+/// Minimum seconds between recorded GIF frames, and the cap that stops a runaway recording.
+const GIF_FRAME_INTERVAL: f64 = 0.04;
+const GIF_MAX_FRAMES: usize = 600;
+/// How long each frame of a GIF recorded in fixed-step mode is shown, in hundredths of a second.
+const FIXED_STEP_GIF_DELAY: u16 = 4;
+const DEFAULT_FILL_DENSITY: f64 = 0.3;
+const FILL_DENSITY_STEP: f64 = 0.05;
+const PAN_SENSITIVITY_STEP: f64 = 0.25;
+const BENCH_SOUP_SIZE: i64 = 1000;
+const BENCH_ITERATIONS: u32 = 5;
+const BENCH_GUN_GENERATIONS: u64 = 1024;
+/// Place palette patterns, and with Ctrl save camera bookmarks. Shift recalls a bookmark, as
+/// does a plain digit no palette pattern is on.
+const DIGIT_KEYS: [KeyboardKey; BOOKMARK_SLOTS] = [
+    KeyboardKey::KEY_ONE,
+    KeyboardKey::KEY_TWO,
+    KeyboardKey::KEY_THREE,
+    KeyboardKey::KEY_FOUR,
+    KeyboardKey::KEY_FIVE,
+    KeyboardKey::KEY_SIX,
+    KeyboardKey::KEY_SEVEN,
+    KeyboardKey::KEY_EIGHT,
+    KeyboardKey::KEY_NINE,
+];
 
 fn main() {
+    let args = Args::parse(env::args().skip(1)).unwrap_or_else(|err| {
+        eprintln!("{err}\n{USAGE}");
+        process::exit(2);
+    });
+
+    if args.bench {
+        run_benchmark();
+        return;
+    }
+
+    if args.headless {
+        run_headless(&args);
+        return;
+    }
+
+    let mut life = Life::new();
+    life.set_rule(args.rule);
+    life.set_noise(args.noise);
+    let palette = PatternPalette::with_builtins();
+    let mut history = EditHistory::new(HISTORY_CAPACITY);
+    let mut generations = GenerationHistory::new(args.history_depth);
+    let mut cycles = CycleDetector::new(args.cycle_window);
+
+    let mut themes = vec![Theme::LIGHT, Theme::DARK];
+    if let Some(path) = &args.theme {
+        match Theme::load(path) {
+            Ok(theme) => themes.insert(0, theme),
+            Err(err) => eprintln!("Failed to load theme {}: {err}", path.display()),
+        }
+    }
+
+    let config_path = config_dir().map(|dir| dir.join(CONFIG_FILE_NAME));
+    let mut config = Config::default();
+    if let Some(path) = &config_path {
+        let (loaded, errors) = Config::load(path);
+        for err in errors {
+            eprintln!("Config {}: {err}, keeping the default", path.display());
+        }
+        config = loaded;
+    }
+    // A theme file given on the command line is used over the preset saved last time.
+    let mut theme_index = match config.dark_theme {
+        Some(dark) if args.theme.is_none() => usize::from(dark),
+        _ => 0,
+    };
+
+    let keys_file = args.keys.clone().or_else(|| config.keys.clone());
+    let keys_path = keys_file.clone().unwrap_or_else(|| PathBuf::from(DEFAULT_KEYS_PATH));
+    let keys = if keys_file.is_some() || keys_path.exists() {
+        let (keys, errors) = KeyBindings::load(&keys_path);
+        for err in errors {
+            eprintln!("Key bindings {}: {err}, keeping the default", keys_path.display());
+        }
+        keys
+    } else {
+        KeyBindings::default()
+    };
+
+    let mut loaded_view = None;
+    if let Some(path) = &args.load {
+        match load_pattern_with_metadata(path) {
+            Ok((cells, metadata)) => {
+                life.extend(cells);
+                life.set_generation(metadata.generation.unwrap_or(0));
+                loaded_view = metadata.view;
+            }
+            Err(err) => eprintln!("Failed to load {}: {err}", path.display()),
+        }
+    }
+    if let Some(path) = &args.image {
+        match load_image_cells(path, args.image_threshold) {
+            Ok(image) => {
+                if image.is_downsampled() {
+                    let (size, fitted) = (image.size, image.fitted);
+                    println!("Downsampling {} from {}x{} to {}x{}", path.display(), size.x, size.y, fitted.x, fitted.y);
+                }
+                life.extend(image.cells);
+            }
+            Err(err) => eprintln!("Failed to load {}: {err}", path.display()),
+        }
+    }
+    // Compared boards are only overlaid, leaving the board itself free to edit and run.
+    let mut board_diff = args.diff.as_ref().map(|(a, b)| {
+        let load = |path: &Path| {
+            load_pattern(path).unwrap_or_else(|err| {
+                eprintln!("Failed to load {}: {err}", path.display());
+                process::exit(1);
+            })
+        };
+        let diff = BoardDiff::between(&load(a), &load(b));
+        println!("{} vs {}: {diff}", a.display(), b.display());
+        diff
+    });
+
     let (mut rl, thread) = init()
         .size(800, 800)
         .title("Infinite Conway's Game of Life")
         .build();
+    rl.set_exit_key(None);
 
     let screen_width = rl.get_screen_width();
     let screen_height = rl.get_screen_height();
 
-    let mut cell_size = 12;
-    let mut origin = IVec2::ZERO;
+    let mut board_texture = rl
+        .load_render_texture(&thread, screen_width as u32, screen_height as u32)
+        .unwrap_or_else(|err| {
+            eprintln!("{err}");
+            process::exit(1);
+        });
+    let mut rendered_board_view = None;
+
+    let mut cell_size = DEFAULT_CELL_SIZE;
+    let mut origin = I64Vec2::ZERO;
+
+    // A pattern saved with its view opens framed the same way; anything else is fitted.
+    if let Some((saved_origin, saved_cell_size)) = loaded_view {
+        origin = saved_origin;
+        cell_size = saved_cell_size.clamp(MIN_CELL_SIZE, MAX_CELL_SIZE);
+    } else if let Some((min, max)) = board_diff.as_ref().and_then(BoardDiff::bounds) {
+        (origin, cell_size) = fit_box(min, max, IVec2::new(screen_width, screen_height), FIT_MARGIN);
+    } else if let Some((min, max)) = bounding_box(&life.live_cells()) {
+        (origin, cell_size) = fit_box(min, max, IVec2::new(screen_width, screen_height), FIT_MARGIN);
+    }
 
-    let mut cells: HashSet<IVec2> = HashSet::new();
     let mut is_dragging = false;
     let mut is_mouse_down = false;
     let mut mouse_down_pos = IVec2::ZERO;
-    let mut previous_offset = IVec2::ZERO;
+    let mut previous_offset = I64Vec2::ZERO;
+    // Where a middle button pan started on screen, and the origin then.
+    let mut middle_pan: Option<(IVec2, I64Vec2)> = None;
+
+    let mut selection: Option<CellRect> = None;
+    let mut is_selecting = false;
+    // Where a Ctrl+drag zoom box was started on screen, while it is being drawn.
+    let mut zoom_anchor: Option<IVec2> = None;
+    let mut selection_anchor = I64Vec2::ZERO;
+    let mut clipboard: Vec<I64Vec2> = Vec::new();
+    let mut is_pasting = false;
+    // Where a pinned paste preview sits and the cell it rotates about. Once pinned, the preview
+    // stays put instead of following the cursor.
+    let mut paste_pin: Option<(I64Vec2, I64Vec2)> = None;
+    let mut is_paste_snapped = false;
+    // The board as it was when the simulation first started, for resetting to.
+    let mut generation_zero: Option<Snapshot> = None;
+    // The board's revision right after the last step, to tell edits apart from steps.
+    let mut stepped_revision: Option<u64> = None;
+    let mut show_changes = false;
+    let mut show_grid = config.show_grid.unwrap_or(true);
+    let mut show_axes = false;
+    // While following, the view last moved to and its zoom. A view moved anywhere else at the
+    // same zoom was panned by hand, which stops following.
+    let mut following: Option<(I64Vec2, i32)> = None;
+    // The middle of the live cells and the board revision it was found at, for following.
+    let mut follow_target: Option<(u64, I64Vec2)> = None;
+    // The gliders last searched for and the board revision they were found at, outlined until
+    // the board changes.
+    let mut found_gliders: Option<(u64, Vec<Glider>)> = None;
+    let mut step_changes: Option<StepChanges> = None;
+    let mut show_trails = false;
+    // Each cell's neighbour count and the board revision it was counted at, for the heatmap.
+    let mut show_heatmap = false;
+    let mut heatmap: Option<(u64, HashMap<I64Vec2, usize>)> = None;
+    let mut trails = Trails::new(args.trail_duration);
+    // A message for the user, its colour and when it appeared, such as why a clipboard paste
+    // failed or which bookmark was saved.
+    let mut notice: Option<(String, Color, f64)> = None;
+    let mut bookmarks: [Option<Bookmark>; BOOKMARK_SLOTS] = [None; BOOKMARK_SLOTS];
 
     let mut is_running = false;
+    let mut pause_on_cycle = false;
+    // Passing the population cap pauses the run until Space dismisses the warning, which lifts
+    // the cap for good.
+    let mut population_cap = Some(args.max_population);
+    let mut is_population_capped = false;
     let mut last_time = rl.get_time();
     let mut last_frame_time = 0.0;
+    // Fixed-step mode runs exactly one generation per frame, whatever the speed or frame time,
+    // so recordings come out the same every time.
+    let mut is_fixed_step = false;
+    let saved_speed = config.speed.and_then(|speed| SPEEDS.iter().position(|&known| known.unwrap_or(0) == speed));
+    let mut speed_index = saved_speed.unwrap_or(DEFAULT_SPEED_INDEX);
+    let mut is_screenshot_requested = false;
+    let mut recording: Option<Recording> = None;
+    let mut frame_sequence: Option<FrameSequence> = None;
+    let mut show_help = false;
+    let mut populations = PopulationHistory::new(POPULATION_SAMPLES);
+    let mut show_population_graph = false;
+    let mut minimap = Minimap::new(MINIMAP_SIZE);
+    let mut show_minimap = false;
+    let mut minimap_revision = None;
+    let mut bounds = None;
+    let mut bounds_revision = None;
+    let mut minimap_refreshed_at = f64::NEG_INFINITY;
+    let mut memory_bytes = 0;
+    let mut memory_measured_at = f64::NEG_INFINITY;
+    let mut is_minimap_dragging = false;
+    let mut prompt: Option<(Prompt, String)> = None;
+    let mut run_target: Option<u64> = None;
+    let mut jump: Option<Jump> = None;
+    let mut worker: Option<Worker> = None;
+    let mut step_times = StepTimes::new(STEP_TIME_SAMPLES);
+    let mut fill_density = config.fill_density.unwrap_or(DEFAULT_FILL_DENSITY);
+    let mut brush = Brush {
+        radius: config.brush_radius.unwrap_or(Brush::default().radius),
+        shape: config.brush_shape.unwrap_or(Brush::default().shape),
+    };
+    let mut is_draw_mode = config.draw_mode.unwrap_or(false);
+    // Left clicks bring cells to life and right clicks erase them, unless clicks toggle instead.
+    let mut is_click_toggle = config.click_toggles.unwrap_or(false);
+    let mut drag_threshold = config.drag_threshold.unwrap_or(DRAG_THRESHOLD);
+    let mut pan_sensitivity = config.pan_sensitivity.unwrap_or(1.0);
+    let is_zoom_smooth = config.smooth_zoom.unwrap_or(true);
+    // The preferences as of the last frame, to tell when the user has changed one.
+    let mut last_preferences: Option<Config> = None;
+    let mut smooth_zoom: Option<SmoothZoom> = None;
+    let mut last_stroke_cell: Option<I64Vec2> = None;
+    let mut shape_tool: Option<ShapeTool> = None;
+    let mut shape_start: Option<I64Vec2> = None;
+    // The ruler's first cell and, once clicked, its second. Until then it reaches the cursor.
+    let mut is_measuring = false;
+    let mut ruler: Option<(I64Vec2, Option<I64Vec2>)> = None;
+    let mut symmetry: Option<Symmetry> = None;
+    let mut symmetry_centre = I64Vec2::ZERO;
+    let mut fill_seed = args.seed.unwrap_or_else(|| {
+        SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+    let mut last_fill_seed: Option<u64> = None;
 
     while !rl.window_should_close() {
         let current_time = rl.get_time();
         let fps = rl.get_fps();
 
+        let theme = themes[theme_index];
+        // Steps record each generation they make; this catches edits and steps back in between.
+        populations.record(life.generation(), life.population() as u64);
         let mut d = rl.begin_drawing(&thread);
-        d.clear_background(Color::WHITE);
+        d.clear_background(theme.background);
 
         let current_mouse_pos = IVec2::new(
             d.get_mouse_position().x as i32,
@@ -42,46 +386,157 @@ fn main() {
 
         let dimensions = IVec2::new(screen_width, screen_height) / cell_size;
         let lower = -origin;
-        let upper = lower + dimensions;
+        let upper = lower + dimensions.as_i64vec2();
+
+        // Cell and grid rendering, redone only when the board or view has changed since the last
+        // frame, otherwise the cached texture is drawn again
+
+        let board_view = (life.revision(), origin, cell_size, theme_index, show_grid);
+        if rendered_board_view != Some(board_view) {
+            let mut t = d.begin_texture_mode(&thread, &mut board_texture);
+            t.clear_background(theme.background);
+            let decay_steps = (life.rule().states - 1) as f32;
+            let inset = cell_inset(args.cell_inset, cell_size);
 
-        // Cell rendering
+            for (cell, &state) in life.cells() {
+                if cell.x < lower.x || cell.x >= upper.x || cell.y < lower.y || cell.y >= upper.y {
+                    continue;
+                }
 
-        for cell in &cells {
-            if cell.x < lower.x || cell.x >= upper.x || cell.y < lower.y || cell.y >= upper.y {
-                continue;
+                let cell_screen_pos = (cell + origin).as_ivec2();
+                t.draw_rectangle(
+                    cell_screen_pos.x * cell_size + inset,
+                    cell_screen_pos.y * cell_size + inset,
+                    cell_size - 2 * inset,
+                    cell_size - 2 * inset,
+                    if state == ALIVE && life.colouring().is_some() {
+                        theme.coloured_cell(life.colour(*cell))
+                    } else if state == ALIVE {
+                        age_color(&theme, life.age(*cell))
+                    } else {
+                        theme.cell.lerp(theme.background, (state - 1) as f32 / decay_steps)
+                    },
+                );
             }
 
-            let cell_screen_pos = cell + origin;
-            d.draw_rectangle(
-                cell_screen_pos.x * cell_size,
-                cell_screen_pos.y * cell_size,
-                cell_size,
-                cell_size,
-                Color::BLACK,
-            );
+            // Zoomed out, the lines would crowd out the cells, so they hide whatever the toggle.
+            if show_grid && cell_size >= MIN_GRID_CELL_SIZE {
+                for x in 0..=dimensions.x {
+                    let sx = x * cell_size;
+                    t.draw_line(sx, 0, sx, screen_height, theme.grid);
+                }
+                for y in 0..=dimensions.y {
+                    let sy = y * cell_size;
+                    t.draw_line(0, sy, screen_width, sy, theme.grid);
+                }
+            }
+            rendered_board_view = Some(board_view);
+        }
+
+        // Render textures are stored bottom-up, so the source is flipped to draw them upright.
+        let board_source = Rectangle::new(0.0, 0.0, screen_width as f32, -screen_height as f32);
+        d.draw_texture_rec(&board_texture, board_source, Vector2::zero(), Color::WHITE);
+
+        if is_screenshot_requested && !args.screenshot_ui {
+            save_screenshot(&d, &thread);
+            is_screenshot_requested = false;
+        }
+
+        if show_axes {
+            // Through the middle of cell (0, 0). An axis out of view is pinned to the nearest edge,
+            // fainter, so it still shows which way the origin lies.
+            let screen_size = IVec2::new(screen_width, screen_height).as_i64vec2();
+            let cell_pixels = I64Vec2::splat(cell_size as i64);
+            let centre = origin.saturating_mul(cell_pixels).saturating_add(cell_pixels / 2);
+            let pinned = centre.clamp(I64Vec2::ZERO, screen_size - 1);
+            let axis_color = |is_pinned: bool| if is_pinned { theme.accent.alpha(0.4) } else { theme.accent };
+            let (x, y) = (pinned.x as i32, pinned.y as i32);
+            d.draw_line(x, 0, x, screen_height, axis_color(pinned.x != centre.x));
+            d.draw_line(0, y, screen_width, y, axis_color(pinned.y != centre.y));
+            if pinned == centre {
+                d.draw_circle_lines(x, y, (cell_size as f32 / 2.0).clamp(3.0, 8.0), theme.accent);
+            }
         }
 
-        // Board rendering
+        if show_heatmap {
+            if heatmap.as_ref().is_none_or(|&(revision, _)| revision != life.revision()) {
+                let counts = convolve(life.cells(), &life.rule().kernel(), life.torus().as_ref());
+                heatmap = Some((life.revision(), counts));
+            }
+            let rule = life.rule();
+            let max_count = rule.neighbourhood.max_count(rule.range);
+            let inset = cell_inset(args.cell_inset, cell_size);
+            for (cell, &count) in heatmap.iter().flat_map(|(_, counts)| counts) {
+                if cell.cmpge(lower).all() && cell.cmple(upper).all() {
+                    let cell_screen_pos = (cell + origin).as_ivec2() * cell_size;
+                    let side = cell_size - 2 * inset;
+                    let (x, y) = (cell_screen_pos.x + inset, cell_screen_pos.y + inset);
+                    d.draw_rectangle(x, y, side, side, heatmap_color(count, max_count));
+                    // Empty cells about to be born are outlined.
+                    if !life.is_alive(*cell) && rule.birth.contains(count) {
+                        let (x, y) = (cell_screen_pos.x, cell_screen_pos.y);
+                        d.draw_rectangle_lines(x, y, cell_size, cell_size, theme.accent);
+                    }
+                }
+            }
+        }
 
-        if cell_size > 5 {
-            for x in 0..=dimensions.x {
-                let sx = x * cell_size;
-                d.draw_line(sx, 0, sx, screen_height, Color::LIGHTGRAY);
+        if show_trails {
+            // Cells that died lately linger as fading afterimages under the change markers.
+            trails.prune(current_time, |cell| life.is_alive(cell));
+            let inset = cell_inset(args.cell_inset, cell_size);
+            let side = cell_size - 2 * inset;
+            for (cell, opacity) in trails.iter(current_time) {
+                if cell.cmpge(lower).all() && cell.cmple(upper).all() {
+                    let cell_screen_pos = (cell + origin).as_ivec2() * cell_size + inset;
+                    d.draw_rectangle(cell_screen_pos.x, cell_screen_pos.y, side, side, theme.cell.alpha(opacity * 0.6));
+                }
             }
-            for y in 0..=dimensions.y {
-                let sy = y * cell_size;
-                d.draw_line(0, sy, screen_width, sy, Color::LIGHTGRAY);
+        }
+
+        if let Some(diff) = &board_diff {
+            let inset = cell_inset(args.cell_inset, cell_size);
+            let side = cell_size - 2 * inset;
+            let categories = [(&diff.both, theme.cell), (&diff.only_a, theme.hover), (&diff.only_b, theme.accent)];
+            for (cells, color) in categories {
+                for &cell in cells.iter().filter(|cell| cell.cmpge(lower).all() && cell.cmple(upper).all()) {
+                    let cell_screen_pos = (cell + origin).as_ivec2() * cell_size + inset;
+                    d.draw_rectangle(cell_screen_pos.x, cell_screen_pos.y, side, side, color.alpha(0.7));
+                }
             }
         }
 
-        let hovered_cell = IVec2::new(
-            ((current_mouse_pos.x as f32) / cell_size as f32).floor() as i32 - origin.x,
-            ((current_mouse_pos.y as f32) / cell_size as f32).floor() as i32 - origin.y,
-        );
-        let hovered_screen_pos = IVec2::new(
-            (hovered_cell.x + origin.x) * cell_size,
-            (hovered_cell.y + origin.y) * cell_size,
-        );
+        if show_changes
+            && let Some(changes) = &step_changes
+            && changes.revision == life.revision()
+        {
+            // The markers cover the last generation only, and those for deaths fade out.
+            let is_visible = |cell: &&I64Vec2| cell.cmpge(lower).all() && cell.cmple(upper).all();
+            for cell in changes.born.iter().filter(is_visible) {
+                let cell_screen_pos = (cell + origin).as_ivec2() * cell_size;
+                d.draw_rectangle(cell_screen_pos.x, cell_screen_pos.y, cell_size, cell_size, Color::GREEN.alpha(0.5));
+            }
+            let fade = 1.0 - ((current_time - changes.stepped_at) / CHANGE_FADE_DURATION).min(1.0) as f32;
+            for cell in changes.died.iter().filter(is_visible) {
+                let cell_screen_pos = (cell + origin).as_ivec2() * cell_size;
+                let rect = Rectangle::new(
+                    cell_screen_pos.x as f32,
+                    cell_screen_pos.y as f32,
+                    cell_size as f32,
+                    cell_size as f32,
+                );
+                d.draw_rectangle_lines_ex(rect, 1.0, Color::RED.alpha(fade));
+            }
+        }
+
+        let hovered_cell = cell_at(current_mouse_pos, origin, cell_size);
+        if (brush.radius > 0 || symmetry.is_some()) && !is_pasting {
+            for cell in mirror(symmetry, symmetry_centre, brush.footprint(hovered_cell)) {
+                let cell_screen_pos = (cell + origin).as_ivec2() * cell_size;
+                d.draw_rectangle(cell_screen_pos.x, cell_screen_pos.y, cell_size, cell_size, theme.hover.alpha(0.3));
+            }
+        }
+        let hovered_screen_pos = (hovered_cell + origin).as_ivec2() * cell_size;
         d.draw_rectangle_lines_ex(
             Rectangle::new(
                 hovered_screen_pos.x as f32,
@@ -90,64 +545,938 @@ fn main() {
                 cell_size as f32,
             ),
             2.0,
-            Color::RED,
+            theme.hover,
         );
 
+        if !is_pasting {
+            paste_pin = None;
+        }
+        let paste_stride = if is_paste_snapped { args.snap_stride } else { 1 };
+        let paste_at = paste_pin.map_or(snap_to_grid(hovered_cell, paste_stride), |(anchor, _)| anchor);
+        if is_pasting {
+            for &cell in &clipboard {
+                let cell_screen_pos = (cell + paste_at + origin).as_ivec2() * cell_size;
+                d.draw_rectangle(cell_screen_pos.x, cell_screen_pos.y, cell_size, cell_size, theme.accent.alpha(0.4));
+            }
+        }
+        if let Some((_, pivot)) = paste_pin {
+            let centre = ((pivot + origin).as_vec2() + 0.5) * cell_size as f32;
+            d.draw_circle_lines(centre.x as i32, centre.y as i32, (cell_size as f32 * 0.5).max(3.0), theme.accent);
+        }
+
+        if let (Some(tool), Some(start)) = (shape_tool, shape_start) {
+            for cell in mirror(symmetry, symmetry_centre, tool.cells(start, hovered_cell)) {
+                let cell_screen_pos = (cell + origin).as_ivec2() * cell_size;
+                d.draw_rectangle(cell_screen_pos.x, cell_screen_pos.y, cell_size, cell_size, theme.accent.alpha(0.4));
+            }
+        }
+
+        if let Some((revision, gliders)) = &found_gliders
+            && *revision == life.revision()
+        {
+            for glider in gliders {
+                let min = glider.cells.iter().copied().reduce(I64Vec2::min).unwrap();
+                let max = glider.cells.iter().copied().reduce(I64Vec2::max).unwrap();
+                let (min, max) = ((min + origin).as_ivec2() * cell_size, (max + 1 + origin).as_ivec2() * cell_size);
+                d.draw_rectangle_lines(min.x - 2, min.y - 2, max.x - min.x + 4, max.y - min.y + 4, theme.accent);
+            }
+        }
+
+        if let Some((start, end)) = ruler {
+            let cell_centre = |cell: I64Vec2| ((cell + origin).as_vec2() + 0.5) * cell_size as f32;
+            let (start, end) = (cell_centre(start), cell_centre(end.unwrap_or(hovered_cell)));
+            d.draw_line_ex(Vector2::new(start.x, start.y), Vector2::new(end.x, end.y), 2.0, theme.accent);
+            for point in [start, end] {
+                d.draw_circle_lines(point.x as i32, point.y as i32, (cell_size as f32 / 2.0).max(3.0), theme.accent);
+            }
+        }
+
+        // Clamp to just outside the window so off-screen edges stay hidden and cheap to draw.
+        let to_screen = |cell: I64Vec2| {
+            cell.saturating_mul(I64Vec2::splat(cell_size as i64))
+                .clamp(I64Vec2::splat(-1), I64Vec2::new(screen_width as i64, screen_height as i64) + 1)
+                .as_ivec2()
+        };
+
+        if let Some(torus) = life.torus() {
+            let min = to_screen(origin);
+            let size = to_screen(torus.size.saturating_add(origin)) - min;
+            // Walls are drawn heavier than the seam of a torus, which cells cross freely.
+            let (thickness, color) = match torus.edges {
+                Edges::Wrap => (2.0, theme.text),
+                Edges::Wall => (WALL_THICKNESS, theme.accent),
+            };
+            d.draw_rectangle_lines_ex(
+                Rectangle::new(min.x as f32, min.y as f32, size.x as f32, size.y as f32),
+                thickness,
+                color,
+            );
+        }
+
+        if let Some(symmetry) = symmetry {
+            // The axes run through the middle of the centre cell, and off-screen ones are clamped
+            // outside the window.
+            let corner = to_screen(symmetry_centre + origin);
+            let centre = corner + cell_size / 2;
+            let color = theme.accent.alpha(0.6);
+            if symmetry != Symmetry::Vertical && (0..screen_width).contains(&corner.x) {
+                d.draw_line(centre.x, 0, centre.x, screen_height, color);
+            }
+            if symmetry != Symmetry::Horizontal && (0..screen_height).contains(&corner.y) {
+                d.draw_line(0, centre.y, screen_width, centre.y, color);
+            }
+        }
+
+        if let Some(rect) = selection {
+            draw_dashed_rectangle(&mut d, to_screen(rect.min + origin), to_screen(rect.max + origin + 1), theme.accent);
+        }
+
         // UI
 
-        d.draw_text(&"Space: Play/Pause, C: Clear".to_string(), 10, 10, 20, Color::GRAY);
-        d.draw_text(&"Left Mouse (Click): Toggle Cell, Left Mouse (Drag): Pan".to_string(), 10, 40, 20, Color::GRAY);
-        d.draw_text(&"Mouse Wheel: Zoom".to_string(), 10, 70, 20, Color::GRAY);
-        d.draw_text(&format!("FPS: {}", fps), 10, 100, 20, Color::GRAY);
-        d.draw_text(&format!("Cells: {}", cells.len()), 10, 130, 20, Color::GRAY);
+        let mut ui_line_y = (0..).map(|line| 10 + line * UI_LINE_HEIGHT);
+        if show_help {
+            for line in HELP_LINES {
+                d.draw_text(line, 10, ui_line_y.next().unwrap(), 20, theme.text);
+            }
+        } else {
+            d.draw_text("F1: Show Controls", 10, ui_line_y.next().unwrap(), 20, theme.text);
+        }
+        d.draw_text(&format!("FPS: {}", fps), 10, ui_line_y.next().unwrap(), 20, theme.text);
+        let StepStats { births, deaths } = life.last_step();
+        let cells_text = format!("Cells: {} (last step: {births} born, {deaths} died)", life.population());
+        d.draw_text(&cells_text, 10, ui_line_y.next().unwrap(), 20, theme.text);
+        if current_time - memory_measured_at >= MEMORY_REFRESH_INTERVAL {
+            memory_bytes = life.memory_bytes();
+            memory_measured_at = current_time;
+        }
+        let memory_text = format!("Memory: about {}", format_bytes(memory_bytes));
+        d.draw_text(&memory_text, 10, ui_line_y.next().unwrap(), 20, theme.text);
+        d.draw_text(&format!("Generation: {}", life.generation()), 10, ui_line_y.next().unwrap(), 20, theme.text);
+        // Only worked out again once the board changes, as a big board takes a while to scan.
+        if bounds_revision != Some(life.revision()) {
+            bounds = bounding_box(&life.live_cells());
+            bounds_revision = Some(life.revision());
+        }
+        let bounds_text = match bounds {
+            Some((min, max)) => {
+                let side = |min: i64, max: i64| max.abs_diff(min).saturating_add(1);
+                format!("Bounds: {}x{} at ({}, {})", side(min.x, max.x), side(min.y, max.y), min.x, min.y)
+            }
+            None => "Bounds: empty".to_string(),
+        };
+        d.draw_text(&bounds_text, 10, ui_line_y.next().unwrap(), 20, theme.text);
+        let rule_name = RulePreset::from_rule(life.rule()).map_or("Custom", RulePreset::name);
+        let colouring = match life.colouring() {
+            Some(colouring) => format!(", {} painting colour {}", colouring.name(), life.paint_colour()),
+            None => String::new(),
+        };
+        let noise = match life.noise() {
+            Some(Noise { birth, survival, seed }) => {
+                format!(", {:.0}% births, {:.0}% survivals, noise seed {seed}", birth * 100.0, survival * 100.0)
+            }
+            None => String::new(),
+        };
         d.draw_text(
-            &format!("{}", if is_running { "Running" } else { "Paused" }),
+            &format!("Rule: {} ({}){colouring}{noise}", rule_name, life.rule()),
             10,
-            160,
+            ui_line_y.next().unwrap(),
             20,
-            if is_running { Color::GREEN } else { Color::GRAY },
+            theme.text,
         );
+        let engine_name = match life.engine() {
+            Engine::Naive => "Naive",
+            Engine::Incremental if life.colouring().is_some() || life.noise().is_some() => {
+                "Incremental (naive for colours and noise)"
+            }
+            Engine::Incremental => "Incremental",
+            Engine::HashLife if life.uses_hashlife() => "HashLife",
+            Engine::HashLife => "HashLife (naive for Generations, von Neumann, ranges, tori, colours and noise)",
+        };
+        d.draw_text(&format!("Engine: {engine_name}"), 10, ui_line_y.next().unwrap(), 20, theme.text);
+        let step_text = match step_times.average() {
+            Some(average) => format!("Step: {:.1} ms", average.as_secs_f64() * 1000.0),
+            None => "Step: -".to_string(),
+        };
+        d.draw_text(&step_text, 10, ui_line_y.next().unwrap(), 20, theme.text);
+        let speed = match SPEEDS[speed_index] {
+            _ if is_fixed_step => "1 step/frame (fixed)".to_string(),
+            Some(steps_per_second) => format!("{steps_per_second} steps/s"),
+            None => "max".to_string(),
+        };
+        d.draw_text(&format!("Speed: {speed}"), 10, ui_line_y.next().unwrap(), 20, theme.text);
+        let fill_text = match last_fill_seed {
+            Some(seed) => format!("Fill density: {fill_density:.2}, last seed: {seed}"),
+            None => format!("Fill density: {fill_density:.2}"),
+        };
+        d.draw_text(&fill_text, 10, ui_line_y.next().unwrap(), 20, theme.text);
+        let brush_mode = match shape_tool {
+            Some(tool) => format!(" ({} tool)", tool.name()),
+            None if is_draw_mode => " (draw mode)".to_string(),
+            None if is_click_toggle => " (clicks toggle)".to_string(),
+            None => String::new(),
+        };
+        let brush_symmetry = match symmetry {
+            Some(symmetry) => format!(", {} symmetry", symmetry.name()),
+            None => String::new(),
+        };
+        let brush_text =
+            format!("Brush: radius {} ({}{brush_symmetry}){brush_mode}", brush.radius, brush.shape.name());
+        d.draw_text(&brush_text, 10, ui_line_y.next().unwrap(), 20, theme.text);
+        let centre = centre_cell(origin, IVec2::new(screen_width, screen_height), cell_size);
+        d.draw_text(&format!("Centre: ({}, {})", centre.x, centre.y), 10, ui_line_y.next().unwrap(), 20, theme.text);
+        let hovered_state = match life.state(hovered_cell) {
+            _ if !life.contains(hovered_cell) => "off the board".to_string(),
+            0 => "dead".to_string(),
+            ALIVE if life.colouring().is_some() => format!("alive, colour {}", life.colour(hovered_cell)),
+            ALIVE => "alive".to_string(),
+            state => format!("decaying, state {state}"),
+        };
+        let cursor_text = format!("Cursor: ({}, {}) {hovered_state}", hovered_cell.x, hovered_cell.y);
+        d.draw_text(&cursor_text, 10, ui_line_y.next().unwrap(), 20, theme.text);
+        if is_pasting {
+            let snap_text = if is_paste_snapped {
+                format!("Paste snaps to every {} cells (Ctrl+G: Off)", args.snap_stride)
+            } else {
+                "Paste follows the cursor (Ctrl+G: Snap to Grid)".to_string()
+            };
+            d.draw_text(&snap_text, 10, ui_line_y.next().unwrap(), 20, theme.accent);
+        }
+        if let Some(diff) = &board_diff {
+            let diff_text = format!("Diff: {diff} (Esc: Hide)");
+            d.draw_text(&diff_text, 10, ui_line_y.next().unwrap(), 20, theme.accent);
+            let key_y = ui_line_y.next().unwrap();
+            for (index, (label, color)) in [("A only", theme.hover), ("B only", theme.accent), ("Both", theme.cell)]
+                .into_iter()
+                .enumerate()
+            {
+                let x = 10 + index as i32 * 100;
+                d.draw_rectangle(x, key_y + 4, 12, 12, color);
+                d.draw_text(label, x + 18, key_y, 20, theme.text);
+            }
+        }
+        if let Some((start, end)) = ruler {
+            let measurement = Measurement::between(start, end.unwrap_or(hovered_cell));
+            let ruler_text = format!("Ruler: {measurement} (Esc: Clear)");
+            d.draw_text(&ruler_text, 10, ui_line_y.next().unwrap(), 20, theme.accent);
+        } else if is_measuring {
+            d.draw_text("Ruler: click two cells (Esc: Cancel)", 10, ui_line_y.next().unwrap(), 20, theme.accent);
+        }
+        let cycle = match cycles.cycle() {
+            Some(cycle) => format!("{} at generation {}", describe_cycle(&cycle), cycle.generation),
+            None => "none".to_string(),
+        };
+        let auto_pause = if pause_on_cycle { " (auto-pause)" } else { "" };
+        d.draw_text(&format!("Cycle: {cycle}{auto_pause}"), 10, ui_line_y.next().unwrap(), 20, theme.text);
+        let status = match (is_running, life.is_stable()) {
+            _ if let Some(jump) = jump => format!(
+                "Jumping to generation {}: {:.0}% (Esc: Cancel)",
+                jump.to,
+                jump.progress(&life) * 100.0
+            ),
+            (true, _) => match run_target {
+                Some(target) => format!("Running to generation {target}: {} steps left", target - life.generation()),
+                None => "Running".to_string(),
+            },
+            (false, _) if is_population_capped => format!(
+                "Paused: population passed {} (Space: Continue Anyway)",
+                population_cap.unwrap_or_default()
+            ),
+            (false, true) => "Stabilized".to_string(),
+            (false, false) => "Paused".to_string(),
+        };
+        d.draw_text(
+            &status,
+            10,
+            ui_line_y.next().unwrap(),
+            20,
+            if is_running || jump.is_some() {
+                Color::GREEN
+            } else if is_population_capped {
+                Color::RED
+            } else {
+                theme.text
+            },
+        );
+        if let Some((text, colour, shown_at)) = &notice
+            && current_time - shown_at < NOTICE_DURATION
+        {
+            d.draw_text(text, 10, ui_line_y.next().unwrap(), 20, *colour);
+        }
+
+        if show_population_graph {
+            let top_left = IVec2::new(screen_width - POPULATION_GRAPH_SIZE.x - 20, 10);
+            draw_population_graph(&mut d, &populations, top_left, &theme);
+        }
+
+        let minimap_corner = IVec2::new(screen_width, screen_height) - MINIMAP_SIZE - 10;
+        if show_minimap {
+            let is_refresh_due = current_time - minimap_refreshed_at >= MINIMAP_REFRESH_INTERVAL;
+            if minimap_revision != Some(life.revision()) && is_refresh_due {
+                minimap.refresh(&life.live_cells());
+                minimap_revision = Some(life.revision());
+                minimap_refreshed_at = current_time;
+            }
+            draw_minimap(&mut d, &minimap, minimap_corner, (lower, upper), &theme);
+        }
+
+        if is_screenshot_requested {
+            save_screenshot(&d, &thread);
+            is_screenshot_requested = false;
+        }
+
+        if let Some(active) = &mut recording
+            && active.is_due(current_time, life.generation())
+            && !active.capture(&d, &thread, current_time, life.generation(), screen_width, screen_height)
+        {
+            recording.take().unwrap().finish();
+        }
+        if let Some(active) = &recording {
+            let text = format!("Recording GIF: {}/{GIF_MAX_FRAMES} frames", active.encoder.frame_count());
+            d.draw_text(&text, 10, ui_line_y.next().unwrap(), 20, Color::RED);
+        }
+        if let Some(active) = &mut frame_sequence
+            && active.is_due(life.generation())
+            && !active.capture(&d, &thread, &life, &theme, args.export_scale)
+        {
+            frame_sequence.take().unwrap().finish();
+        }
+        if let Some(active) = &frame_sequence {
+            let text = format!("Recording PNGs: {}/{} frames", active.count, active.max_frames);
+            d.draw_text(&text, 10, ui_line_y.next().unwrap(), 20, Color::RED);
+        }
+
+        if let Some((kind, input)) = &prompt {
+            let top = screen_height - 50;
+            d.draw_rectangle(10, top, screen_width - 20, 40, theme.background.alpha(0.9));
+            d.draw_rectangle_lines(10, top, screen_width - 20, 40, theme.text);
+            d.draw_text(&format!("{}: {input}_", kind.label()), 20, top + 10, 20, theme.text);
+        }
 
         // Mouse input handling
 
-        if d.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+        let is_shift_down = d.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) || d.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+        let is_control_down =
+            d.is_key_down(KeyboardKey::KEY_LEFT_CONTROL) || d.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+
+        // Draw mode paints along the cursor's path instead of panning. The right button always
+        // erases along its path. Shift still starts a selection, and Ctrl a zoom box.
+        let is_left_down = d.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT);
+
+        // Pressing on the minimap, or dragging after that, centres the view on the cell below.
+        let minimap_pixel = current_mouse_pos - minimap_corner;
+        let is_over_minimap =
+            show_minimap && minimap_pixel.cmpge(IVec2::ZERO).all() && minimap_pixel.cmplt(MINIMAP_SIZE).all();
+        is_minimap_dragging = is_left_down
+            && (is_minimap_dragging || (is_over_minimap && !is_mouse_down && last_stroke_cell.is_none()));
+        if is_minimap_dragging {
+            let cell = minimap.to_world(minimap_pixel.clamp(IVec2::ZERO, MINIMAP_SIZE - 1));
+            origin = centre_on(cell, IVec2::new(screen_width, screen_height), cell_size);
+        }
+        let is_tool_active = shape_tool.is_some() || is_measuring;
+        let is_stroke_blocked =
+            is_pasting || is_selecting || zoom_anchor.is_some() || is_tool_active || is_minimap_dragging;
+        let is_box_started = is_shift_down || is_control_down;
+        let stroke_state = if is_stroke_blocked {
+            None
+        } else if is_draw_mode && is_left_down && (last_stroke_cell.is_some() || !is_box_started) {
+            Some(ALIVE)
+        } else if d.is_mouse_button_down(MouseButton::MOUSE_BUTTON_RIGHT) {
+            Some(0)
+        } else {
+            None
+        };
+        if let Some(state) = stroke_state {
+            let path = line(last_stroke_cell.unwrap_or(hovered_cell), hovered_cell);
+            let cells: Vec<I64Vec2> = path.into_iter().flat_map(|cell| brush.footprint(cell)).collect();
+            let cells = mirror(symmetry, symmetry_centre, cells);
+            history.stroke(&mut life, &cells, state);
+            last_stroke_cell = Some(hovered_cell);
+        } else if last_stroke_cell.take().is_some() {
+            history.end_stroke();
+        }
+
+        // The middle button only ever pans, so it moves the view straight away with no threshold.
+        if d.is_mouse_button_down(MouseButton::MOUSE_BUTTON_MIDDLE) {
+            let (start, start_origin) = *middle_pan.get_or_insert((current_mouse_pos, origin));
+            let drag = scale_drag(current_mouse_pos - start, pan_sensitivity);
+            origin = start_origin.saturating_add(drag_cells(drag, cell_size));
+        } else {
+            middle_pan = None;
+        }
+
+        if is_left_down && stroke_state.is_none() && !is_minimap_dragging {
             let drag_distance: IVec2 = current_mouse_pos - mouse_down_pos;
             if !is_mouse_down {
                 is_mouse_down = true;
                 mouse_down_pos = current_mouse_pos;
                 previous_offset = origin;
-            } else if drag_distance.length_squared() > DRAG_THRESHOLD {
+                is_selecting = is_shift_down;
+                selection_anchor = hovered_cell;
+                zoom_anchor = (is_control_down && !is_shift_down).then_some(current_mouse_pos);
+            }
+
+            if is_selecting {
+                selection = Some(CellRect::from_corners(selection_anchor, hovered_cell));
+            } else if let Some(anchor) = zoom_anchor {
+                let (min, max) = (anchor.min(current_mouse_pos), anchor.max(current_mouse_pos));
+                d.draw_rectangle_lines(min.x, min.y, max.x - min.x + 1, max.y - min.y + 1, theme.accent);
+            } else if is_drag(drag_distance, drag_threshold) {
                 is_dragging = true;
-                let pan_delta = (current_mouse_pos - mouse_down_pos) / cell_size;
-                origin = previous_offset + pan_delta;
+                let drag = scale_drag(drag_distance, pan_sensitivity);
+                origin = previous_offset.saturating_add(drag_cells(drag, cell_size));
             }
         } else if d.is_mouse_button_up(MouseButton::MOUSE_BUTTON_LEFT) && is_mouse_down {
-            if !is_dragging {
-                let cell = current_mouse_pos / cell_size - origin;
-
-                if cells.contains(&cell) {
-                    cells.remove(&cell);
+            if let Some(anchor) = zoom_anchor.take() {
+                // A box too small to mean anything is dropped rather than zooming all the way in.
+                if is_drag(current_mouse_pos - anchor, drag_threshold) {
+                    let min = cell_at(anchor.min(current_mouse_pos), origin, cell_size);
+                    let max = cell_at(anchor.max(current_mouse_pos), origin, cell_size);
+                    (origin, cell_size) = fit_box(min, max, IVec2::new(screen_width, screen_height), 0);
+                }
+            } else if is_pasting && !is_dragging {
+                history.paste(&mut life, clipboard.iter().map(|&cell| cell + paste_at));
+                is_pasting = false;
+            } else if !is_dragging && !is_selecting {
+                let cell = cell_at(current_mouse_pos, origin, cell_size);
+                if is_measuring {
+                    // A third click starts a new measurement.
+                    ruler = match ruler {
+                        Some((start, None)) => Some((start, Some(cell))),
+                        _ => Some((cell, None)),
+                    };
+                } else if let Some(tool) = shape_tool {
+                    match shape_start.take() {
+                        Some(start) => {
+                            history.paste(&mut life, mirror(symmetry, symmetry_centre, tool.cells(start, cell)));
+                        }
+                        None => shape_start = Some(cell),
+                    }
+                } else if !is_click_toggle {
+                    let footprint = mirror(symmetry, symmetry_centre, brush.footprint(cell));
+                    history.replace(&mut life, &[], &footprint);
+                } else if brush.radius == 0 && symmetry.is_none() {
+                    history.toggle(&mut life, cell);
                 } else {
-                    cells.insert(cell);
+                    // Like a toggle, clicking a live cell erases under the brush and a dead one fills it.
+                    let footprint = mirror(symmetry, symmetry_centre, brush.footprint(cell));
+                    let added: &[I64Vec2] = if life.state(cell) == 0 { &footprint } else { &[] };
+                    history.replace(&mut life, &footprint, added);
                 }
             }
 
             is_dragging = false;
             is_mouse_down = false;
+            is_selecting = false;
         }
 
         // Keyboard input handling
 
-        if d.is_key_pressed(KeyboardKey::KEY_SPACE) {
+        if let Some((kind, mut input)) = prompt.take() {
+            while let Some(ch) = d.get_char_pressed() {
+                if input.len() < PROMPT_MAX_LENGTH {
+                    input.push(ch);
+                }
+            }
+
+            if d.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+                input.pop();
+                prompt = Some((kind, input));
+            } else if d.is_key_pressed(KeyboardKey::KEY_ENTER) || d.is_key_pressed(KeyboardKey::KEY_KP_ENTER) {
+                match kind {
+                    Prompt::GoTo => {
+                        if let Some(cell) = parse_cell(&input) {
+                            origin = centre_on(cell, IVec2::new(screen_width, screen_height), cell_size);
+                        }
+                    }
+                    Prompt::Jump => {
+                        if let Some(generations) = input.trim().parse().ok().filter(|&generations| generations > 0) {
+                            jump = Some(Jump::new(&life, generations));
+                            is_running = false;
+                            if is_snapshot_due(&generation_zero, &life, stepped_revision, args.snapshot_edits) {
+                                generation_zero = Some(Snapshot::capture(&life));
+                            }
+                        }
+                    }
+                    Prompt::FillSeed => {
+                        if let Ok(seed) = input.trim().parse() {
+                            fill_seed = seed;
+                        }
+                    }
+                    Prompt::RunTo => {
+                        if let Some(target) = parse_target(&input, life.generation()) {
+                            run_target = Some(target);
+                            is_running = true;
+                            if is_snapshot_due(&generation_zero, &life, stepped_revision, args.snapshot_edits) {
+                                generation_zero = Some(Snapshot::capture(&life));
+                            }
+                        }
+                    }
+                }
+            } else if !d.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                prompt = Some((kind, input));
+            }
+        } else if jump.is_some() && d.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            jump = None;
+            println!("Cancelled the jump at generation {}", life.generation());
+        } else if std::iter::from_fn(|| d.get_char_pressed()).any(|ch| ch == ':') {
+            prompt = Some((Prompt::GoTo, String::new()));
+        } else if keys.is_pressed(&d, Action::RunTo) {
+            prompt = Some((Prompt::RunTo, String::new()));
+        } else if keys.is_pressed(&d, Action::Jump) {
+            prompt = Some((Prompt::Jump, String::new()));
+        } else if keys.is_pressed(&d, Action::FillSeed) {
+            prompt = Some((Prompt::FillSeed, String::new()));
+        } else if is_control_down && is_shift_down && d.is_key_pressed(KeyboardKey::KEY_A) {
+            match bounding_box(&life.live_cells()) {
+                Some((min, max)) => selection = Some(CellRect::from_corners(min, max)),
+                None => notice = Some(("Nothing to select: the board is empty".to_string(), theme.text, current_time)),
+            }
+        } else if is_control_down && d.is_key_pressed(KeyboardKey::KEY_A) {
+            // Takes in the cells cut off at the right and bottom edges too.
+            let corner = cell_at(IVec2::new(screen_width, screen_height) - 1, origin, cell_size);
+            selection = Some(CellRect::from_corners(lower, corner));
+        } else if is_control_down && d.is_key_pressed(KeyboardKey::KEY_G) {
+            is_paste_snapped = !is_paste_snapped;
+        } else if is_control_down && d.is_key_pressed(KeyboardKey::KEY_Z) {
+            history.undo(&mut life);
+        } else if is_control_down && d.is_key_pressed(KeyboardKey::KEY_Y) {
+            history.redo(&mut life);
+        } else if is_control_down && is_shift_down && d.is_key_pressed(KeyboardKey::KEY_C) {
+            // Copies just the selection when there is one, and otherwise the whole board.
+            let cells = match selection {
+                Some(rect) => rect.filter(&life.live_cells()),
+                None => life.live_cells(),
+            };
+            match d.set_clipboard_text(&to_rle_with_rule(&cells, life.rule())) {
+                Ok(()) => println!("Copied {} cells as RLE", cells.len()),
+                Err(err) => eprintln!("Failed to copy RLE: {err}"),
+            }
+        } else if is_control_down && d.is_key_pressed(KeyboardKey::KEY_C) {
+            if let Some(rect) = selection {
+                clipboard = rect.copy(&life.live_cells());
+            }
+        } else if is_control_down && d.is_key_pressed(KeyboardKey::KEY_X) {
+            if let Some(rect) = selection {
+                clipboard = rect.copy(&life.live_cells());
+                history.erase(&mut life, &rect);
+            }
+        } else if keys.is_pressed(&d, Action::DeleteSelection) {
+            if let Some(rect) = selection {
+                history.erase(&mut life, &rect);
+            }
+        } else if keys.is_pressed(&d, Action::InvertSelection) {
+            match selection {
+                Some(rect) if rect.area() > MAX_EDIT_CELLS => {
+                    eprintln!("Not inverting {} cells, the most is {MAX_EDIT_CELLS}", rect.area());
+                }
+                Some(rect) => history.invert(&mut life, &rect),
+                None => {}
+            }
+        } else if is_control_down && is_shift_down && d.is_key_pressed(KeyboardKey::KEY_V) {
+            let pasted = d.get_clipboard_text().map_err(|err| err.to_string()).and_then(|text| {
+                let cells = parse_rle(&text).map_err(|err| err.to_string())?;
+                if cells.is_empty() { Err("no live cells".to_string()) } else { Ok(cells) }
+            });
+            match pasted {
+                Ok(cells) => {
+                    clipboard = cells.into_iter().collect();
+                    clipboard.sort_by_key(|cell| (cell.y, cell.x));
+                    is_pasting = true;
+                    notice = None;
+                }
+                Err(err) => {
+                    notice = Some((format!("Clipboard isn't an RLE pattern: {err}"), Color::RED, current_time));
+                }
+            }
+        } else if is_control_down && d.is_key_pressed(KeyboardKey::KEY_V) {
+            is_pasting = !clipboard.is_empty();
+        } else if is_control_down && d.is_key_pressed(KeyboardKey::KEY_S) {
+            let session = Session {
+                bookmarks,
+                fill_seed: last_fill_seed,
+                ..Session::capture(&life, origin, cell_size, is_running)
+            };
+            save_file(SESSION_PATH, session.to_json());
+        } else if is_control_down && d.is_key_pressed(KeyboardKey::KEY_O) {
+            match Session::load(Path::new(SESSION_PATH)) {
+                Ok(session) => {
+                    let (engine, torus) = (life.engine(), life.torus());
+                    life = session.board();
+                    life.set_engine(engine);
+                    life.set_torus(torus);
+                    origin = session.origin;
+                    bookmarks = session.bookmarks;
+                    if let Some(seed) = session.fill_seed {
+                        // The next fill of the same view at the same density makes the saved soup again.
+                        fill_seed = seed;
+                        last_fill_seed = Some(seed);
+                    }
+                    cell_size = session.cell_size.clamp(MIN_CELL_SIZE, MAX_CELL_SIZE);
+                    is_running = session.is_running;
+                    jump = None;
+                    // The histories describe the board being replaced, so they start over.
+                    history = EditHistory::new(HISTORY_CAPACITY);
+                    generations = GenerationHistory::new(args.history_depth);
+                    cycles.reset();
+                    worker = None;
+                    println!("Restored {}", display_path(SESSION_PATH));
+                }
+                Err(err) => eprintln!("Failed to restore {SESSION_PATH}: {err}"),
+            }
+        } else if keys.is_pressed(&d, Action::SpeedUp) {
+            speed_index = (speed_index + 1).min(SPEEDS.len() - 1);
+        } else if keys.is_pressed(&d, Action::SlowDown) {
+            speed_index = speed_index.saturating_sub(1);
+        } else if is_pasting
+            && (d.is_key_pressed(KeyboardKey::KEY_LEFT_ALT) || d.is_key_pressed(KeyboardKey::KEY_RIGHT_ALT))
+        {
+            // The first press pins the preview where it is; each press moves the pivot.
+            paste_pin = Some((paste_at, hovered_cell));
+        } else if d.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+            selection = None;
+            is_pasting = false;
+            shape_tool = None;
+            shape_start = None;
+            is_measuring = false;
+            ruler = None;
+            board_diff = None;
+        } else if is_shift_down && keys.is_pressed(&d, Action::ToggleRun) {
+            is_fixed_step = !is_fixed_step;
+            let text = if is_fixed_step { "Fixed step: one generation per frame" } else { "Real-time stepping" };
+            notice = Some((text.to_string(), theme.text, current_time));
+        } else if keys.is_pressed(&d, Action::ToggleRun) {
             is_running = !is_running;
-        } else if d.is_key_pressed(KeyboardKey::KEY_C) {
-            cells.clear();
+            if mem::take(&mut is_population_capped) {
+                population_cap = None;
+            }
+            if is_running && is_snapshot_due(&generation_zero, &life, stepped_revision, args.snapshot_edits) {
+                generation_zero = Some(Snapshot::capture(&life));
+            }
+        } else if keys.is_pressed(&d, Action::TakeSnapshot) {
+            generation_zero = Some(Snapshot::capture(&life));
+            println!("Took a generation zero snapshot of {} cells", life.population());
+        } else if keys.is_pressed(&d, Action::ResetToStart) {
+            match &generation_zero {
+                Some(snapshot) => {
+                    snapshot.restore(&mut life);
+                    life.set_generation(0);
+                    is_running = false;
+                    jump = None;
+                    // The histories describe the board being replaced, so they start over.
+                    history = EditHistory::new(HISTORY_CAPACITY);
+                    generations = GenerationHistory::new(args.history_depth);
+                    cycles.reset();
+                }
+                None => {
+                    let text = "Nothing to reset to until the simulation starts".to_string();
+                    notice = Some((text, Color::RED, current_time));
+                }
+            }
+        } else if !is_running && keys.is_pressed(&d, Action::Step) {
+            if is_snapshot_due(&generation_zero, &life, stepped_revision, args.snapshot_edits) {
+                generation_zero = Some(Snapshot::capture(&life));
+            }
+            let previous = (show_changes || show_trails).then(|| life.live_cells());
+            step(&mut life, None, &mut generations, &mut cycles, &mut step_times, &mut populations);
+            stepped_revision = Some(life.revision());
+            if let Some(previous) = previous {
+                let changes = StepChanges::since(&previous, &life, current_time);
+                if show_trails {
+                    trails.record(changes.died.iter().copied(), current_time);
+                }
+                step_changes = Some(changes);
+            }
+        } else if !is_running && keys.is_pressed(&d, Action::StepBack) {
+            // A jump only goes forwards, so going back ends it.
+            jump = None;
+            generations.step_back(&mut life);
+        } else if keys.is_pressed(&d, Action::Clear) {
+            jump = None;
+            history.clear(&mut life);
+        } else if let Some(direction) = [
+            (Action::PanUp, I64Vec2::NEG_Y),
+            (Action::PanDown, I64Vec2::Y),
+            (Action::PanLeft, I64Vec2::NEG_X),
+            (Action::PanRight, I64Vec2::X),
+        ]
+        .into_iter()
+        .find_map(|(action, direction)| keys.is_repeated(&d, action).then_some(direction))
+        {
+            let multiplier = if is_shift_down { FAST_PAN_MULTIPLIER } else { 1 };
+            // Moving the view towards a direction shifts the world the other way on screen.
+            origin = origin.saturating_sub(direction * pan_step(args.pan_step, cell_size) * multiplier);
+        } else if is_shift_down && keys.is_pressed(&d, Action::CycleRule) {
+            let noise = match life.noise() {
+                Some(_) => None,
+                None => Some(args.noise.unwrap_or(Noise {
+                    birth: DEFAULT_NOISE_CHANCE,
+                    survival: DEFAULT_NOISE_CHANCE,
+                    ..Noise::default()
+                })),
+            };
+            life.set_noise(noise);
+            let text = if noise.is_some() { "Random births and survivals" } else { "Certain births and survivals" };
+            notice = Some((text.to_string(), theme.text, current_time));
+        } else if keys.is_pressed(&d, Action::CycleRule) {
+            let preset = RulePreset::from_rule(life.rule()).map_or(RulePreset::Conway, RulePreset::next);
+            life.set_rule(preset.rule());
+        } else if keys.is_pressed(&d, Action::Neighbourhood) {
+            let neighbourhood = match life.rule().neighbourhood {
+                Neighbourhood::Moore => Neighbourhood::VonNeumann,
+                Neighbourhood::VonNeumann | Neighbourhood::Weighted(_) => Neighbourhood::Moore,
+            };
+            life.set_rule(life.rule().with_neighbourhood(neighbourhood));
+        } else if is_shift_down && keys.is_pressed(&d, Action::OldestCells) {
+            let gliders = find_gliders(&life.live_cells());
+            let summary = describe_gliders(&gliders);
+            println!("{summary} at generation {}", life.generation());
+            for glider in &gliders {
+                let corner = glider.cells.iter().copied().reduce(I64Vec2::min).unwrap();
+                println!("  ({}, {}) heading {}", corner.x, corner.y, direction_name(glider.direction));
+            }
+            notice = Some((summary, theme.text, current_time));
+            found_gliders = Some((life.revision(), gliders));
+        } else if keys.is_pressed(&d, Action::OldestCells) {
+            let oldest = life.oldest_cells(OLDEST_CELLS_LISTED);
+            println!("The {} longest-lived cells at generation {}:", oldest.len(), life.generation());
+            for (cell, age) in oldest {
+                println!("  ({}, {}): {age} generations", cell.x, cell.y);
+            }
+        } else if keys.is_pressed(&d, Action::ExportRle) {
+            let rle = to_rle_with_view(&life.live_cells(), life.rule(), life.generation(), (origin, cell_size));
+            save_file(RLE_EXPORT_PATH, rle);
+        } else if keys.is_pressed(&d, Action::ExportCells) {
+            save_file(CELLS_EXPORT_PATH, to_cells(&life.live_cells()));
+        } else if keys.is_pressed(&d, Action::ExportImage) {
+            match render_cells(&life.live_cells(), args.export_scale, theme.background, theme.cell) {
+                Ok(image) => match image.export_image_to_memory(".png") {
+                    Ok(png) => save_file(PNG_EXPORT_PATH, png),
+                    Err(err) => eprintln!("Failed to encode {PNG_EXPORT_PATH}: {err}"),
+                },
+                Err(err) => eprintln!("Not exporting {PNG_EXPORT_PATH}: {err}"),
+            }
+        } else if keys.is_pressed(&d, Action::RandomFill) {
+            println!("Random fill seed: {fill_seed}");
+            let soup = random_soup(&mut Rng::seed_from_u64(fill_seed), lower, upper, fill_density);
+            last_fill_seed = Some(fill_seed);
+            fill_seed = fill_seed.wrapping_add(1);
+
+            let soup: Vec<I64Vec2> = soup.into_iter().collect();
+            if is_shift_down {
+                let visible = CellRect::from_corners(lower, upper - 1);
+                let replaced: Vec<I64Vec2> = visible.filter(&life.live_cells()).into_iter().collect();
+                history.replace(&mut life, &replaced, &soup);
+            } else {
+                history.paste(&mut life, soup);
+            }
+        } else if let Some(is_raised) = [(KeyboardKey::KEY_COMMA, false), (KeyboardKey::KEY_PERIOD, true)]
+            .into_iter()
+            .find_map(|(key, is_raised)| (is_control_down && d.is_key_pressed(key)).then_some(is_raised))
+        {
+            let text = if is_shift_down {
+                drag_threshold = (drag_threshold + if is_raised { 1 } else { -1 }).clamp(0, MAX_DRAG_THRESHOLD);
+                format!("Drags start after {:.1} pixels", (drag_threshold as f64).sqrt())
+            } else {
+                let step = if is_raised { PAN_SENSITIVITY_STEP } else { -PAN_SENSITIVITY_STEP };
+                pan_sensitivity = (pan_sensitivity + step).clamp(MIN_PAN_SENSITIVITY, MAX_PAN_SENSITIVITY);
+                format!("Pan sensitivity: {pan_sensitivity:.2}x")
+            };
+            notice = Some((text, theme.text, current_time));
+        } else if is_shift_down && d.is_key_pressed(KeyboardKey::KEY_COMMA) {
+            fill_density = (fill_density - FILL_DENSITY_STEP).max(0.0);
+        } else if is_shift_down && d.is_key_pressed(KeyboardKey::KEY_PERIOD) {
+            fill_density = (fill_density + FILL_DENSITY_STEP).min(1.0);
+        } else if d.is_key_pressed(KeyboardKey::KEY_COMMA) {
+            brush.shrink();
+        } else if d.is_key_pressed(KeyboardKey::KEY_PERIOD) {
+            brush.grow();
+        } else if d.is_key_pressed(KeyboardKey::KEY_SLASH) {
+            brush.shape = brush.shape.next();
+        } else if keys.is_pressed(&d, Action::ToggleHelp) {
+            show_help = !show_help;
+        } else if keys.is_pressed(&d, Action::ResetView) {
+            origin = I64Vec2::ZERO;
+            cell_size = DEFAULT_CELL_SIZE;
+        } else if is_shift_down && keys.is_pressed(&d, Action::FitView) {
+            // Fills the window with the selection, as a zoom box drawn around it would.
+            if let Some(rect) = selection {
+                (origin, cell_size) = fit_box(rect.min, rect.max, IVec2::new(screen_width, screen_height), 0);
+            }
+        } else if keys.is_pressed(&d, Action::FitView) {
+            if let Some((min, max)) = bounding_box(&life.live_cells()) {
+                (origin, cell_size) = fit_box(min, max, IVec2::new(screen_width, screen_height), FIT_MARGIN);
+            }
+        } else if keys.is_pressed(&d, Action::Follow) {
+            following = match following {
+                Some(_) => None,
+                None => Some((origin, cell_size)),
+            };
+            let text = if following.is_some() { "Following the pattern" } else { "Stopped following" };
+            notice = Some((text.to_string(), theme.text, current_time));
+        } else if keys.is_pressed(&d, Action::Screenshot) {
+            is_screenshot_requested = true;
+        } else if is_shift_down && keys.is_pressed(&d, Action::RecordGif) {
+            match frame_sequence.take() {
+                Some(active) => active.finish(),
+                None => frame_sequence = FrameSequence::start(&args),
+            }
+        } else if keys.is_pressed(&d, Action::RecordGif) {
+            match recording.take() {
+                Some(active) => active.finish(),
+                None => recording = Recording::start(screen_width, screen_height, current_time, is_fixed_step),
+            }
+        } else if keys.is_pressed(&d, Action::CycleTheme) {
+            theme_index = (theme_index + 1) % themes.len();
+        } else if is_shift_down && keys.is_pressed(&d, Action::Grid) {
+            show_axes = !show_axes;
+        } else if keys.is_pressed(&d, Action::Grid) {
+            show_grid = !show_grid;
+        } else if keys.is_pressed(&d, Action::HighlightChanges) {
+            show_changes = !show_changes;
+            step_changes = None;
+        } else if keys.is_pressed(&d, Action::Heatmap) {
+            show_heatmap = !show_heatmap;
+            heatmap = None;
+        } else if is_shift_down && keys.is_pressed(&d, Action::Trails) {
+            let next = TRAIL_DURATIONS.iter().position(|&duration| duration > trails.duration()).unwrap_or(0);
+            trails.set_duration(TRAIL_DURATIONS[next]);
+            notice = Some((format!("Trails fade over {}s", TRAIL_DURATIONS[next]), theme.text, current_time));
+        } else if keys.is_pressed(&d, Action::Trails) {
+            show_trails = !show_trails;
+            trails.clear();
+        } else if keys.is_pressed(&d, Action::Minimap) {
+            show_minimap = !show_minimap;
+        } else if keys.is_pressed(&d, Action::PopulationGraph) {
+            show_population_graph = !show_population_graph;
+        } else if is_shift_down && keys.is_pressed(&d, Action::LineTool) {
+            is_measuring = !is_measuring;
+            ruler = None;
+            shape_tool = None;
+        } else if keys.is_pressed(&d, Action::LineTool) {
+            shape_tool = if shape_tool == Some(ShapeTool::Line) { None } else { Some(ShapeTool::Line) };
+            shape_start = None;
+            is_measuring = false;
+        } else if keys.is_pressed(&d, Action::RectangleTool) {
+            shape_tool = match shape_tool {
+                Some(ShapeTool::Rectangle) => Some(ShapeTool::FilledRectangle),
+                Some(ShapeTool::FilledRectangle) => None,
+                _ => Some(ShapeTool::Rectangle),
+            };
+            shape_start = None;
+            is_measuring = false;
+        } else if is_shift_down && keys.is_pressed(&d, Action::Symmetry) {
+            symmetry_centre = hovered_cell;
+        } else if keys.is_pressed(&d, Action::Symmetry) {
+            symmetry = Symmetry::next(symmetry);
+        } else if keys.is_pressed(&d, Action::DrawMode) {
+            is_draw_mode = !is_draw_mode;
+        } else if keys.is_pressed(&d, Action::ClickToggles) {
+            is_click_toggle = !is_click_toggle;
+        } else if keys.is_pressed(&d, Action::Colouring) {
+            life.set_colouring(Colouring::next(life.colouring()));
+        } else if let Some(colouring) = life.colouring()
+            && keys.is_pressed(&d, Action::PaintColour)
+        {
+            life.set_paint_colour(life.paint_colour() % colouring.colours() + 1);
+        } else if keys.is_pressed(&d, Action::Torus) {
+            // Cycles from the infinite board to a torus, then to walls and back.
+            life.set_torus(match life.torus().map(|torus| torus.edges) {
+                None => Some(args.torus.with_edges(Edges::Wrap)),
+                Some(Edges::Wrap) => Some(args.torus.with_edges(Edges::Wall)),
+                Some(Edges::Wall) => None,
+            });
+        } else if keys.is_pressed(&d, Action::PauseOnCycle) {
+            pause_on_cycle = !pause_on_cycle;
+        } else if keys.is_pressed(&d, Action::CycleEngine) {
+            life.set_engine(match life.engine() {
+                Engine::Naive => Engine::Incremental,
+                Engine::Incremental => Engine::HashLife,
+                Engine::HashLife => Engine::Naive,
+            });
+        }
+
+        let is_typing = prompt.is_some();
+        let pressed_transform = [
+            (KeyboardKey::KEY_LEFT_BRACKET, rotate_counterclockwise as fn(&[I64Vec2]) -> Vec<I64Vec2>),
+            (KeyboardKey::KEY_RIGHT_BRACKET, rotate_clockwise),
+            (KeyboardKey::KEY_H, flip_horizontal),
+            (KeyboardKey::KEY_V, flip_vertical),
+        ]
+        .into_iter()
+        .find(|&(key, _)| !is_control_down && !is_shift_down && !is_typing && d.is_key_pressed(key));
+        let pressed_scale = [(KeyboardKey::KEY_LEFT_BRACKET, false), (KeyboardKey::KEY_RIGHT_BRACKET, true)]
+            .into_iter()
+            .find(|&(key, _)| is_shift_down && !is_control_down && !is_typing && d.is_key_pressed(key));
+        if let Some((_, is_growing)) = pressed_scale {
+            let scale = if is_growing { scale_up } else { scale_down };
+            if is_pasting {
+                clipboard = scale(&clipboard, I64Vec2::ZERO, SCALE_FACTOR);
+            } else if let Some(rect) = selection {
+                let selected: Vec<I64Vec2> = rect.filter(&life.live_cells()).into_iter().collect();
+                let grown_cells = (selected.len() as u64).saturating_mul((SCALE_FACTOR * SCALE_FACTOR) as u64);
+                if is_growing && grown_cells > MAX_EDIT_CELLS {
+                    eprintln!("Not scaling up to {grown_cells} cells, the most is {MAX_EDIT_CELLS}");
+                } else {
+                    history.replace(&mut life, &selected, &scale(&selected, rect.min, SCALE_FACTOR));
+                    let size = if is_growing {
+                        rect.size().saturating_mul(I64Vec2::splat(SCALE_FACTOR))
+                    } else {
+                        (rect.size() + SCALE_FACTOR - 1) / SCALE_FACTOR
+                    };
+                    selection = Some(CellRect::from_corners(rect.min, rect.min.saturating_add(size - 1)));
+                }
+            }
+        }
+        if let Some((key, transform)) = pressed_transform {
+            let is_rotation = matches!(key, KeyboardKey::KEY_LEFT_BRACKET | KeyboardKey::KEY_RIGHT_BRACKET);
+            if is_pasting
+                && is_rotation
+                && let Some((anchor, pivot)) = paste_pin
+            {
+                clipboard = rotate_about(&clipboard, pivot - anchor, key == KeyboardKey::KEY_RIGHT_BRACKET);
+            } else if is_pasting {
+                clipboard = transform(&clipboard);
+            } else if let Some(rect) = selection {
+                let selected: Vec<I64Vec2> = rect.filter(&life.live_cells()).into_iter().collect();
+                let transformed = transform(&selected);
+                history.replace(&mut life, &selected, &transformed);
+                if let Some((min, max)) = bounding_box(&transformed.iter().copied().collect()) {
+                    selection = Some(CellRect::from_corners(min, max));
+                }
+            }
+        }
+
+        let pressed_digit = DIGIT_KEYS.iter().position(|&key| !is_typing && d.is_key_pressed(key));
+        if let Some(slot) = pressed_digit {
+            if is_control_down {
+                bookmarks[slot] = Some(Bookmark { origin, cell_size });
+                notice = Some((format!("Saved bookmark {}", slot + 1), theme.text, current_time));
+            } else if let Some(pattern) = palette.get(slot).filter(|_| !is_shift_down) {
+                history.paste(&mut life, pattern.cells.iter().map(|&cell| cell + hovered_cell));
+            } else {
+                // Digits with a palette pattern on them need Shift to recall their bookmark.
+                let text = match bookmarks[slot] {
+                    Some(bookmark) => {
+                        origin = bookmark.origin;
+                        cell_size = bookmark.cell_size.clamp(MIN_CELL_SIZE, MAX_CELL_SIZE);
+                        format!("Went to bookmark {}", slot + 1)
+                    }
+                    None => format!("Bookmark {} is empty, save it with Ctrl+{}", slot + 1, slot + 1),
+                };
+                notice = Some((text, theme.text, current_time));
+            }
         }
 
         // Wheel input handling
 
         let wheel = d.get_mouse_wheel_move();
-        if wheel != 0.0 {
+        // Anything else that zooms, such as fitting the view, takes over from a smooth zoom.
+        if smooth_zoom.is_some_and(|zoom| zoom.size.round() as i32 != cell_size) {
+            smooth_zoom = None;
+        }
+        if wheel != 0.0 && is_zoom_smooth {
+            smooth_zoom = Some(SmoothZoom::step(smooth_zoom, cell_size, wheel > 0.0, current_mouse_pos));
+        } else if wheel != 0.0 {
             let old_cell_size = cell_size;
 
             if wheel > 0.0 {
@@ -155,56 +1484,603 @@ fn main() {
             } else {
                 cell_size = (cell_size as f32 * 0.9).floor() as i32;
             }
-            cell_size = cell_size.clamp(2, 100);
+            cell_size = cell_size.clamp(MIN_CELL_SIZE, MAX_CELL_SIZE);
 
-            if cell_size != old_cell_size {
-                let world_under_mouse = origin + current_mouse_pos / old_cell_size;
-                let new_screen_pos = (world_under_mouse - origin) * cell_size;
-
-                origin += (current_mouse_pos - new_screen_pos) / cell_size;
+            origin = zoom_at(current_mouse_pos, origin, old_cell_size, cell_size);
+        }
+        if let Some(zoom) = &mut smooth_zoom {
+            // Each frame keeps the cell under the cursor in place, so the whole zoom does too.
+            let new_cell_size = zoom.advance().unwrap_or(zoom.target.round() as i32);
+            origin = zoom_at(zoom.pixel, origin, cell_size, new_cell_size);
+            cell_size = new_cell_size;
+            if zoom.size == zoom.target {
+                smooth_zoom = None;
             }
         }
 
         // Simulation Logic
 
+        if let Some(active) = jump {
+            is_running = false;
+            let is_done = active.run(&mut life, JUMP_FRAME_BUDGET, |life| {
+                populations.record(life.generation(), life.population() as u64);
+            });
+            stepped_revision = Some(life.revision());
+            if is_done {
+                jump = None;
+                println!("Jumped to generation {}", life.generation());
+            } else if population_cap.is_some_and(|cap| life.population() > cap) {
+                jump = None;
+                is_population_capped = true;
+            }
+        }
         if is_running {
-            let elapsed_time = current_time - last_time;
-            last_time = current_time;
-            last_frame_time += elapsed_time;
-            if last_frame_time >= (1.0 / SIMULATION_STEPS_PER_SECOND as f32) as f64 {
+            last_frame_time += current_time - last_time;
+            // Unlimited speed steps once per frame; otherwise every step that has come due
+            // runs, keeping the leftover time for the next frame. A run to a target generation
+            // ignores the speed and steps for as long as the frame budget allows.
+            let interval = SPEEDS[speed_index].map_or(0.0, |steps_per_second| 1.0 / steps_per_second as f64);
+            let is_one_per_frame = is_fixed_step && run_target.is_none();
+            if is_one_per_frame {
                 last_frame_time = 0.0;
-                cells = process_cells(&cells);
+            }
+            let started = Instant::now();
+            let mut steps = 0;
+            let mut is_waiting = false;
+            // The worker steps ahead of the board on screen until the board is edited.
+            if worker.as_ref().is_some_and(|worker| !worker.follows(&life)) {
+                worker = None;
+            }
+            while is_running {
+                let is_due = match run_target {
+                    Some(target) => life.generation() < target && started.elapsed() < RUN_TO_FRAME_BUDGET,
+                    None if is_one_per_frame => steps == 0,
+                    None => last_frame_time >= interval && steps < MAX_STEPS_PER_FRAME,
+                };
+                if !is_due {
+                    break;
+                }
+                // Only a run to a target waits on the worker; otherwise the frame is drawn
+                // with the latest finished generation.
+                let timeout = match run_target {
+                    Some(_) => RUN_TO_FRAME_BUDGET.saturating_sub(started.elapsed()),
+                    None => Duration::ZERO,
+                };
+                // Fixed-step mode steps here rather than skipping a frame the worker isn't ready for.
+                let next = if is_one_per_frame {
+                    None
+                } else {
+                    let Some(next) = worker.get_or_insert_with(|| Worker::spawn(&life)).next_within(timeout) else {
+                        is_waiting = true;
+                        break;
+                    };
+                    Some(next)
+                };
+                if run_target.is_none() && !is_one_per_frame {
+                    last_frame_time -= interval;
+                }
+                steps += 1;
+                // Covers a session restored mid-run, which never pressed start.
+                if generation_zero.is_none() {
+                    generation_zero = Some(Snapshot::capture(&life));
+                }
+                let previous = (show_changes || show_trails).then(|| life.live_cells());
+                let cycle = step(&mut life, next, &mut generations, &mut cycles, &mut step_times, &mut populations);
+                stepped_revision = Some(life.revision());
+                if let Some(previous) = previous {
+                    let changes = StepChanges::since(&previous, &life, current_time);
+                    if show_trails {
+                        trails.record(changes.died.iter().copied(), current_time);
+                    }
+                    step_changes = Some(changes);
+                }
+                if life.is_stable() || (cycle.is_some() && pause_on_cycle) {
+                    is_running = false;
+                }
+                if population_cap.is_some_and(|cap| life.population() > cap) {
+                    is_running = false;
+                    is_population_capped = true;
+                }
+                if interval == 0.0 && run_target.is_none() {
+                    break;
+                }
+            }
+            if run_target.is_some_and(|target| life.generation() >= target) {
+                is_running = false;
+            }
+            // A board too slow to keep up drops the backlog instead of falling ever further behind.
+            if steps == MAX_STEPS_PER_FRAME || is_waiting {
+                last_frame_time = last_frame_time.min(interval);
             }
         }
+        // Time spent paused doesn't count towards the next step once running again, and pausing
+        // abandons a run to a target generation.
+        if !is_running {
+            last_frame_time = 0.0;
+            run_target = None;
+            worker = None;
+        }
+        last_time = current_time;
+
+        // Keeps the middle of the live cells in the middle of the view. It works best on a lone
+        // spaceship; a scattered board just sits still around its centre.
+        if let Some((followed_origin, followed_cell_size)) = following {
+            if origin != followed_origin && cell_size == followed_cell_size {
+                following = None;
+            } else {
+                if follow_target.is_none_or(|(revision, _)| revision != life.revision()) {
+                    let live = life.cells().iter().filter(|&(_, &state)| state == ALIVE).map(|(&cell, _)| cell);
+                    follow_target = centroid(live).map(|target| (life.revision(), target));
+                }
+                if let Some((_, target)) = follow_target {
+                    origin = follow_step(origin, centre_on(target, IVec2::new(screen_width, screen_height), cell_size));
+                }
+                following = Some((origin, cell_size));
+            }
+        }
+
+        // Preferences are saved as soon as the user changes one, so they survive however the app
+        // is closed. The first frame only notes them, so a launch that changes nothing writes nothing.
+        let preferences = Config {
+            dark_theme: if args.theme.is_none() { Some(themes[theme_index] == Theme::DARK) } else { config.dark_theme },
+            speed: Some(SPEEDS[speed_index].unwrap_or(0)),
+            brush_radius: Some(brush.radius),
+            brush_shape: Some(brush.shape),
+            fill_density: Some(fill_density),
+            show_grid: Some(show_grid),
+            draw_mode: Some(is_draw_mode),
+            click_toggles: Some(is_click_toggle),
+            drag_threshold: Some(drag_threshold),
+            pan_sensitivity: Some(pan_sensitivity),
+            smooth_zoom: config.smooth_zoom,
+            keys: config.keys.clone(),
+        };
+        if last_preferences.as_ref().is_some_and(|last| *last != preferences)
+            && let Some(path) = &config_path
+            && let Err(err) = preferences.save(path)
+        {
+            eprintln!("Failed to save the config to {}: {err}", path.display());
+        }
+        last_preferences = Some(preferences);
     }
 }
 
-fn process_cells(survived_cells: &HashSet<IVec2>) -> HashSet<IVec2> {
-    let neighbour_counts = convolve(survived_cells);
+/// Steps the board forward, or moves it on to the `next` generation a worker has stepped,
+/// returning the cycle it has just fallen into, if any. Either way the step's time and the new
+/// generation's population are recorded.
+fn step(
+    life: &mut Life,
+    next: Option<(Life, Duration)>,
+    generations: &mut GenerationHistory,
+    cycles: &mut CycleDetector,
+    step_times: &mut StepTimes,
+    populations: &mut PopulationHistory,
+) -> Option<Cycle> {
+    // Observing first catches edits made since the last step.
+    cycles.observe(life);
+    generations.record(life);
+    let took = match next {
+        Some((next, took)) => {
+            *life = next;
+            took
+        }
+        None => {
+            let started = Instant::now();
+            life.step();
+            started.elapsed()
+        }
+    };
+    step_times.record(took);
+    populations.record(life.generation(), life.population() as u64);
+    let cycle = cycles.observe(life)?;
+    println!("Detected {} at generation {}", describe_cycle(&cycle), cycle.generation);
+    Some(cycle)
+}
 
-    let survivors = survived_cells
-        .iter()
-        .filter(|&cell| match neighbour_counts.get(cell) {
-            Some(2) | Some(3) => true,
-            _ => false,
-        }).copied();
+/// Whether starting the simulation should take a new generation zero snapshot: always the
+/// first time, and with `--snapshot-edits` whenever the board was edited since the last step.
+fn is_snapshot_due(snapshot: &Option<Snapshot>, life: &Life, stepped_revision: Option<u64>, edits: bool) -> bool {
+    snapshot.is_none() || (edits && stepped_revision != Some(life.revision()))
+}
 
-    let births = neighbour_counts
-        .iter()
-        .filter(|&(_, &count)| count == 3)
-        .map(|(&cell, _)| cell);
+/// Adds the mirror images of the cells under the active symmetry, if any.
+fn mirror(symmetry: Option<Symmetry>, centre: I64Vec2, cells: Vec<I64Vec2>) -> Vec<I64Vec2> {
+    match symmetry {
+        Some(symmetry) => symmetry.apply(&cells, centre),
+        None => cells,
+    }
+}
+
+fn describe_cycle(cycle: &Cycle) -> String {
+    match (cycle.period, cycle.displacement) {
+        (1, _) => "still life".to_string(),
+        (period, I64Vec2::ZERO) => format!("period {period} oscillator"),
+        (period, displacement) => format!("period {period} spaceship ({}, {})", displacement.x, displacement.y),
+    }
+}
+
+/// Steps the loaded pattern without opening a window, exiting with an error if it can't load.
+fn run_headless(args: &Args) {
+    let Some(path) = &args.load else {
+        eprintln!("--headless needs a pattern to load\n{USAGE}");
+        process::exit(2);
+    };
+    let cells = load_pattern(path).unwrap_or_else(|err| {
+        eprintln!("Failed to load {}: {err}", path.display());
+        process::exit(1);
+    });
+
+    let mut life = Life::from_cells(cells);
+    life.set_rule(args.rule);
+    life.set_noise(args.noise);
+    if !args.csv {
+        println!("{}", simulate(&mut life, args.steps));
+    } else if let Err(err) = simulate_csv(&mut life, args.steps, &mut io::stdout().lock()) {
+        // A plotter closing the pipe early is how a piped run normally ends, so it isn't reported.
+        if err.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("Failed to write CSV: {err}");
+            process::exit(1);
+        }
+    }
+}
 
-    survivors.chain(births).collect()
+fn run_benchmark() {
+    let soup = random_soup(&mut Rng::seed_from_u64(0), I64Vec2::ZERO, I64Vec2::splat(BENCH_SOUP_SIZE), 0.5);
+    let life = Life::from_cells(soup.clone());
+    println!("Counting neighbours of a {}-cell soup, {BENCH_ITERATIONS} iterations each", life.population());
+
+    let time = |name: &str, convolve: fn(&_, &_) -> _| {
+        let start = Instant::now();
+        let mut counts = Default::default();
+        for _ in 0..BENCH_ITERATIONS {
+            counts = convolve(life.cells(), &Kernel::default());
+        }
+        let elapsed = start.elapsed().as_secs_f64() * 1000.0 / BENCH_ITERATIONS as f64;
+        println!("{name:>11}: {elapsed:.1} ms");
+        counts
+    };
+
+    let serial = time("serial", convolve_serial);
+    let parallel = time("parallel", convolve_parallel);
+    println!("Results identical: {}", serial == parallel);
+
+    println!("Running a Gosper glider gun for {BENCH_GUN_GENERATIONS} generations");
+    let time_engine = |name: &str, engine: Engine| {
+        let mut life = Life::from_cells(gosper_glider_gun().into_iter().collect());
+        life.set_engine(engine);
+        let start = Instant::now();
+        life.advance(BENCH_GUN_GENERATIONS);
+        println!("{name:>11}: {:.1} ms", start.elapsed().as_secs_f64() * 1000.0);
+        life
+    };
+
+    let naive = time_engine("naive", Engine::Naive);
+    let incremental = time_engine("incremental", Engine::Incremental);
+    let hashlife = time_engine("hashlife", Engine::HashLife);
+    println!("Results identical: {}", naive == incremental && naive == hashlife);
+
+    println!("Stepping the soup with hash set and chunked storage, {BENCH_ITERATIONS} generations each");
+    let mut hashed = life.clone();
+    let hashed_bytes = hashed.cells().capacity() * (mem::size_of::<(I64Vec2, u8)>() + 1);
+    let start = Instant::now();
+    hashed.advance(BENCH_ITERATIONS as u64);
+    let elapsed = start.elapsed().as_secs_f64() * 1000.0 / BENCH_ITERATIONS as f64;
+    println!("{:>11}: {elapsed:.1} ms, {:.1} MB", "hash set", hashed_bytes as f64 / 1e6);
+
+    let mut chunked = ChunkedWorld::from_cells(&soup, *life.rule());
+    let chunked_bytes = chunked.memory_bytes();
+    let start = Instant::now();
+    for _ in 0..BENCH_ITERATIONS {
+        chunked.step();
+    }
+    let elapsed = start.elapsed().as_secs_f64() * 1000.0 / BENCH_ITERATIONS as f64;
+    let chunks = chunked.chunk_count();
+    println!("{:>11}: {elapsed:.1} ms, {:.1} MB in {chunks} chunks", "chunked", chunked_bytes as f64 / 1e6);
+    println!("Results identical: {}", chunked.cells() == hashed.live_cells());
 }
 
-fn convolve(survived_cells: &HashSet<IVec2>) -> HashMap<IVec2, usize> {
-    let deltas = (-1..=1)
-        .cartesian_product(-1..=1)
-        .map(|(x, y)| IVec2::new(x, y))
-        .filter(|&d| d != IVec2::ZERO)
-        .collect_vec();
+/// Writes a byte count in the largest unit that keeps it at least 1, such as `12.3 MB`.
+fn format_bytes(bytes: usize) -> String {
+    let units = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < units.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{bytes} B") } else { format!("{size:.1} {}", units[unit]) }
+}
+
+/// Outlines the screen-space box from `min` to `max` with dashes along every edge.
+fn draw_dashed_rectangle(d: &mut RaylibDrawHandle, min: IVec2, max: IVec2, color: Color) {
+    let corners = [min, IVec2::new(max.x, min.y), max, IVec2::new(min.x, max.y)];
+
+    for (index, &start) in corners.iter().enumerate() {
+        let end = corners[(index + 1) % corners.len()];
+        let direction = (end - start).signum();
+        let length = (end - start).abs().max_element();
+
+        for offset in (0..length).step_by(DASH_LENGTH as usize * 2) {
+            let dash_start = start + direction * offset;
+            let dash_end = start + direction * (offset + DASH_LENGTH).min(length);
+            d.draw_line_ex(
+                Vector2::new(dash_start.x as f32, dash_start.y as f32),
+                Vector2::new(dash_end.x as f32, dash_end.y as f32),
+                2.0,
+                color,
+            );
+        }
+    }
+}
+
+/// Draws the population line graph in a panel whose top-left corner is `top_left`, labelled
+/// with the largest population it shows.
+fn draw_population_graph(d: &mut RaylibDrawHandle, populations: &PopulationHistory, top_left: IVec2, theme: &Theme) {
+    let panel_size = POPULATION_GRAPH_SIZE + IVec2::new(10, 40);
+    d.draw_rectangle(top_left.x, top_left.y, panel_size.x, panel_size.y, theme.background.alpha(0.8));
+    d.draw_rectangle_lines(top_left.x, top_left.y, panel_size.x, panel_size.y, theme.text);
+    d.draw_text(&format!("Population (max {})", populations.max()), top_left.x + 5, top_left.y + 5, 20, theme.text);
+
+    let graph_origin = (top_left + IVec2::new(5, 35)).as_vec2();
+    let points: Vec<Vector2> = populations
+        .points(POPULATION_GRAPH_SIZE.as_vec2())
+        .into_iter()
+        .map(|point| Vector2::new(graph_origin.x + point.x, graph_origin.y + point.y))
+        .collect();
+    for pair in points.windows(2) {
+        d.draw_line_ex(pair[0], pair[1], 2.0, theme.accent);
+    }
+}
+
+/// Draws the minimap with its top-left corner at `top_left`, outlining the visible cells
+/// `lower..upper`.
+fn draw_minimap(d: &mut RaylibDrawHandle, minimap: &Minimap, top_left: IVec2, view: (I64Vec2, I64Vec2), theme: &Theme) {
+    let size = minimap.size();
+    d.draw_rectangle(top_left.x, top_left.y, size.x, size.y, theme.background.alpha(0.8));
+    let pixel_size = minimap.pixel_size();
+    for &pixel in minimap.pixels() {
+        let corner = top_left + pixel;
+        d.draw_rectangle(corner.x, corner.y, pixel_size, pixel_size, theme.cell);
+    }
+
+    // The viewport can reach well past the pattern, so it is clipped to the minimap.
+    let (lower, upper) = view;
+    let clip = |corner: Vec2| corner.clamp(Vec2::ZERO, size.as_vec2()) + top_left.as_vec2();
+    let (min, max) = (clip(minimap.to_minimap(lower)), clip(minimap.to_minimap(upper)));
+    d.draw_rectangle_lines_ex(Rectangle::new(min.x, min.y, max.x - min.x, max.y - min.y), 1.0, theme.accent);
+    d.draw_rectangle_lines(top_left.x, top_left.y, size.x, size.y, theme.text);
+}
+
+/// Ramps from the young to the old cell colour on a log scale, so early generations stay distinct.
+fn age_color(theme: &Theme, age: u32) -> Color {
+    let t = ((age as f32).ln() / (AGE_RAMP_GENERATIONS as f32).ln()).clamp(0.0, 1.0);
+    theme.cell.lerp(theme.old_cell, t)
+}
+
+/// Ramps from blue for lone cells through green to red for the most crowded, translucent so
+/// the cells underneath still show.
+fn heatmap_color(count: usize, max_count: usize) -> Color {
+    let t = (count as f32 / max_count.max(1) as f32).min(1.0);
+    Color::color_from_hsv(240.0 * (1.0 - t), 0.8, 0.9).alpha(0.45)
+}
+
+/// The cells born and the cells that died in the last step, for the change highlight.
+struct StepChanges {
+    born: Vec<I64Vec2>,
+    died: Vec<I64Vec2>,
+    stepped_at: f64,
+    /// The board revision after the step, so an edit can hide markers that no longer apply.
+    revision: u64,
+}
+
+impl StepChanges {
+    fn since(previous: &HashSet<I64Vec2>, life: &Life, time: f64) -> Self {
+        let next = life.live_cells();
+        Self {
+            born: next.difference(previous).copied().collect(),
+            died: previous.difference(&next).copied().collect(),
+            stepped_at: time,
+            revision: life.revision(),
+        }
+    }
+}
+
+/// What the text prompt at the bottom of the screen is asking for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    GoTo,
+    RunTo,
+    Jump,
+    FillSeed,
+}
+
+impl Prompt {
+    fn label(self) -> &'static str {
+        match self {
+            Prompt::GoTo => "Go to x,y",
+            Prompt::RunTo => "Run to generation (or +steps)",
+            Prompt::Jump => "Jump ahead by generations",
+            Prompt::FillSeed => "Seed for the next random fill",
+        }
+    }
+}
+
+/// A GIF being streamed to disk from the window contents.
+struct Recording {
+    encoder: GifEncoder<BufWriter<File>>,
+    path: String,
+    last_capture: f64,
+    /// Whether it was started in fixed-step mode, taking one equally long frame per generation
+    /// instead of a frame every [`GIF_FRAME_INTERVAL`].
+    is_fixed_step: bool,
+    last_generation: Option<u64>,
+}
+
+impl Recording {
+    fn start(width: i32, height: i32, time: f64, is_fixed_step: bool) -> Option<Self> {
+        let path = format!("recording-{}.gif", timestamp(SystemTime::now()));
+        let encoder = File::create(&path)
+            .and_then(|file| GifEncoder::new(BufWriter::new(file), width as u16, height as u16));
+        match encoder {
+            Ok(encoder) => Some(Self {
+                encoder,
+                path,
+                last_capture: time - GIF_FRAME_INTERVAL,
+                is_fixed_step,
+                last_generation: None,
+            }),
+            Err(err) => {
+                eprintln!("Failed to start recording {path}: {err}");
+                None
+            }
+        }
+    }
+
+    /// Whether the frame on screen at `time`, showing `generation`, should be recorded.
+    fn is_due(&self, time: f64, generation: u64) -> bool {
+        if self.is_fixed_step {
+            self.last_generation != Some(generation)
+        } else {
+            time - self.last_capture >= GIF_FRAME_INTERVAL
+        }
+    }
+
+    /// Appends the frame drawn so far, shown for as long as it took to record, or for
+    /// [`FIXED_STEP_GIF_DELAY`] in fixed-step mode. Returns whether recording should continue.
+    fn capture(
+        &mut self,
+        d: &RaylibDrawHandle,
+        thread: &RaylibThread,
+        time: f64,
+        generation: u64,
+        width: i32,
+        height: i32,
+    ) -> bool {
+        let mut image = capture_screen(d, thread);
+        if image.width() != width || image.height() != height {
+            image.resize_nn(width, height);
+        }
+        let pixels: Vec<[u8; 4]> =
+            image.get_image_data().iter().map(|color| [color.r, color.g, color.b, color.a]).collect();
+        let delay = if self.is_fixed_step {
+            FIXED_STEP_GIF_DELAY
+        } else {
+            ((time - self.last_capture) * 100.0).round().clamp(2.0, u16::MAX as f64) as u16
+        };
+        self.last_capture = time;
+        self.last_generation = Some(generation);
+
+        if let Err(err) = self.encoder.add_frame(&pixels, delay) {
+            eprintln!("Failed to record {}: {err}", self.path);
+            return false;
+        }
+        self.encoder.frame_count() < GIF_MAX_FRAMES
+    }
+
+    fn finish(self) {
+        match self.encoder.finish() {
+            Ok(_) => println!("Saved {}", display_path(&self.path)),
+            Err(err) => eprintln!("Failed to write {}: {err}", self.path),
+        }
+    }
+}
+
+/// Numbered PNGs written to a directory of their own, one every `stride` generations, for
+/// assembling into a video elsewhere.
+struct FrameSequence {
+    dir: PathBuf,
+    stride: u64,
+    max_frames: usize,
+    /// Whether frames render the whole board rather than the view.
+    is_board: bool,
+    count: usize,
+    last_generation: Option<u64>,
+}
+
+impl FrameSequence {
+    fn start(args: &Args) -> Option<Self> {
+        let dir = PathBuf::from(format!("frames-{}", timestamp(SystemTime::now())));
+        if let Err(err) = fs::create_dir_all(&dir) {
+            eprintln!("Failed to start recording frames in {}: {err}", dir.display());
+            return None;
+        }
+        Some(Self {
+            dir,
+            stride: args.frame_stride,
+            max_frames: args.max_frames,
+            is_board: args.frames_board,
+            count: 0,
+            last_generation: None,
+        })
+    }
+
+    /// Whether the board at `generation` is at least a stride on from the last frame, either
+    /// way, as stepping back or resetting moves the generation backwards.
+    fn is_due(&self, generation: u64) -> bool {
+        self.last_generation.is_none_or(|last| generation.abs_diff(last) >= self.stride)
+    }
+
+    /// Writes the next numbered frame, from the screen or the whole board. Returns whether
+    /// recording should continue.
+    fn capture(&mut self, d: &RaylibDrawHandle, thread: &RaylibThread, life: &Life, theme: &Theme, scale: i32) -> bool {
+        self.last_generation = Some(life.generation());
+        let image = if self.is_board {
+            match render_cells(&life.live_cells(), scale, theme.background, theme.cell) {
+                Ok(image) => image,
+                // An empty board has no frame to show, so it is skipped rather than ending the recording.
+                Err(ExportError::EmptyBoard) => return true,
+                Err(err) => {
+                    eprintln!("Stopped recording frames: {err}");
+                    return false;
+                }
+            }
+        } else {
+            capture_screen(d, thread)
+        };
+        self.count += 1;
+        let path = self.dir.join(format!("frame_{:05}.png", self.count));
+        let written = image.export_image_to_memory(".png").map_err(|err| err.to_string()).and_then(|png| {
+            fs::write(&path, png).map_err(|err| err.to_string())
+        });
+        if let Err(err) = written {
+            eprintln!("Failed to write {}: {err}", path.display());
+            return false;
+        }
+        self.count < self.max_frames
+    }
+
+    fn finish(self) {
+        println!("Saved {} frames to {}", self.count, display_path(&self.dir.to_string_lossy()));
+    }
+}
+
+/// Reads back what has been drawn so far this frame.
+fn capture_screen(d: &RaylibDrawHandle, thread: &RaylibThread) -> Image {
+    // Draws are batched, so flush them into the framebuffer before reading it back.
+    // SAFETY: called between BeginDrawing and EndDrawing on the main thread.
+    unsafe { raylib::ffi::rlDrawRenderBatchActive() };
+    d.load_image_from_screen(thread)
+}
+
+/// Writes what has been drawn so far this frame to a timestamped PNG.
+fn save_screenshot(d: &RaylibDrawHandle, thread: &RaylibThread) {
+    let image = capture_screen(d, thread);
+    let path = format!("screenshot-{}.png", timestamp(SystemTime::now()));
+    match image.export_image_to_memory(".png") {
+        Ok(png) => save_file(&path, png),
+        Err(err) => eprintln!("Failed to encode {path}: {err}"),
+    }
+}
+
+fn save_file(path: &str, contents: impl AsRef<[u8]>) {
+    match fs::write(path, contents) {
+        Ok(()) => println!("Saved {}", display_path(path)),
+        Err(err) => eprintln!("Failed to write {path}: {err}"),
+    }
+}
 
-    survived_cells.iter()
-        .flat_map(|&cell| deltas.iter().map(move |&delta| cell + delta))
-        .counts()
+fn display_path(path: &str) -> String {
+    env::current_dir()
+        .map(|dir| dir.join(path).display().to_string())
+        .unwrap_or_else(|_| path.to_string())
 }
@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+use glam::{I64Vec2, IVec2, Vec2};
+use crate::life::bounding_box;
+
+/// How far a small pattern is magnified, in minimap pixels per cell.
+const MAX_PIXELS_PER_CELL: f64 = 4.0;
+
+/// A scaled-down view of every live cell, fitted to the pattern's bounding box.
+///
+/// The fit and the pixels to draw are only worked out by [`Minimap::refresh`], so a caller
+/// can redraw every frame and refresh now and then without touching every cell each time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Minimap {
+    size: IVec2,
+    /// The world cell drawn at the middle of the minimap.
+    centre: I64Vec2,
+    cells_per_pixel: f64,
+    pixels: Vec<IVec2>,
+}
+
+impl Minimap {
+    pub fn new(size: IVec2) -> Self {
+        Self { size, centre: I64Vec2::ZERO, cells_per_pixel: 1.0, pixels: Vec::new() }
+    }
+
+    pub fn size(&self) -> IVec2 {
+        self.size
+    }
+
+    /// Refits the minimap to the cells and records which pixels hold at least one of them.
+    pub fn refresh(&mut self, cells: &HashSet<I64Vec2>) {
+        self.pixels.clear();
+        let Some((min, max)) = bounding_box(cells) else {
+            return;
+        };
+        self.centre = min + (max - min) / 2;
+        let extent = (max - min).as_dvec2() + 1.0;
+        let fit = extent / self.size.as_dvec2();
+        self.cells_per_pixel = fit.max_element().max(1.0 / MAX_PIXELS_PER_CELL);
+
+        let mut seen = HashSet::new();
+        for &cell in cells {
+            let pixel = self.to_minimap(cell).floor().as_ivec2().clamp(IVec2::ZERO, self.size - 1);
+            if seen.insert(pixel) {
+                self.pixels.push(pixel);
+            }
+        }
+    }
+
+    /// The top-left corners of the pixels with live cells, as of the last refresh.
+    pub fn pixels(&self) -> &[IVec2] {
+        &self.pixels
+    }
+
+    /// How many pixels wide one cell is, at least one so single cells stay visible.
+    pub fn pixel_size(&self) -> i32 {
+        (1.0 / self.cells_per_pixel).floor().max(1.0) as i32
+    }
+
+    /// Where a world cell's top-left corner lands, in pixels from the minimap's corner.
+    pub fn to_minimap(&self, cell: I64Vec2) -> Vec2 {
+        let offset = cell.saturating_sub(self.centre).as_dvec2() / self.cells_per_pixel;
+        (offset + self.size.as_dvec2() / 2.0).as_vec2()
+    }
+
+    /// The world cell under a pixel of the minimap, the inverse of [`Minimap::to_minimap`].
+    pub fn to_world(&self, pixel: IVec2) -> I64Vec2 {
+        let offset = (pixel.as_dvec2() - self.size.as_dvec2() / 2.0) * self.cells_per_pixel;
+        self.centre.saturating_add(offset.floor().as_i64vec2())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_the_pattern_and_maps_back() {
+        let cells: HashSet<I64Vec2> = [I64Vec2::new(-500, 100), I64Vec2::new(499, 299), I64Vec2::new(0, 200)].into();
+        let mut minimap = Minimap::new(IVec2::new(100, 100));
+        minimap.refresh(&cells);
+        assert_eq!(minimap.pixels().len(), 3);
+        assert!(minimap.pixels().contains(&IVec2::new(50, 50)));
+        let inside = |pixel: &IVec2| pixel.cmpge(IVec2::ZERO).all() && pixel.cmplt(minimap.size()).all();
+        assert!(minimap.pixels().iter().all(inside));
+        assert_eq!(minimap.pixel_size(), 1);
+
+        let world = minimap.to_world(IVec2::new(50, 50));
+        assert!((world - I64Vec2::new(0, 200)).abs().max_element() < 10);
+        assert!((0.0..1.0).contains(&minimap.to_minimap(I64Vec2::new(-500, 200)).x));
+    }
+
+    #[test]
+    fn magnifies_small_patterns_up_to_a_limit() {
+        let mut minimap = Minimap::new(IVec2::new(100, 100));
+        minimap.refresh(&[I64Vec2::ZERO, I64Vec2::ONE].into());
+        assert_eq!(minimap.pixel_size(), MAX_PIXELS_PER_CELL as i32);
+        assert_eq!(minimap.pixels().len(), 2);
+
+        minimap.refresh(&HashSet::new());
+        assert!(minimap.pixels().is_empty());
+    }
+}
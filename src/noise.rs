@@ -0,0 +1,74 @@
+use glam::I64Vec2;
+use crate::rng::Rng;
+
+/// The chance of births and survivals that Shift+R switches on when `--birth-chance` and
+/// `--survival-chance` weren't given.
+pub const DEFAULT_NOISE_CHANCE: f64 = 0.95;
+
+/// Makes births and survivals the rule allows happen only by chance, for studying noisy
+/// automata. Each cell's roll depends only on the seed, the generation and where the cell is,
+/// so a run is the same every time from the same seed and board, however it is stepped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Noise {
+    /// The chance a dead cell the rule would bring to life is born.
+    pub birth: f64,
+    /// The chance a live cell the rule would keep alive survives.
+    pub survival: f64,
+    pub seed: u64,
+}
+
+impl Noise {
+    /// The noise of one generation, with its own rolls for every cell.
+    pub fn for_generation(&self, generation: u64) -> Noise {
+        let seed = Rng::seed_from_u64(self.seed ^ generation.wrapping_mul(0xD6E8_FEB8_6659_FD93)).next_u64();
+        Noise { seed, ..*self }
+    }
+
+    /// Whether the birth the rule allows at `cell` goes ahead.
+    pub fn allows_birth(&self, cell: I64Vec2) -> bool {
+        self.roll(cell, 0) < self.birth
+    }
+
+    /// Whether the survival the rule allows at `cell` goes ahead.
+    pub fn allows_survival(&self, cell: I64Vec2) -> bool {
+        self.roll(cell, 1) < self.survival
+    }
+
+    /// A value in `[0, 1)` for the cell, different for births and survivals.
+    fn roll(&self, cell: I64Vec2, salt: u64) -> f64 {
+        let key = (cell.x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (cell.y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+            ^ salt.wrapping_mul(0x1656_67B1_9E37_79F9);
+        Rng::seed_from_u64(self.seed ^ key).next_f64()
+    }
+}
+
+impl Default for Noise {
+    /// Certain births and survivals, which step just like the rule without noise.
+    fn default() -> Self {
+        Noise { birth: 1.0, survival: 1.0, seed: 0 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolls_are_reproducible_and_near_the_chance() {
+        let noise = Noise { birth: 0.25, survival: 0.75, seed: 9 };
+        let cells: Vec<I64Vec2> = (0..100).flat_map(|x| (0..100).map(move |y| I64Vec2::new(x, y))).collect();
+        let allowed = |noise: &Noise, allows: fn(&Noise, I64Vec2) -> bool| -> Vec<I64Vec2> {
+            cells.iter().copied().filter(|&cell| allows(noise, cell)).collect()
+        };
+        let births = allowed(&noise.for_generation(3), Noise::allows_birth);
+        assert!((2300..2700).contains(&births.len()), "{}", births.len());
+        assert!((7300..7700).contains(&allowed(&noise.for_generation(3), Noise::allows_survival).len()));
+        assert_eq!(allowed(&noise.for_generation(3), Noise::allows_birth), births);
+        assert_ne!(allowed(&noise.for_generation(4), Noise::allows_birth), births);
+
+        let certain = Noise::default().for_generation(5);
+        assert_eq!(allowed(&certain, Noise::allows_birth), cells);
+        assert_eq!(allowed(&certain, Noise::allows_survival), cells);
+    }
+}
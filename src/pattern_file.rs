@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::{fmt, fs, io};
+use glam::I64Vec2;
+use crate::lif::{parse_lif, LifError};
+use crate::plaintext::parse_cells;
+use crate::rle::{parse_rle, parse_rle_metadata, RleError, RleMetadata};
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    Rle(RleError),
+    Lif(LifError),
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "{err}"),
+            LoadError::Rle(err) => write!(f, "{err}"),
+            LoadError::Lif(err) => write!(f, "{err}"),
+            LoadError::UnsupportedFormat(ext) => {
+                write!(f, "unsupported pattern format {ext:?} (expected .rle, .cells or .lif)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> Self {
+        LoadError::Io(err)
+    }
+}
+
+impl From<RleError> for LoadError {
+    fn from(err: RleError) -> Self {
+        LoadError::Rle(err)
+    }
+}
+
+impl From<LifError> for LoadError {
+    fn from(err: LifError) -> Self {
+        LoadError::Lif(err)
+    }
+}
+
+/// Reads a pattern file, picking the parser from the file extension.
+pub fn load_pattern(path: &Path) -> Result<HashSet<I64Vec2>, LoadError> {
+    load_pattern_with_metadata(path).map(|(cells, _)| cells)
+}
+
+/// Like [`load_pattern`], but also returns what an RLE file records about its position,
+/// generation and view. Cells are moved to the recorded position, if there is one, so the
+/// recorded view frames them again. Other formats have no metadata.
+pub fn load_pattern_with_metadata(path: &Path) -> Result<(HashSet<I64Vec2>, RleMetadata), LoadError> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+
+    let parse: fn(&str) -> Result<HashSet<I64Vec2>, LoadError> = match extension.as_str() {
+        "rle" => |input| Ok(parse_rle(input)?),
+        "cells" => |input| Ok(parse_cells(input)),
+        "lif" | "life" => |input| Ok(parse_lif(input)?),
+        _ => return Err(LoadError::UnsupportedFormat(extension)),
+    };
+
+    let input = fs::read_to_string(path)?;
+    let cells = parse(&input)?;
+    if extension != "rle" {
+        return Ok((cells, RleMetadata::default()));
+    }
+    let metadata = parse_rle_metadata(&input);
+    let position = metadata.position.unwrap_or(I64Vec2::ZERO);
+    Ok((cells.into_iter().map(|cell| cell + position).collect(), metadata))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_unsupported_and_missing_files() {
+        assert!(matches!(
+            load_pattern(Path::new("pattern.txt")),
+            Err(LoadError::UnsupportedFormat(ext)) if ext == "txt",
+        ));
+        assert!(matches!(
+            load_pattern(Path::new("does/not/exist.rle")),
+            Err(LoadError::Io(_)),
+        ));
+    }
+}
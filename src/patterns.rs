@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+use glam::I64Vec2;
+use crate::rle::parse_rle;
+use crate::rng::Rng;
+
+const GLIDER: &str = "bo$2bo$3o!";
+const LIGHTWEIGHT_SPACESHIP: &str = "bo2bo$o4b$o3bo$4o!";
+const GOSPER_GLIDER_GUN: &str = "24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4bobo$\
+10bo5bo7bo$11bo3bo$12b2o!";
+const PULSAR: &str = "2b3o3b3o2b2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2b2$2b3o3b3o2b$o4bobo4bo$\
+o4bobo4bo$o4bobo4bo2$2b3o3b3o!";
+
+fn from_rle(rle: &str) -> Vec<I64Vec2> {
+    let mut cells: Vec<I64Vec2> = parse_rle(rle)
+        .expect("built-in patterns are valid RLE")
+        .into_iter()
+        .collect();
+    cells.sort_by_key(|cell| (cell.y, cell.x));
+    cells
+}
+
+pub fn glider() -> Vec<I64Vec2> {
+    from_rle(GLIDER)
+}
+
+pub fn lightweight_spaceship() -> Vec<I64Vec2> {
+    from_rle(LIGHTWEIGHT_SPACESHIP)
+}
+
+pub fn gosper_glider_gun() -> Vec<I64Vec2> {
+    from_rle(GOSPER_GLIDER_GUN)
+}
+
+pub fn pulsar() -> Vec<I64Vec2> {
+    from_rle(PULSAR)
+}
+
+/// Fills the half-open box `lower..upper` with live cells at the given density.
+pub fn random_soup(rng: &mut Rng, lower: I64Vec2, upper: I64Vec2, density: f64) -> HashSet<I64Vec2> {
+    (lower.y..upper.y)
+        .flat_map(|y| (lower.x..upper.x).map(move |x| I64Vec2::new(x, y)))
+        .filter(|_| rng.chance(density))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PalettePattern {
+    pub name: String,
+    pub cells: Vec<I64Vec2>,
+}
+
+/// An ordered list of named patterns that can be stamped onto the board.
+#[derive(Debug, Clone, Default)]
+pub struct PatternPalette {
+    patterns: Vec<PalettePattern>,
+}
+
+impl PatternPalette {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_builtins() -> Self {
+        let mut palette = Self::new();
+        palette.register("Glider", glider());
+        palette.register("Lightweight Spaceship", lightweight_spaceship());
+        palette.register("Gosper Glider Gun", gosper_glider_gun());
+        palette.register("Pulsar", pulsar());
+        palette
+    }
+
+    pub fn register(&mut self, name: impl Into<String>, cells: Vec<I64Vec2>) {
+        self.patterns.push(PalettePattern { name: name.into(), cells });
+    }
+
+    pub fn get(&self, index: usize) -> Option<&PalettePattern> {
+        self.patterns.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PalettePattern> {
+        self.patterns.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::life::Life;
+
+    fn run(cells: &[I64Vec2], generations: usize) -> Life {
+        let mut life = Life::from_cells(cells.iter().copied().collect());
+        for _ in 0..generations {
+            life.step();
+        }
+        life
+    }
+
+    #[test]
+    fn builtin_populations() {
+        assert_eq!(glider().len(), 5);
+        assert_eq!(lightweight_spaceship().len(), 9);
+        assert_eq!(gosper_glider_gun().len(), 36);
+        assert_eq!(pulsar().len(), 48);
+    }
+
+    #[test]
+    fn spaceships_translate() {
+        let glider_start = Life::from_cells(glider().into_iter().collect());
+        let glider_end = run(&glider(), 4);
+        let shifted = glider_start.live_cells().iter().map(|&c| c + I64Vec2::new(1, 1)).collect();
+        assert_eq!(glider_end, Life::from_cells(shifted));
+
+        let lwss_start = Life::from_cells(lightweight_spaceship().into_iter().collect());
+        let lwss_end = run(&lightweight_spaceship(), 4);
+        let shifted = lwss_start.live_cells().iter().map(|&c| c + I64Vec2::new(-2, 0)).collect();
+        assert_eq!(lwss_end, Life::from_cells(shifted));
+    }
+
+    #[test]
+    fn pulsar_has_period_three() {
+        let start = run(&pulsar(), 0);
+        assert_ne!(run(&pulsar(), 1), start);
+        assert_eq!(run(&pulsar(), 3), start);
+    }
+
+    #[test]
+    fn gun_emits_a_glider_every_thirty_generations() {
+        assert_eq!(run(&gosper_glider_gun(), 30).population(), 36 + 5);
+    }
+
+    #[test]
+    fn soup_respects_bounds_and_seed() {
+        let soup = random_soup(&mut Rng::seed_from_u64(9), I64Vec2::new(-5, -5), I64Vec2::new(5, 5), 0.5);
+        assert!(soup.iter().all(|c| (-5..5).contains(&c.x) && (-5..5).contains(&c.y)));
+        assert!(!soup.is_empty() && soup.len() < 100);
+        assert_eq!(soup, random_soup(&mut Rng::seed_from_u64(9), I64Vec2::new(-5, -5), I64Vec2::new(5, 5), 0.5));
+
+        let full = random_soup(&mut Rng::seed_from_u64(9), I64Vec2::ZERO, I64Vec2::new(4, 3), 1.0);
+        assert_eq!(full.len(), 12);
+    }
+
+    #[test]
+    fn palette_registers_in_order() {
+        let mut palette = PatternPalette::with_builtins();
+        assert_eq!(palette.len(), 4);
+        assert_eq!(palette.get(0).unwrap().name, "Glider");
+
+        palette.register("Dot", vec![I64Vec2::ZERO]);
+        assert_eq!(palette.get(4).unwrap().cells, vec![I64Vec2::ZERO]);
+        assert!(palette.get(5).is_none());
+    }
+}
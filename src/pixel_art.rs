@@ -0,0 +1,158 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use glam::{I64Vec2, IVec2};
+use raylib::prelude::{Color, Image};
+use crate::life::bounding_box;
+
+/// Pixels darker than this brightness become live cells unless `--image-threshold` says
+/// otherwise.
+pub const DEFAULT_IMAGE_THRESHOLD: u8 = 128;
+
+/// Larger images are shrunk to about this many pixels before conversion.
+pub const MAX_IMAGE_PIXELS: i64 = 1_000_000;
+
+/// Pixels per cell in a board export unless `--export-scale` says otherwise.
+pub const DEFAULT_EXPORT_SCALE: i32 = 4;
+
+/// The largest board export, in pixels, so a sprawling pattern can't exhaust memory.
+pub const MAX_EXPORT_PIXELS: u64 = 25_000_000;
+
+/// The cells of an image and the size it was converted at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageCells {
+    pub cells: HashSet<I64Vec2>,
+    /// The image's own size in pixels.
+    pub size: IVec2,
+    /// The size it was downsampled to, which is `size` unless it was too large.
+    pub fitted: IVec2,
+}
+
+impl ImageCells {
+    pub fn is_downsampled(&self) -> bool {
+        self.fitted != self.size
+    }
+}
+
+/// Loads an image as one cell per pixel, with dark opaque pixels alive and the top-left pixel
+/// at the origin. Images over [`MAX_IMAGE_PIXELS`] are downsampled first, keeping their shape.
+pub fn load_image_cells(path: &Path, threshold: u8) -> Result<ImageCells, String> {
+    let mut image = Image::load_image(&path.to_string_lossy()).map_err(|err| err.to_string())?;
+    let size = IVec2::new(image.width(), image.height());
+    let fitted = fit_to_limit(size, MAX_IMAGE_PIXELS);
+    if fitted != size {
+        // Nearest neighbour keeps hard pixel-art edges instead of blurring them to grey.
+        image.resize_nn(fitted.x, fitted.y);
+    }
+    let cells = cells_from_pixels(image.width(), &image.get_image_data(), threshold);
+    Ok(ImageCells { cells, size, fitted })
+}
+
+/// The largest size with the same aspect ratio and at most `max_pixels` pixels, or the size
+/// itself if it already fits. Neither side shrinks below one pixel.
+pub fn fit_to_limit(size: IVec2, max_pixels: i64) -> IVec2 {
+    let pixels = size.x as i64 * size.y as i64;
+    if pixels <= max_pixels {
+        return size;
+    }
+    let scale = (max_pixels as f64 / pixels as f64).sqrt();
+    let side = |length: i32| ((length as f64 * scale) as i64).max(1);
+    // A very thin image has its short side pinned at one pixel, so the long side is capped to
+    // stay within the limit.
+    let height = side(size.y).min(max_pixels);
+    let width = side(size.x).min(max_pixels / height);
+    IVec2::new(width as i32, height.min(max_pixels / width) as i32)
+}
+
+/// The cells for the pixels, given in rows of `width`, that are darker than `threshold`.
+/// Mostly transparent pixels count as background.
+pub fn cells_from_pixels(width: i32, pixels: &[Color], threshold: u8) -> HashSet<I64Vec2> {
+    pixels
+        .iter()
+        .enumerate()
+        .filter(|(_, color)| color.a >= 128 && brightness(**color) < threshold)
+        .map(|(index, _)| I64Vec2::new(index as i64 % width as i64, index as i64 / width as i64))
+        .collect()
+}
+
+/// Draws every cell as a `scale`x`scale` block of the `cell` colour over the `background`,
+/// covering the cells' bounding box whatever part of the board is in view.
+pub fn render_cells(
+    cells: &HashSet<I64Vec2>,
+    scale: i32,
+    background: Color,
+    cell: Color,
+) -> Result<Image, ExportError> {
+    let (min, max) = bounding_box(cells).ok_or(ExportError::EmptyBoard)?;
+    let side = |min: i64, max: i64| max.abs_diff(min).saturating_add(1).saturating_mul(scale as u64);
+    let (width, height) = (side(min.x, max.x), side(min.y, max.y));
+    if width.saturating_mul(height) > MAX_EXPORT_PIXELS {
+        return Err(ExportError::TooLarge { width, height });
+    }
+
+    let mut image = Image::gen_image_color(width as i32, height as i32, background);
+    for &live in cells {
+        let corner = ((live - min) * scale as i64).as_ivec2();
+        image.draw_rectangle(corner.x, corner.y, scale, scale, cell);
+    }
+    Ok(image)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportError {
+    EmptyBoard,
+    TooLarge { width: u64, height: u64 },
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExportError::EmptyBoard => write!(f, "the board is empty"),
+            ExportError::TooLarge { width, height } => {
+                write!(f, "a {width}x{height} image is over the {MAX_EXPORT_PIXELS} pixel limit")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+/// Perceived brightness, weighting green most as the eye does.
+fn brightness(color: Color) -> u8 {
+    ((299 * color.r as u32 + 587 * color.g as u32 + 114 * color.b as u32) / 1000) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dark_opaque_pixels_become_cells() {
+        let pixels = [Color::BLACK, Color::WHITE, Color::DARKBLUE, Color::new(0, 0, 0, 0), Color::GRAY, Color::RED];
+        let cells = cells_from_pixels(3, &pixels, DEFAULT_IMAGE_THRESHOLD);
+        assert_eq!(cells, [I64Vec2::new(0, 0), I64Vec2::new(2, 0), I64Vec2::new(2, 1)].into());
+        assert_eq!(cells_from_pixels(3, &pixels, 0), HashSet::new());
+    }
+
+    #[test]
+    fn renders_the_whole_pattern_at_scale() {
+        let cells: HashSet<I64Vec2> = [I64Vec2::new(-10, 5), I64Vec2::new(-8, 6)].into();
+        let mut image = render_cells(&cells, 3, Color::WHITE, Color::BLACK).unwrap();
+        assert_eq!((image.width(), image.height()), (9, 6));
+        assert_eq!(image.get_color(2, 2), Color::BLACK);
+        assert_eq!(image.get_color(3, 2), Color::WHITE);
+        assert_eq!(image.get_color(8, 5), Color::BLACK);
+
+        assert_eq!(render_cells(&HashSet::new(), 3, Color::WHITE, Color::BLACK).err(), Some(ExportError::EmptyBoard));
+        let far: HashSet<I64Vec2> = [I64Vec2::ZERO, I64Vec2::splat(10_000)].into();
+        assert!(matches!(render_cells(&far, 1, Color::WHITE, Color::BLACK), Err(ExportError::TooLarge { .. })));
+    }
+
+    #[test]
+    fn large_images_keep_their_shape() {
+        assert_eq!(fit_to_limit(IVec2::new(640, 480), MAX_IMAGE_PIXELS), IVec2::new(640, 480));
+        let fitted = fit_to_limit(IVec2::new(4000, 2000), MAX_IMAGE_PIXELS);
+        assert_eq!(fitted, IVec2::new(1414, 707));
+        assert_eq!(fit_to_limit(IVec2::new(100_000, 1), 100), IVec2::new(100, 1));
+    }
+}
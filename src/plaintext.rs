@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+use glam::I64Vec2;
+use crate::life::bounding_box;
+
+/// Parses a pattern in the plaintext `.cells` format.
+///
+/// Lines starting with `!` are comments. On data lines `O` is a live cell and every other
+/// character is dead. The first data line is row 0 and cells are anchored at (0, 0).
+pub fn parse_cells(input: &str) -> HashSet<I64Vec2> {
+    input
+        .lines()
+        .filter(|line| !line.starts_with('!'))
+        .enumerate()
+        .flat_map(|(y, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|&(_, ch)| ch == 'O')
+                .map(move |(x, _)| I64Vec2::new(x as i64, y as i64))
+        })
+        .collect()
+}
+
+/// Encodes the live cells as plaintext, anchored at the top-left of their bounding box.
+pub fn to_cells(cells: &HashSet<I64Vec2>) -> String {
+    let mut output = String::from("!Name: export\n");
+    let Some((min, max)) = bounding_box(cells) else {
+        return output;
+    };
+
+    for y in min.y..=max.y {
+        let row_end = (min.x..=max.x)
+            .rev()
+            .find(|&x| cells.contains(&I64Vec2::new(x, y)));
+        if let Some(row_end) = row_end {
+            output.extend((min.x..=row_end).map(|x| {
+                if cells.contains(&I64Vec2::new(x, y)) { 'O' } else { '.' }
+            }));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glider() -> HashSet<I64Vec2> {
+        [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+            .iter()
+            .map(|&(x, y)| I64Vec2::new(x, y))
+            .collect()
+    }
+
+    #[test]
+    fn parses_glider_with_comments() {
+        let input = "!Name: Glider\n!\n.O.\n..O\nOOO\n";
+        assert_eq!(parse_cells(input), glider());
+    }
+
+    #[test]
+    fn treats_unknown_characters_as_dead() {
+        let input = "!Name: Noise\n.Ox\n*-O\nOOO";
+        assert_eq!(parse_cells(input), glider());
+    }
+
+    #[test]
+    fn keeps_blank_rows() {
+        let input = "O\n\n\nO";
+        let expected = [I64Vec2::new(0, 0), I64Vec2::new(0, 3)].into_iter().collect();
+        assert_eq!(parse_cells(input), expected);
+    }
+
+    #[test]
+    fn round_trips_translated_patterns() {
+        let shifted: HashSet<I64Vec2> = glider().iter().map(|&c| c + I64Vec2::new(9, -4)).collect();
+        let output = to_cells(&shifted);
+
+        assert_eq!(output, "!Name: export\n.O\n..O\nOOO\n");
+        assert_eq!(parse_cells(&output), glider());
+    }
+
+    #[test]
+    fn encodes_empty_board() {
+        assert_eq!(parse_cells(&to_cells(&HashSet::new())), HashSet::new());
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::VecDeque;
+use glam::Vec2;
+
+/// The population of the most recent generations, one sample each, for graphing growth. A
+/// HashLife jump lands in one go, so it leaves a single sample for the generations it skips.
+#[derive(Debug, Clone)]
+pub struct PopulationHistory {
+    samples: VecDeque<(u64, u64)>,
+    capacity: usize,
+}
+
+impl PopulationHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self { samples: VecDeque::with_capacity(capacity), capacity }
+    }
+
+    /// Records the population at `generation`, replacing any sample already taken for it. Going
+    /// back to an earlier generation, by stepping back or clearing the board, forgets the samples
+    /// after it.
+    pub fn record(&mut self, generation: u64, population: u64) {
+        while self.samples.back().is_some_and(|&(last, _)| last >= generation) {
+            self.samples.pop_back();
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        if self.capacity > 0 {
+            self.samples.push_back((generation, population));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// The largest population among the kept samples.
+    pub fn max(&self) -> u64 {
+        self.samples.iter().map(|&(_, population)| population).max().unwrap_or(0)
+    }
+
+    /// Lays the samples out as a line graph in a `size` box with the origin at the top left.
+    /// Samples fill the width from the left at one step per slot, and the vertical axis scales
+    /// so the largest kept population touches the top.
+    pub fn points(&self, size: Vec2) -> Vec<Vec2> {
+        let step = size.x / self.capacity.saturating_sub(1).max(1) as f32;
+        let max = self.max().max(1) as f32;
+        self.samples
+            .iter()
+            .enumerate()
+            .map(|(index, &(_, population))| Vec2::new(index as f32 * step, size.y * (1.0 - population as f32 / max)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_latest_samples() {
+        let mut history = PopulationHistory::new(3);
+        for generation in 0..5 {
+            history.record(generation, generation * 10);
+        }
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.max(), 40);
+
+        history.record(4, 7);
+        assert_eq!(history.len(), 3);
+        assert_eq!(history.max(), 30);
+    }
+
+    #[test]
+    fn stepping_back_drops_later_samples() {
+        let mut history = PopulationHistory::new(10);
+        for generation in 0..5 {
+            history.record(generation, 100 - generation);
+        }
+        history.record(2, 50);
+        assert_eq!(history.len(), 3);
+
+        history.record(0, 0);
+        assert_eq!(history.len(), 1);
+        assert!(PopulationHistory::new(0).points(Vec2::ONE).is_empty());
+    }
+
+    #[test]
+    fn scales_points_to_the_box() {
+        let mut history = PopulationHistory::new(5);
+        for (generation, population) in [(0, 0), (1, 5), (2, 10)] {
+            history.record(generation, population);
+        }
+        let points = history.points(Vec2::new(200.0, 50.0));
+        assert_eq!(points, [Vec2::new(0.0, 50.0), Vec2::new(50.0, 25.0), Vec2::new(100.0, 0.0)]);
+    }
+}
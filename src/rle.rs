@@ -0,0 +1,337 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use glam::I64Vec2;
+use crate::life::bounding_box;
+use crate::rule::Rule;
+
+const MAX_LINE_LENGTH: usize = 70;
+const MAX_RUN_COUNT: i64 = i32::MAX as i64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RleError {
+    InvalidHeader(String),
+    UnexpectedChar { ch: char, line: usize },
+    InvalidRunCount { line: usize },
+}
+
+impl fmt::Display for RleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RleError::InvalidHeader(header) => write!(f, "invalid RLE header: {header:?}"),
+            RleError::UnexpectedChar { ch, line } => write!(f, "unexpected character {ch:?} on line {line}"),
+            RleError::InvalidRunCount { line } => write!(f, "invalid run count on line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for RleError {}
+
+/// Parses a pattern in Golly's run-length encoded format.
+///
+/// The top-left of the pattern is placed at (0, 0). Comment lines (`#`) are skipped and the
+/// `x = .., y = ..` header is validated but otherwise only informative. Runs may wrap across
+/// lines, and a missing terminating `!` is tolerated.
+pub fn parse_rle(input: &str) -> Result<HashSet<I64Vec2>, RleError> {
+    let mut cells = HashSet::new();
+    let mut position = I64Vec2::ZERO;
+    let mut run: Option<i64> = None;
+    let mut seen_header = false;
+
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if !seen_header && line.starts_with('x') {
+            parse_header(line)?;
+            seen_header = true;
+            continue;
+        }
+        seen_header = true;
+
+        for ch in line.chars() {
+            match ch {
+                '0'..='9' => {
+                    let digit = ch.to_digit(10).unwrap() as i64;
+                    let count = run
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|count| count.checked_add(digit))
+                        .filter(|&count| count <= MAX_RUN_COUNT)
+                        .ok_or(RleError::InvalidRunCount { line: line_number })?;
+                    run = Some(count);
+                }
+                'b' => {
+                    position.x += run.take().unwrap_or(1);
+                }
+                'o' => {
+                    for _ in 0..run.take().unwrap_or(1) {
+                        cells.insert(position);
+                        position.x += 1;
+                    }
+                }
+                '$' => {
+                    position.y += run.take().unwrap_or(1);
+                    position.x = 0;
+                }
+                '!' => return Ok(cells),
+                c if c.is_whitespace() => {}
+                c => return Err(RleError::UnexpectedChar { ch: c, line: line_number }),
+            }
+        }
+    }
+
+    Ok(cells)
+}
+
+/// What an RLE file's comment lines say about where the pattern was and how it was viewed:
+/// Golly's `#CXRLE Pos=x,y Gen=n` line and a `#VIEW x,y,cell_size` line for the camera. Other
+/// tools skip both as comments.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RleMetadata {
+    /// Where the pattern's top-left corner was on the board.
+    pub position: Option<I64Vec2>,
+    pub generation: Option<u64>,
+    /// The camera origin and cell size.
+    pub view: Option<(I64Vec2, i32)>,
+}
+
+/// Reads the metadata from an RLE file's comment lines. Malformed or unknown lines are
+/// ignored, so a file that doesn't parse here still loads.
+pub fn parse_rle_metadata(input: &str) -> RleMetadata {
+    let mut metadata = RleMetadata::default();
+    let pair = |text: &str| {
+        let (x, y) = text.split_once(',')?;
+        Some(I64Vec2::new(x.trim().parse().ok()?, y.trim().parse().ok()?))
+    };
+
+    for line in input.lines().map(str::trim).take_while(|line| line.is_empty() || line.starts_with('#')) {
+        if let Some(fields) = line.strip_prefix("#CXRLE") {
+            for (key, value) in fields.split_whitespace().filter_map(|field| field.split_once('=')) {
+                match key {
+                    "Pos" => metadata.position = pair(value).or(metadata.position),
+                    "Gen" => metadata.generation = value.parse().ok().or(metadata.generation),
+                    _ => {}
+                }
+            }
+        } else if let Some(view) = line.strip_prefix("#VIEW") {
+            let (origin, cell_size) = view.trim().rsplit_once(',').unwrap_or_default();
+            metadata.view = pair(origin).zip(cell_size.parse().ok()).or(metadata.view);
+        }
+    }
+    metadata
+}
+
+/// Encodes the live cells as RLE, anchored at the top-left of their bounding box.
+pub fn to_rle(cells: &HashSet<I64Vec2>) -> String {
+    to_rle_with_rule(cells, &Rule::CONWAY)
+}
+
+/// Like [`to_rle`], but names `rule` in the header so Golly runs the pattern under it.
+pub fn to_rle_with_rule(cells: &HashSet<I64Vec2>, rule: &Rule) -> String {
+    let Some((min, max)) = bounding_box(cells) else {
+        return format!("x = 0, y = 0, rule = {rule}\n!\n");
+    };
+    let size = max - min + I64Vec2::ONE;
+
+    let mut rows: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+    for cell in cells {
+        rows.entry(cell.y - min.y).or_default().push(cell.x - min.x);
+    }
+
+    let mut tokens = Vec::new();
+    let mut previous_row = 0;
+    for (y, mut xs) in rows {
+        if y > previous_row {
+            tokens.push(run_token(y - previous_row, '$'));
+        }
+        previous_row = y;
+
+        xs.sort_unstable();
+        let mut x = 0;
+        for chunk in xs.chunk_by(|a, b| b - a == 1) {
+            let start = chunk[0];
+            if start > x {
+                tokens.push(run_token(start - x, 'b'));
+            }
+            tokens.push(run_token(chunk.len() as i64, 'o'));
+            x = start + chunk.len() as i64;
+        }
+    }
+    tokens.push("!".to_string());
+
+    let mut output = format!("x = {}, y = {}, rule = {rule}\n", size.x, size.y);
+    let mut line_length = 0;
+    for token in tokens {
+        if line_length + token.len() > MAX_LINE_LENGTH {
+            output.push('\n');
+            line_length = 0;
+        }
+        line_length += token.len();
+        output.push_str(&token);
+    }
+    output.push('\n');
+    output
+}
+
+/// Like [`to_rle_with_rule`], but led by [`RleMetadata`] lines recording where the pattern is,
+/// its generation and the camera, so loading it puts everything back as it was.
+pub fn to_rle_with_view(cells: &HashSet<I64Vec2>, rule: &Rule, generation: u64, view: (I64Vec2, i32)) -> String {
+    let (origin, cell_size) = view;
+    let position = bounding_box(cells).map_or(I64Vec2::ZERO, |(min, _)| min);
+    format!(
+        "#CXRLE Pos={},{} Gen={generation}\n#VIEW {},{},{cell_size}\n{}",
+        position.x,
+        position.y,
+        origin.x,
+        origin.y,
+        to_rle_with_rule(cells, rule)
+    )
+}
+
+fn run_token(count: i64, tag: char) -> String {
+    if count == 1 {
+        tag.to_string()
+    } else {
+        format!("{count}{tag}")
+    }
+}
+
+fn parse_header(line: &str) -> Result<(), RleError> {
+    let invalid = || RleError::InvalidHeader(line.to_string());
+
+    for field in line.split(',') {
+        let (key, value) = field.split_once('=').ok_or_else(invalid)?;
+        match key.trim() {
+            "x" | "y" => {
+                value.trim().parse::<u32>().map_err(|_| invalid())?;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(coords: &[(i64, i64)]) -> HashSet<I64Vec2> {
+        coords.iter().map(|&(x, y)| I64Vec2::new(x, y)).collect()
+    }
+
+    fn glider() -> HashSet<I64Vec2> {
+        cells(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)])
+    }
+
+    #[test]
+    fn parses_glider() {
+        let input = "#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!\n";
+        assert_eq!(parse_rle(input), Ok(glider()));
+    }
+
+    #[test]
+    fn parses_runs_wrapped_across_lines() {
+        let input = "x = 3, y = 3\nbo\nb$2\nbo$\n3o!";
+        assert_eq!(parse_rle(input), Ok(glider()));
+
+        let input = "x = 12, y = 1\n1\n2o!";
+        assert_eq!(parse_rle(input), Ok((0..12).map(|x| I64Vec2::new(x, 0)).collect()));
+    }
+
+    #[test]
+    fn tolerates_trailing_whitespace_and_missing_terminator() {
+        let input = "x = 3, y = 3   \n  bob$2bo$3o  \n\n";
+        assert_eq!(parse_rle(input), Ok(glider()));
+    }
+
+    #[test]
+    fn skips_blank_row_runs() {
+        let input = "x = 1, y = 4\no3$o!";
+        assert_eq!(parse_rle(input), Ok(cells(&[(0, 0), (0, 3)])));
+    }
+
+    #[test]
+    fn ignores_data_after_terminator() {
+        let input = "x = 1, y = 1\no!\nthis is not rle";
+        assert_eq!(parse_rle(input), Ok(cells(&[(0, 0)])));
+    }
+
+    #[test]
+    fn encodes_glider() {
+        assert_eq!(to_rle(&glider()), "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n");
+    }
+
+    #[test]
+    fn names_the_rule_in_the_header() {
+        let highlife = Rule::parse("B36/S23").unwrap();
+        assert_eq!(to_rle_with_rule(&glider(), &highlife), "x = 3, y = 3, rule = B36/S23\nbo$2bo$3o!\n");
+        assert_eq!(to_rle_with_rule(&HashSet::new(), &highlife), "x = 0, y = 0, rule = B36/S23\n!\n");
+    }
+
+    #[test]
+    fn round_trips_the_view_in_comments() {
+        let shifted: HashSet<I64Vec2> = glider().iter().map(|&c| c + I64Vec2::new(-40, 17)).collect();
+        let output = to_rle_with_view(&shifted, &Rule::CONWAY, 12, (I64Vec2::new(35, -10), 8));
+        assert!(output.starts_with("#CXRLE Pos=-40,17 Gen=12\n#VIEW 35,-10,8\nx = 3"), "{output}");
+        assert_eq!(parse_rle(&output), Ok(glider()));
+
+        let metadata = parse_rle_metadata(&output);
+        assert_eq!(metadata.position, Some(I64Vec2::new(-40, 17)));
+        assert_eq!(metadata.generation, Some(12));
+        assert_eq!(metadata.view, Some((I64Vec2::new(35, -10), 8)));
+    }
+
+    #[test]
+    fn ignores_unknown_and_malformed_metadata() {
+        let input = "#N Glider\n#CXRLE Pos=a,2 Gen=7 Foo=1\n#VIEW 1,2\n#C VIEW 1,2,3\nx = 3, y = 3\nbo$2bo$3o!\n";
+        assert_eq!(parse_rle_metadata(input), RleMetadata { generation: Some(7), ..RleMetadata::default() });
+        assert_eq!(parse_rle_metadata("x = 1, y = 1\no!\n#VIEW 1,2,3"), RleMetadata::default());
+        assert_eq!(parse_rle(input), Ok(glider()));
+    }
+
+    #[test]
+    fn encodes_empty_board() {
+        let output = to_rle(&HashSet::new());
+        assert_eq!(output, "x = 0, y = 0, rule = B3/S23\n!\n");
+        assert_eq!(parse_rle(&output), Ok(HashSet::new()));
+    }
+
+    #[test]
+    fn collapses_long_runs() {
+        let mut line: HashSet<I64Vec2> = (0..120).map(|x| I64Vec2::new(x, 0)).collect();
+        line.insert(I64Vec2::new(130, 5));
+        assert_eq!(to_rle(&line), "x = 131, y = 6, rule = B3/S23\n120o5$130bo!\n");
+    }
+
+    #[test]
+    fn round_trips_translated_patterns() {
+        let shifted: HashSet<I64Vec2> = glider().iter().map(|&c| c + I64Vec2::new(-40, 17)).collect();
+        assert_eq!(parse_rle(&to_rle(&shifted)), Ok(glider()));
+    }
+
+    #[test]
+    fn wraps_long_lines() {
+        let checkerboard: HashSet<I64Vec2> = (0..100).map(|x| I64Vec2::new(x * 2, 0)).collect();
+        let output = to_rle(&checkerboard);
+
+        assert!(output.lines().all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert_eq!(parse_rle(&output), Ok(checkerboard));
+    }
+
+    #[test]
+    fn rejects_invalid_input() {
+        assert_eq!(
+            parse_rle("x = 1, y = 1\noz!"),
+            Err(RleError::UnexpectedChar { ch: 'z', line: 2 }),
+        );
+        assert!(matches!(parse_rle("x = a, y = 1\no!"), Err(RleError::InvalidHeader(_))));
+        assert_eq!(
+            parse_rle("99999999999o!"),
+            Err(RleError::InvalidRunCount { line: 1 }),
+        );
+    }
+}
@@ -0,0 +1,674 @@
+use std::fmt;
+use std::ops::Index;
+use std::str::FromStr;
+use itertools::Itertools;
+use crate::kernel::{Kernel, Weights};
+
+/// The furthest a Larger than Life neighbourhood may reach, which keeps every count within
+/// what [`Counts`] holds.
+pub const MAX_RANGE: u8 = 7;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleError {
+    MissingPart(char),
+    DuplicatePart(char),
+    InvalidDigit(char),
+    UnexpectedChar(char),
+    BirthOnZero,
+    InvalidStateCount(String),
+    /// A count higher than the 4 neighbours a von Neumann neighbourhood has.
+    BeyondVonNeumann(char),
+    InvalidRange(String),
+    /// Larger than Life counts that aren't a list of `min..max` spans and single counts.
+    InvalidSpan(String),
+    /// A count higher than a Larger than Life neighbourhood has cells.
+    BeyondRange { count: usize, max: usize },
+    InvalidWeights(String),
+    /// Weights covering a different range from the rule's.
+    WeightsRange { weights: u8, range: u8 },
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::MissingPart(part) => write!(f, "rule is missing its {part} part (expected e.g. B3/S23)"),
+            RuleError::DuplicatePart(part) => write!(f, "rule has more than one {part} part"),
+            RuleError::InvalidDigit(digit) => write!(f, "neighbour count {digit:?} must be between 0 and 8"),
+            RuleError::UnexpectedChar(ch) => write!(f, "unexpected character {ch:?} in rule (expected e.g. B3/S23)"),
+            RuleError::BirthOnZero => write!(f, "B0 rules are not supported on an unbounded board"),
+            RuleError::InvalidStateCount(count) => write!(f, "state count {count:?} must be between 2 and 255"),
+            RuleError::BeyondVonNeumann(digit) => {
+                write!(f, "neighbour count {digit:?} must be between 0 and 4 in a von Neumann neighbourhood")
+            }
+            RuleError::InvalidRange(range) => write!(f, "range {range:?} must be between 1 and {MAX_RANGE}"),
+            RuleError::InvalidSpan(span) => write!(f, "neighbour counts {span:?} must be written as min..max"),
+            RuleError::BeyondRange { count, max } => {
+                write!(f, "neighbour count {count} is more than the {max} cells in the neighbourhood")
+            }
+            RuleError::InvalidWeights(weights) => {
+                write!(f, "weights {weights:?} must be one hex digit per cell of a square around the cell")
+            }
+            RuleError::WeightsRange { weights, range } => {
+                write!(f, "weights reaching {weights} cells out don't fit a rule of range {range}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RuleError {}
+
+/// Which cells count as a cell's neighbours.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Neighbourhood {
+    /// The eight cells around it, diagonals included.
+    #[default]
+    Moore,
+    /// The four orthogonally adjacent cells.
+    VonNeumann,
+    /// The cells in the box around it, each counting as much as its weight.
+    Weighted(Weights),
+}
+
+impl Neighbourhood {
+    /// The most live neighbours a cell can have when the neighbourhood reaches `range` cells
+    /// out: a square for Moore and a diamond for von Neumann. Weighted neighbourhoods can reach
+    /// the sum of their weights.
+    pub fn max_count(self, range: u8) -> usize {
+        let range = range as usize;
+        match self {
+            Neighbourhood::Moore => (2 * range + 1).pow(2) - 1,
+            Neighbourhood::VonNeumann => 2 * range * (range + 1),
+            Neighbourhood::Weighted(weights) => weights.total(),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Neighbourhood::Moore => "Moore",
+            Neighbourhood::VonNeumann => "von Neumann",
+            Neighbourhood::Weighted(_) => "weighted",
+        }
+    }
+}
+
+/// A set of neighbour counts from 0 to 255, indexed like an array of flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Counts([u64; 4]);
+
+impl Counts {
+    pub const EMPTY: Counts = Counts([0; 4]);
+
+    const fn from_flags(flags: [bool; 9]) -> Counts {
+        let mut bits = 0;
+        let mut count = 0;
+        while count < flags.len() {
+            if flags[count] {
+                bits |= 1 << count;
+            }
+            count += 1;
+        }
+        Counts([bits, 0, 0, 0])
+    }
+
+    /// Parses Larger than Life counts: a comma-separated list of `min..max` spans and single
+    /// counts, like `2..3,5`. An empty list has no counts at all.
+    pub fn parse_spans(text: &str) -> Option<Counts> {
+        let mut counts = Counts::EMPTY;
+        for span in text.split(',').filter(|_| !text.is_empty()) {
+            let (min, max) = span.trim().split_once("..").unwrap_or((span.trim(), span.trim()));
+            let (min, max): (usize, usize) = (min.parse().ok()?, max.parse().ok()?);
+            if min > max || max > 255 {
+                return None;
+            }
+            for count in min..=max {
+                counts.insert(count);
+            }
+        }
+        Some(counts)
+    }
+
+    /// The runs of consecutive counts, as `(min, max)` pairs.
+    fn spans(&self) -> Vec<(usize, usize)> {
+        let mut spans: Vec<(usize, usize)> = Vec::new();
+        for count in self.iter() {
+            match spans.last_mut() {
+                Some((_, max)) if *max + 1 == count => *max = count,
+                _ => spans.push((count, count)),
+            }
+        }
+        spans
+    }
+
+    pub fn contains(&self, count: usize) -> bool {
+        count < 256 && self.0[count / 64] >> (count % 64) & 1 == 1
+    }
+
+    pub fn insert(&mut self, count: usize) {
+        if count < 256 {
+            self.0[count / 64] |= 1 << (count % 64);
+        }
+    }
+
+    pub fn remove(&mut self, count: usize) {
+        if count < 256 {
+            self.0[count / 64] &= !(1 << (count % 64));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Counts::EMPTY
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..256).filter(|&count| self.contains(count))
+    }
+
+    /// Every count moved up by `offset`, or down for a negative one, dropping those pushed
+    /// out of range.
+    fn shifted(&self, offset: isize) -> Counts {
+        let mut shifted = Counts::EMPTY;
+        for count in self.iter().filter_map(|count| count.checked_add_signed(offset)) {
+            shifted.insert(count);
+        }
+        shifted
+    }
+}
+
+impl Index<usize> for Counts {
+    type Output = bool;
+
+    fn index(&self, count: usize) -> &bool {
+        if self.contains(count) { &true } else { &false }
+    }
+}
+
+/// A birth/survival rule indexed by live neighbour count, either Life-like or, with a range
+/// over 1, Larger than Life.
+///
+/// `states` is the Generations state count: cells that fail survival decay through
+/// `states - 2` intermediate states before dying. Plain Life rules have 2 states.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule {
+    pub birth: Counts,
+    /// Survival counts, which never include the cell itself.
+    pub survival: Counts,
+    pub states: u8,
+    pub neighbourhood: Neighbourhood,
+    /// How many cells out the neighbourhood reaches: 1 for Life-like rules.
+    pub range: u8,
+    /// Whether the notation counts the cell itself towards its survival, as Larger than Life
+    /// rules often do. It only changes how survival is written.
+    pub middle: bool,
+}
+
+impl Rule {
+    pub const CONWAY: Rule = Rule {
+        birth: Counts::from_flags([false, false, false, true, false, false, false, false, false]),
+        survival: Counts::from_flags([false, false, true, true, false, false, false, false, false]),
+        states: 2,
+        neighbourhood: Neighbourhood::Moore,
+        range: 1,
+        middle: false,
+    };
+
+    /// Parses a rule in `B3/S23` notation, optionally followed by a Generations state count
+    /// as in `B2/S/C3`. The parts may appear in any order and the prefixes are
+    /// case-insensitive. A trailing `V`, as in `B1/S1V`, counts neighbours in the von Neumann
+    /// neighbourhood instead of the Moore one.
+    ///
+    /// Larger than Life rules are written as in Golly, like `R5,C0,M1,S34..58,B34..45,NM`
+    /// for Bosco's Rule.
+    pub fn parse(text: &str) -> Result<Self, RuleError> {
+        let mut birth = None;
+        let mut survival = None;
+        let mut states = None;
+
+        let text = text.trim();
+        if text.starts_with(['R', 'r']) {
+            return parse_larger_than_life(text);
+        }
+        let (text, neighbourhood) = match text.strip_suffix(['V', 'v']) {
+            Some(text) => (text, Neighbourhood::VonNeumann),
+            None => (text, Neighbourhood::Moore),
+        };
+
+        for part in text.split('/') {
+            let mut chars = part.chars();
+            let name = match chars.next().map(|ch| ch.to_ascii_uppercase()) {
+                Some(name @ ('B' | 'S' | 'C')) => name,
+                Some(ch) => return Err(RuleError::UnexpectedChar(ch)),
+                None => return Err(RuleError::UnexpectedChar('/')),
+            };
+            let duplicate = match name {
+                'B' => birth.replace(parse_counts(chars)?).is_some(),
+                'S' => survival.replace(parse_counts(chars)?).is_some(),
+                _ => states.replace(parse_state_count(chars.as_str())?).is_some(),
+            };
+            if duplicate {
+                return Err(RuleError::DuplicatePart(name));
+            }
+        }
+
+        let birth = birth.ok_or(RuleError::MissingPart('B'))?;
+        let survival = survival.ok_or(RuleError::MissingPart('S'))?;
+        Rule { birth, survival, states: states.unwrap_or(2), neighbourhood, range: 1, middle: false }.checked()
+    }
+
+    /// Returns the rule if it can run: a range from 1 to [`MAX_RANGE`] matching any weights, no
+    /// births on zero neighbours and no counts beyond the neighbourhood's size.
+    pub fn checked(self) -> Result<Rule, RuleError> {
+        if !(1..=MAX_RANGE).contains(&self.range) {
+            return Err(RuleError::InvalidRange(self.range.to_string()));
+        }
+        if let Neighbourhood::Weighted(weights) = self.neighbourhood
+            && weights.range() != self.range
+        {
+            return Err(RuleError::WeightsRange { weights: weights.range(), range: self.range });
+        }
+        if self.birth[0] {
+            return Err(RuleError::BirthOnZero);
+        }
+        let max = self.neighbourhood.max_count(self.range);
+        if let Some(count) = self.birth.iter().chain(self.survival.iter()).filter(|&count| count > max).max() {
+            return Err(if self.is_life_like() {
+                RuleError::BeyondVonNeumann(char::from(b'0' + count as u8))
+            } else {
+                RuleError::BeyondRange { count, max }
+            });
+        }
+        Ok(self)
+    }
+
+    /// Whether the rule can be written in `B3/S23` notation: range 1 without weights.
+    pub fn is_life_like(&self) -> bool {
+        self.range == 1 && !matches!(self.neighbourhood, Neighbourhood::Weighted(_))
+    }
+
+    /// The weights [`convolve`](crate::life::convolve) counts neighbours with under this rule.
+    pub fn kernel(&self) -> Kernel {
+        Kernel::new(self.neighbourhood, self.range)
+    }
+
+    /// The same rule in another neighbourhood, dropping counts the neighbourhood can't reach.
+    pub fn with_neighbourhood(self, neighbourhood: Neighbourhood) -> Rule {
+        let mut rule = Rule { neighbourhood, ..self };
+        for count in neighbourhood.max_count(self.range) + 1..256 {
+            rule.birth.remove(count);
+            rule.survival.remove(count);
+        }
+        rule
+    }
+}
+
+/// Parses the comma-separated parts of a Larger than Life rule. `R`, `S` and `B` are needed;
+/// the rest default to 2 states (`C0`), not counting the middle (`M0`) and the Moore
+/// neighbourhood (`NM`, `NN` for von Neumann or `NW` followed by [`Weights`]). A part starting
+/// with a digit carries on the count list before it, as in `S2..3,5`.
+fn parse_larger_than_life(text: &str) -> Result<Rule, RuleError> {
+    let mut parts: [Option<String>; 6] = Default::default();
+    let mut last = None;
+    for part in text.split(',').map(str::trim) {
+        if let Some(index @ (3 | 4)) = last
+            && part.starts_with(|ch: char| ch.is_ascii_digit())
+            && let Some(list) = &mut parts[index]
+        {
+            list.push(',');
+            list.push_str(part);
+            continue;
+        }
+        let mut chars = part.chars();
+        let name = chars.next().ok_or(RuleError::UnexpectedChar(','))?.to_ascii_uppercase();
+        let index = "RCMSBN".find(name).ok_or(RuleError::UnexpectedChar(name))?;
+        if parts[index].replace(chars.as_str().to_string()).is_some() {
+            return Err(RuleError::DuplicatePart(name));
+        }
+        last = Some(index);
+    }
+    let [range, states, middle, survival, birth, neighbourhood] = parts.each_ref().map(Option::as_deref);
+
+    let range = range.ok_or(RuleError::MissingPart('R'))?;
+    let range = range.parse().map_err(|_| RuleError::InvalidRange(range.to_string()))?;
+    let states = match states.unwrap_or("0") {
+        "0" => 2,
+        states => parse_state_count(states)?,
+    };
+    let middle = match middle.unwrap_or("0") {
+        "0" => false,
+        "1" => true,
+        middle => return Err(RuleError::UnexpectedChar(middle.chars().next().unwrap_or('M'))),
+    };
+    let span = |span: &str| Counts::parse_spans(span).ok_or_else(|| RuleError::InvalidSpan(span.to_string()));
+    let survival = span(survival.ok_or(RuleError::MissingPart('S'))?)?;
+    let birth = span(birth.ok_or(RuleError::MissingPart('B'))?)?;
+    let neighbourhood = match neighbourhood.unwrap_or("M") {
+        "M" | "m" => Neighbourhood::Moore,
+        "N" | "n" => Neighbourhood::VonNeumann,
+        other if let Some(weights) = other.strip_prefix(['W', 'w']) => {
+            let invalid = || RuleError::InvalidWeights(weights.to_string());
+            Neighbourhood::Weighted(Weights::parse(weights).ok_or_else(invalid)?)
+        }
+        other => return Err(RuleError::UnexpectedChar(other.chars().next().unwrap_or('N'))),
+    };
+
+    // The cell counting itself is the same as surviving on one neighbour fewer.
+    let survival = if middle { survival.shifted(-1) } else { survival };
+    let rule = Rule { birth, survival, states, neighbourhood, range, middle };
+    Rule { middle: middle && !rule.is_life_like(), ..rule }.checked()
+}
+
+fn parse_state_count(text: &str) -> Result<u8, RuleError> {
+    match text.parse::<u8>() {
+        Ok(count) if count >= 2 => Ok(count),
+        _ => Err(RuleError::InvalidStateCount(text.to_string())),
+    }
+}
+
+fn parse_counts(digits: impl Iterator<Item = char>) -> Result<Counts, RuleError> {
+    let mut counts = Counts::EMPTY;
+    for ch in digits {
+        let count = ch.to_digit(10).ok_or(RuleError::UnexpectedChar(ch))?;
+        if count > 8 {
+            return Err(RuleError::InvalidDigit(ch));
+        }
+        counts.insert(count as usize);
+    }
+    Ok(counts)
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule::CONWAY
+    }
+}
+
+impl FromStr for Rule {
+    type Err = RuleError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Rule::parse(text)
+    }
+}
+
+/// Life-like rules are written in `B3/S23` notation and Larger than Life rules as in Golly.
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.is_life_like() {
+            let span = |counts: Counts| {
+                let spans = counts.spans().into_iter();
+                spans.map(|(min, max)| if min == max { min.to_string() } else { format!("{min}..{max}") }).join(",")
+            };
+            let survival = if self.middle { self.survival.shifted(1) } else { self.survival };
+            let states = if self.states == 2 { 0 } else { self.states };
+            let neighbourhood = match self.neighbourhood {
+                Neighbourhood::Moore => "M".to_string(),
+                Neighbourhood::VonNeumann => "N".to_string(),
+                Neighbourhood::Weighted(weights) => format!("W{weights}"),
+            };
+            return write!(
+                f,
+                "R{},C{states},M{},S{},B{},N{neighbourhood}",
+                self.range,
+                u8::from(self.middle),
+                span(survival),
+                span(self.birth)
+            );
+        }
+
+        let digits = |counts: &Counts| -> String {
+            (0..9).filter(|&n| counts[n]).map(|n| char::from(b'0' + n as u8)).collect()
+        };
+        write!(f, "B{}/S{}", digits(&self.birth), digits(&self.survival))?;
+        if self.states != 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        if self.neighbourhood == Neighbourhood::VonNeumann {
+            write!(f, "V")?;
+        }
+        Ok(())
+    }
+}
+
+/// Well-known Life-like rules that can be cycled through at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RulePreset {
+    Conway,
+    HighLife,
+    DayAndNight,
+    Seeds,
+    LifeWithoutDeath,
+}
+
+impl RulePreset {
+    pub const ALL: [RulePreset; 5] = [
+        RulePreset::Conway,
+        RulePreset::HighLife,
+        RulePreset::DayAndNight,
+        RulePreset::Seeds,
+        RulePreset::LifeWithoutDeath,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            RulePreset::Conway => "Conway",
+            RulePreset::HighLife => "HighLife",
+            RulePreset::DayAndNight => "Day & Night",
+            RulePreset::Seeds => "Seeds",
+            RulePreset::LifeWithoutDeath => "Life without Death",
+        }
+    }
+
+    pub fn rule(self) -> Rule {
+        let notation = match self {
+            RulePreset::Conway => "B3/S23",
+            RulePreset::HighLife => "B36/S23",
+            RulePreset::DayAndNight => "B3678/S34678",
+            RulePreset::Seeds => "B2/S",
+            RulePreset::LifeWithoutDeath => "B3/S012345678",
+        };
+        Rule::parse(notation).expect("presets are valid rules")
+    }
+
+    pub fn from_rule(rule: &Rule) -> Option<Self> {
+        Self::ALL.into_iter().find(|preset| preset.rule() == *rule)
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&preset| preset == self).unwrap();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use glam::I64Vec2;
+    use crate::life::{Engine, Life};
+    use crate::patterns::random_soup;
+    use crate::rle::parse_rle;
+    use crate::rng::Rng;
+
+    #[test]
+    fn parses_conway_and_highlife() {
+        assert_eq!(Rule::parse("B3/S23"), Ok(Rule::CONWAY));
+
+        let highlife = Rule::parse("B36/S23").unwrap();
+        assert!(highlife.birth[3] && highlife.birth[6]);
+        assert_eq!(highlife.survival, Rule::CONWAY.survival);
+    }
+
+    #[test]
+    fn accepts_notation_variations() {
+        assert_eq!(Rule::parse("b3/s23"), Ok(Rule::CONWAY));
+        assert_eq!(Rule::parse("S23/B3"), Ok(Rule::CONWAY));
+        assert_eq!(Rule::parse(" B3/S23 "), Ok(Rule::CONWAY));
+        assert_eq!(Rule::parse("B2/S").unwrap().survival, Counts::EMPTY);
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        assert_eq!(Rule::parse("B3"), Err(RuleError::MissingPart('S')));
+        assert_eq!(Rule::parse("B3/S23/B4"), Err(RuleError::DuplicatePart('B')));
+        assert_eq!(Rule::parse("B39/S23"), Err(RuleError::InvalidDigit('9')));
+        assert_eq!(Rule::parse("B3x/S23"), Err(RuleError::UnexpectedChar('x')));
+        assert_eq!(Rule::parse("23/3"), Err(RuleError::UnexpectedChar('2')));
+        assert_eq!(Rule::parse(""), Err(RuleError::UnexpectedChar('/')));
+        assert_eq!(Rule::parse("B03/S23"), Err(RuleError::BirthOnZero));
+    }
+
+    #[test]
+    fn parses_generations_state_count() {
+        let brians_brain = Rule::parse("B2/S/C3").unwrap();
+        assert_eq!(brians_brain.states, 3);
+        assert_eq!(Rule::parse("B3/S23/C2"), Ok(Rule::CONWAY));
+
+        assert_eq!(Rule::parse("B2/S/C1"), Err(RuleError::InvalidStateCount("1".to_string())));
+        assert_eq!(Rule::parse("B2/S/C"), Err(RuleError::InvalidStateCount(String::new())));
+        assert_eq!(Rule::parse("B2/S/C3/C4"), Err(RuleError::DuplicatePart('C')));
+    }
+
+    #[test]
+    fn displays_in_canonical_form() {
+        assert_eq!(Rule::CONWAY.to_string(), "B3/S23");
+        assert_eq!(Rule::parse("s32/b63").unwrap().to_string(), "B36/S23");
+        assert_eq!(Rule::parse("C3/S/B2").unwrap().to_string(), "B2/S/C3");
+    }
+
+    #[test]
+    fn parses_von_neumann_suffix() {
+        let rule = Rule::parse("B1/S1V").unwrap();
+        assert_eq!(rule.neighbourhood, Neighbourhood::VonNeumann);
+        assert!(rule.birth[1] && rule.survival[1]);
+        assert_eq!(rule.to_string(), "B1/S1V");
+        assert_eq!(Rule::parse("b2/s/c3v").unwrap().to_string(), "B2/S/C3V");
+        assert_eq!(Rule::CONWAY.neighbourhood, Neighbourhood::Moore);
+
+        assert_eq!(Rule::parse("B3/S35V"), Err(RuleError::BeyondVonNeumann('5')));
+        assert_eq!(Rule::parse("V"), Err(RuleError::UnexpectedChar('/')));
+        assert_eq!(Rule::CONWAY.with_neighbourhood(Neighbourhood::VonNeumann).to_string(), "B3/S23V");
+        let day_and_night = RulePreset::DayAndNight.rule().with_neighbourhood(Neighbourhood::VonNeumann);
+        assert_eq!(day_and_night.to_string(), "B3/S34V");
+    }
+
+    #[test]
+    fn parses_larger_than_life_notation() {
+        let bosco = Rule::parse("R5,C0,M1,S34..58,B34..45,NM").unwrap();
+        assert_eq!((bosco.range, bosco.states, bosco.middle), (5, 2, true));
+        assert!(bosco.survival[33] && bosco.survival[57] && !bosco.survival[58]);
+        assert!(!bosco.birth[33] && bosco.birth[34] && bosco.birth[45] && !bosco.birth[46]);
+        assert_eq!(bosco.to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+
+        let listed = Rule::parse("r2,c3,s2..3,5,b3,6..7,nn").unwrap();
+        assert_eq!((listed.states, listed.neighbourhood), (3, Neighbourhood::VonNeumann));
+        assert_eq!(listed.to_string(), "R2,C3,M0,S2..3,5,B3,6..7,NN");
+        assert_eq!(Rule::parse("R1,C0,M1,S3..4,B3,NM"), Ok(Rule::CONWAY));
+
+        assert_eq!(Rule::parse("R5,S34..58"), Err(RuleError::MissingPart('B')));
+        assert_eq!(Rule::parse("R8,S1,B1"), Err(RuleError::InvalidRange("8".to_string())));
+        assert_eq!(Rule::parse("R2,S1,B4..3"), Err(RuleError::InvalidSpan("4..3".to_string())));
+        assert_eq!(Rule::parse("R2,S1,B3,S2"), Err(RuleError::DuplicatePart('S')));
+        assert_eq!(Rule::parse("R2,S1..30,B3,NN"), Err(RuleError::BeyondRange { count: 30, max: 12 }));
+        assert_eq!(Rule::parse("R2,S1,B0..3"), Err(RuleError::BirthOnZero));
+    }
+
+    #[test]
+    fn parses_weighted_neighbourhoods() {
+        let weighted = Rule::parse("R1,C0,M0,S3..5,B4,6,NW121202121").unwrap();
+        let Neighbourhood::Weighted(weights) = weighted.neighbourhood else { panic!("not weighted") };
+        assert_eq!(weights.total(), 12);
+        assert_eq!(weighted.to_string(), "R1,C0,M0,S3..5,B4,6,NW121202121");
+
+        assert_eq!(Rule::parse("R1,S1,B1,NW1234"), Err(RuleError::InvalidWeights("1234".to_string())));
+        assert_eq!(Rule::parse("R2,S1,B1,NW111101111"), Err(RuleError::WeightsRange { weights: 1, range: 2 }));
+        assert_eq!(Rule::parse("R1,S1,B13,NW121202121"), Err(RuleError::BeyondRange { count: 13, max: 12 }));
+    }
+
+    #[test]
+    fn asymmetric_weights_move_patterns() {
+        // Cells are born under a live cell and survive only with one above them, so everything
+        // falls a row each generation.
+        let falling = Rule::parse("R1,C0,M0,S1,B1,NW010000000").unwrap();
+        let cells: HashSet<I64Vec2> = [I64Vec2::ZERO, I64Vec2::new(3, 1), I64Vec2::new(4, 1)].into();
+        for engine in [Engine::Naive, Engine::Incremental, Engine::HashLife] {
+            let mut life = Life::from_cells(cells.clone());
+            life.set_rule(falling);
+            life.set_engine(engine);
+            life.step();
+            life.step();
+            let expected: HashSet<I64Vec2> = cells.iter().map(|&cell| cell + I64Vec2::new(0, 2)).collect();
+            assert_eq!(life.live_cells(), expected, "{engine:?}");
+        }
+    }
+
+    #[test]
+    fn bosco_steps_like_a_direct_count() {
+        let bosco = Rule::parse("R5,C0,M1,S34..58,B34..45,NM").unwrap();
+        let soup = random_soup(&mut Rng::seed_from_u64(3), I64Vec2::ZERO, I64Vec2::splat(30), 0.5);
+        let mut naive = Life::from_cells(soup.clone());
+        naive.set_rule(bosco);
+        let mut incremental = naive.clone();
+        incremental.set_engine(Engine::Incremental);
+        let mut expected = soup;
+
+        for generation in 0..8 {
+            let near = |cell: I64Vec2| {
+                (-5..=5).flat_map(move |y| (-5..=5).map(move |x| cell + I64Vec2::new(x, y)))
+            };
+            let candidates: HashSet<I64Vec2> = expected.iter().flat_map(|&cell| near(cell)).collect();
+            expected = candidates
+                .into_iter()
+                .filter(|&cell| {
+                    // Bosco's Rule counts the cell itself, as M1 says.
+                    let count = near(cell).filter(|neighbour| expected.contains(neighbour)).count();
+                    if expected.contains(&cell) { (34..=58).contains(&count) } else { (34..=45).contains(&count) }
+                })
+                .collect();
+            naive.step();
+            incremental.step();
+            assert_eq!(naive.live_cells(), expected, "diverged at generation {generation}");
+            assert_eq!(incremental.live_cells(), expected, "diverged at generation {generation}");
+        }
+        assert!(!expected.is_empty());
+    }
+
+    #[test]
+    fn presets_cycle_and_round_trip() {
+        for preset in RulePreset::ALL {
+            assert_eq!(RulePreset::from_rule(&preset.rule()), Some(preset));
+        }
+        assert_eq!(RulePreset::Conway.next(), RulePreset::HighLife);
+        assert_eq!(RulePreset::LifeWithoutDeath.next(), RulePreset::Conway);
+        assert_eq!(RulePreset::from_rule(&Rule::parse("B1/S1").unwrap()), None);
+    }
+
+    #[test]
+    fn seeds_births_from_two_neighbours() {
+        let mut life = Life::from_cells([I64Vec2::new(0, 0), I64Vec2::new(1, 0)].into_iter().collect());
+        life.set_rule(RulePreset::Seeds.rule());
+        life.step();
+
+        let expected: HashSet<I64Vec2> = [(0, -1), (1, -1), (0, 1), (1, 1)]
+            .iter()
+            .map(|&(x, y)| I64Vec2::new(x, y))
+            .collect();
+        assert_eq!(life.live_cells(), expected);
+    }
+
+    #[test]
+    fn highlife_replicator_copies_itself() {
+        let replicator = parse_rle("2b3o$bo2bo$o3bo$o2bo$3o!").unwrap();
+        let shifted = |offset: I64Vec2| replicator.iter().map(move |&cell| cell + offset);
+        let expected: HashSet<I64Vec2> = shifted(I64Vec2::splat(-2)).chain(shifted(I64Vec2::splat(2))).collect();
+
+        let mut highlife = Life::from_cells(replicator.clone());
+        highlife.set_rule(RulePreset::HighLife.rule());
+        let mut conway = Life::from_cells(replicator.clone());
+        for _ in 0..12 {
+            highlife.step();
+            conway.step();
+        }
+
+        assert_eq!(highlife.live_cells(), expected);
+        assert_ne!(conway.live_cells(), expected);
+    }
+}
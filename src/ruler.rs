@@ -0,0 +1,57 @@
+use std::fmt;
+use glam::I64Vec2;
+
+/// How far one cell is from another, for lining up spaceships and spacing out guns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    pub delta: I64Vec2,
+}
+
+impl Measurement {
+    pub fn between(from: I64Vec2, to: I64Vec2) -> Self {
+        Self { delta: to.saturating_sub(from) }
+    }
+
+    /// The distance a king moves, and so the generations a light-speed signal takes.
+    pub fn chebyshev(&self) -> u64 {
+        self.delta.x.unsigned_abs().max(self.delta.y.unsigned_abs())
+    }
+
+    pub fn manhattan(&self) -> u64 {
+        self.delta.x.unsigned_abs().saturating_add(self.delta.y.unsigned_abs())
+    }
+
+    pub fn euclidean(&self) -> f64 {
+        (self.delta.x as f64).hypot(self.delta.y as f64)
+    }
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "dx {}, dy {}, Chebyshev {}, Manhattan {}, Euclidean {:.2}",
+            self.delta.x,
+            self.delta.y,
+            self.chebyshev(),
+            self.manhattan(),
+            self.euclidean()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_every_distance() {
+        let measurement = Measurement::between(I64Vec2::new(2, 5), I64Vec2::new(-1, 9));
+        assert_eq!(measurement.delta, I64Vec2::new(-3, 4));
+        assert_eq!((measurement.chebyshev(), measurement.manhattan(), measurement.euclidean()), (4, 7, 5.0));
+        assert_eq!(measurement.to_string(), "dx -3, dy 4, Chebyshev 4, Manhattan 7, Euclidean 5.00");
+
+        let far = Measurement::between(I64Vec2::MIN, I64Vec2::MAX);
+        assert_eq!((far.chebyshev(), far.manhattan()), (i64::MAX as u64, 2 * i64::MAX as u64));
+    }
+}
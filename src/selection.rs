@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+use glam::I64Vec2;
+
+/// An axis-aligned box of whole cells in world space with inclusive corners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CellRect {
+    pub min: I64Vec2,
+    pub max: I64Vec2,
+}
+
+impl CellRect {
+    /// Spans the two cells, whichever corners they are.
+    pub fn from_corners(a: I64Vec2, b: I64Vec2) -> Self {
+        Self { min: a.min(b), max: a.max(b) }
+    }
+
+    /// Width and height in cells.
+    pub fn size(&self) -> I64Vec2 {
+        self.max - self.min + I64Vec2::ONE
+    }
+
+    /// How many cells the box covers, saturating for boxes too large to count.
+    pub fn area(&self) -> u64 {
+        let width = self.max.x.abs_diff(self.min.x).saturating_add(1);
+        let height = self.max.y.abs_diff(self.min.y).saturating_add(1);
+        width.saturating_mul(height)
+    }
+
+    pub fn contains(&self, cell: I64Vec2) -> bool {
+        cell.cmpge(self.min).all() && cell.cmple(self.max).all()
+    }
+
+    /// The given cells that fall inside the box.
+    pub fn filter(&self, cells: &HashSet<I64Vec2>) -> HashSet<I64Vec2> {
+        cells.iter().copied().filter(|&cell| self.contains(cell)).collect()
+    }
+
+    /// The cells inside the box relative to its top-left corner, sorted by row then column.
+    pub fn copy(&self, cells: &HashSet<I64Vec2>) -> Vec<I64Vec2> {
+        let mut copied: Vec<I64Vec2> = self.filter(cells).into_iter().map(|cell| cell - self.min).collect();
+        copied.sort_by_key(|cell| (cell.y, cell.x));
+        copied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_corners() {
+        let rect = CellRect::from_corners(I64Vec2::new(4, -1), I64Vec2::new(-2, 3));
+        assert_eq!(rect.min, I64Vec2::new(-2, -1));
+        assert_eq!(rect.max, I64Vec2::new(4, 3));
+        assert_eq!(rect.size(), I64Vec2::new(7, 5));
+        assert_eq!(CellRect::from_corners(I64Vec2::ONE, I64Vec2::ONE).size(), I64Vec2::ONE);
+        assert_eq!(rect.area(), 35);
+        assert_eq!(CellRect::from_corners(I64Vec2::MIN, I64Vec2::MAX).area(), u64::MAX);
+    }
+
+    #[test]
+    fn contains_edges_inclusively() {
+        let rect = CellRect::from_corners(I64Vec2::ZERO, I64Vec2::new(2, 2));
+        assert!(rect.contains(I64Vec2::ZERO));
+        assert!(rect.contains(I64Vec2::new(2, 2)));
+        assert!(!rect.contains(I64Vec2::new(3, 2)));
+        assert!(!rect.contains(I64Vec2::new(1, -1)));
+
+        let cells = [I64Vec2::new(1, 1), I64Vec2::new(5, 5)].into_iter().collect();
+        assert_eq!(rect.filter(&cells), [I64Vec2::new(1, 1)].into_iter().collect());
+    }
+
+    #[test]
+    fn copy_is_relative_to_the_top_left() {
+        let glider = [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].map(|(x, y)| I64Vec2::new(x, y));
+        let offset = I64Vec2::new(-40, 17);
+        let board: HashSet<I64Vec2> = glider.iter().map(|&cell| cell + offset).chain([I64Vec2::ZERO]).collect();
+
+        let rect = CellRect::from_corners(offset, offset + 2);
+        let mut expected = glider.to_vec();
+        expected.sort_by_key(|cell| (cell.y, cell.x));
+        assert_eq!(rect.copy(&board), expected);
+
+        let pasted: HashSet<I64Vec2> = rect.copy(&board).into_iter().map(|cell| cell + offset).collect();
+        assert_eq!(pasted, rect.filter(&board));
+    }
+}
@@ -0,0 +1,332 @@
+use std::path::Path;
+use std::{fmt, fs, io};
+use glam::I64Vec2;
+use crate::camera::{Bookmark, BOOKMARK_SLOTS, DEFAULT_CELL_SIZE};
+use crate::json::{Json, JsonError};
+use crate::life::{Colouring, Life, ALIVE};
+use crate::rule::{Rule, RuleError};
+
+/// The session format written by [`Session::to_json`]. Older or newer files still load, with
+/// any fields they lack left at their defaults.
+pub const SESSION_VERSION: i64 = 1;
+
+/// Everything needed to pick up where the app was left: the board, the camera and whether
+/// the simulation was running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub cells: Vec<I64Vec2>,
+    /// Decaying cells of a Generations rule with their states.
+    pub decaying: Vec<(I64Vec2, u8)>,
+    /// The coloured variant live cells follow, if any.
+    pub colouring: Option<Colouring>,
+    /// Live cells of a colouring with their colours, leaving out those of colour 1.
+    pub colours: Vec<(I64Vec2, u8)>,
+    pub origin: I64Vec2,
+    pub cell_size: i32,
+    pub is_running: bool,
+    pub generation: u64,
+    pub rule: Rule,
+    /// The camera bookmarks, by slot.
+    pub bookmarks: [Option<Bookmark>; BOOKMARK_SLOTS],
+    /// The seed of the last random fill, so the soup can be made again.
+    pub fill_seed: Option<u64>,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self {
+            cells: Vec::new(),
+            decaying: Vec::new(),
+            colouring: None,
+            colours: Vec::new(),
+            origin: I64Vec2::ZERO,
+            cell_size: DEFAULT_CELL_SIZE,
+            is_running: false,
+            generation: 0,
+            rule: Rule::default(),
+            bookmarks: [None; BOOKMARK_SLOTS],
+            fill_seed: None,
+        }
+    }
+}
+
+impl Session {
+    pub fn capture(life: &Life, origin: I64Vec2, cell_size: i32, is_running: bool) -> Self {
+        let mut cells: Vec<I64Vec2> = life.live_cells().into_iter().collect();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+        let mut decaying: Vec<(I64Vec2, u8)> =
+            life.cells().iter().filter(|&(_, &state)| state != ALIVE).map(|(&cell, &state)| (cell, state)).collect();
+        decaying.sort_by_key(|&(cell, _)| (cell.y, cell.x));
+        let colours = cells.iter().map(|&cell| (cell, life.colour(cell))).filter(|&(_, colour)| colour > 1).collect();
+
+        Self {
+            cells,
+            decaying,
+            colouring: life.colouring(),
+            colours,
+            origin,
+            cell_size,
+            is_running,
+            generation: life.generation(),
+            rule: *life.rule(),
+            bookmarks: [None; BOOKMARK_SLOTS],
+            fill_seed: None,
+        }
+    }
+
+    /// Builds the saved board with the default engine.
+    pub fn board(&self) -> Life {
+        let mut life = Life::new();
+        life.set_rule(self.rule);
+        life.set_colouring(self.colouring);
+        life.extend(self.cells.iter().copied());
+        for &(cell, state) in &self.decaying {
+            life.set_state(cell, state);
+        }
+        for &(cell, colour) in &self.colours {
+            life.set_colour(cell, colour);
+        }
+        life.set_generation(self.generation);
+        life
+    }
+
+    /// Writes the session with one field per line and coordinates as `[x, y]` pairs. Bookmarks
+    /// are `[x, y, cell_size]`, or `null` for an empty slot. The fill seed is a string, as a
+    /// JSON integer here can't hold every `u64`.
+    pub fn to_json(&self) -> String {
+        let point = |cell: I64Vec2| Json::Array(vec![Json::Int(cell.x), Json::Int(cell.y)]);
+        let bookmark = |bookmark: &Option<Bookmark>| match bookmark {
+            Some(Bookmark { origin, cell_size }) => {
+                Json::Array(vec![Json::Int(origin.x), Json::Int(origin.y), Json::Int(*cell_size as i64)])
+            }
+            None => Json::Null,
+        };
+        let with_value = |&(cell, value): &(I64Vec2, u8)| {
+            Json::Array(vec![Json::Int(cell.x), Json::Int(cell.y), Json::Int(value as i64)])
+        };
+        let fields = [
+            ("version", Json::Int(SESSION_VERSION)),
+            ("rule", Json::String(self.rule.to_string())),
+            ("generation", Json::Int(i64::try_from(self.generation).unwrap_or(i64::MAX))),
+            ("origin", point(self.origin)),
+            ("cell_size", Json::Int(self.cell_size as i64)),
+            ("is_running", Json::Bool(self.is_running)),
+            ("cells", Json::Array(self.cells.iter().map(|&cell| point(cell)).collect())),
+            ("decaying", Json::Array(self.decaying.iter().map(with_value).collect())),
+            ("colouring", self.colouring.map_or(Json::Null, |colouring| Json::String(colouring.name().to_string()))),
+            ("colours", Json::Array(self.colours.iter().map(with_value).collect())),
+            ("bookmarks", Json::Array(self.bookmarks.iter().map(bookmark).collect())),
+            ("fill_seed", self.fill_seed.map_or(Json::Null, |seed| Json::String(seed.to_string()))),
+        ];
+
+        let lines: Vec<String> =
+            fields.iter().map(|(key, value)| format!("  {}: {value}", Json::String(key.to_string()))).collect();
+        format!("{{\n{}\n}}\n", lines.join(",\n"))
+    }
+
+    /// Reads a session, keeping the default for any field the file leaves out and ignoring
+    /// fields it doesn't know.
+    pub fn from_json(input: &str) -> Result<Session, SessionError> {
+        let json = Json::parse(input)?;
+        if !matches!(json, Json::Object(_)) {
+            return Err(SessionError::InvalidField("session"));
+        }
+        let mut session = Session::default();
+
+        if let Some(rule) = json.get("rule") {
+            session.rule = Rule::parse(rule.as_str().ok_or(SessionError::InvalidField("rule"))?)?;
+        }
+        if let Some(generation) = json.get("generation") {
+            session.generation = field(generation.as_i64().and_then(|value| u64::try_from(value).ok()), "generation")?;
+        }
+        if let Some(origin) = json.get("origin") {
+            session.origin = field(point(origin), "origin")?;
+        }
+        if let Some(cell_size) = json.get("cell_size") {
+            session.cell_size = field(cell_size.as_i64().and_then(|value| i32::try_from(value).ok()), "cell_size")?;
+        }
+        if let Some(is_running) = json.get("is_running") {
+            session.is_running = field(is_running.as_bool(), "is_running")?;
+        }
+        if let Some(cells) = json.get("cells") {
+            let cells = cells.as_array().ok_or(SessionError::InvalidField("cells"))?;
+            session.cells = cells.iter().map(|cell| field(point(cell), "cells")).collect::<Result<_, _>>()?;
+        }
+        if let Some(decaying) = json.get("decaying") {
+            session.decaying = with_values(decaying, "decaying")?;
+        }
+        if let Some(colouring) = json.get("colouring") {
+            session.colouring = match colouring {
+                Json::Null => None,
+                colouring => Some(field(colouring.as_str().and_then(Colouring::parse), "colouring")?),
+            };
+        }
+        if let Some(colours) = json.get("colours") {
+            session.colours = with_values(colours, "colours")?;
+        }
+        if let Some(bookmarks) = json.get("bookmarks") {
+            let bookmarks = bookmarks.as_array().filter(|slots| slots.len() <= BOOKMARK_SLOTS);
+            for (slot, entry) in field(bookmarks, "bookmarks")?.iter().enumerate() {
+                session.bookmarks[slot] = match entry {
+                    Json::Null => None,
+                    entry => Some(field(bookmark(entry), "bookmarks")?),
+                };
+            }
+        }
+        if let Some(fill_seed) = json.get("fill_seed") {
+            session.fill_seed = match fill_seed {
+                Json::Null => None,
+                seed => Some(field(seed.as_str().and_then(|seed| seed.parse().ok()), "fill_seed")?),
+            };
+        }
+
+        Ok(session)
+    }
+
+    pub fn load(path: &Path) -> Result<Session, SessionError> {
+        Session::from_json(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+}
+
+fn point(json: &Json) -> Option<I64Vec2> {
+    match json.as_array()? {
+        [x, y] => Some(I64Vec2::new(x.as_i64()?, y.as_i64()?)),
+        _ => None,
+    }
+}
+
+/// Reads `[x, y, value]` entries, such as decaying cells with their states.
+fn with_values(json: &Json, name: &'static str) -> Result<Vec<(I64Vec2, u8)>, SessionError> {
+    field(json.as_array(), name)?
+        .iter()
+        .map(|entry| match entry.as_array() {
+            Some([x, y, value]) => {
+                let value = value.as_i64().and_then(|value| u8::try_from(value).ok());
+                Some((I64Vec2::new(x.as_i64()?, y.as_i64()?), value?))
+            }
+            _ => None,
+        })
+        .map(|entry| field(entry, name))
+        .collect()
+}
+
+fn bookmark(json: &Json) -> Option<Bookmark> {
+    match json.as_array()? {
+        [x, y, cell_size] => Some(Bookmark {
+            origin: I64Vec2::new(x.as_i64()?, y.as_i64()?),
+            cell_size: i32::try_from(cell_size.as_i64()?).ok()?,
+        }),
+        _ => None,
+    }
+}
+
+fn field<T>(value: Option<T>, name: &'static str) -> Result<T, SessionError> {
+    value.ok_or(SessionError::InvalidField(name))
+}
+
+#[derive(Debug)]
+pub enum SessionError {
+    Io(io::Error),
+    Json(JsonError),
+    Rule(RuleError),
+    /// The field is present but holds the wrong kind of value.
+    InvalidField(&'static str),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionError::Io(err) => write!(f, "{err}"),
+            SessionError::Json(err) => write!(f, "invalid JSON: {err}"),
+            SessionError::Rule(err) => write!(f, "invalid rule: {err}"),
+            SessionError::InvalidField(name) => write!(f, "invalid value for {name:?}"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+impl From<io::Error> for SessionError {
+    fn from(err: io::Error) -> Self {
+        SessionError::Io(err)
+    }
+}
+
+impl From<JsonError> for SessionError {
+    fn from(err: JsonError) -> Self {
+        SessionError::Json(err)
+    }
+}
+
+impl From<RuleError> for SessionError {
+    fn from(err: RuleError) -> Self {
+        SessionError::Rule(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_a_session() {
+        let mut life = Life::from_cells([I64Vec2::new(-3, 7), I64Vec2::new(i64::MAX, i64::MIN)].into_iter().collect());
+        life.set_rule(Rule::parse("B2/S/C3").unwrap());
+        life.set_state(I64Vec2::new(1, 1), 2);
+        life.set_generation(42);
+        let mut session = Session::capture(&life, I64Vec2::new(-10, 20), 7, true);
+        session.bookmarks[2] = Some(Bookmark { origin: I64Vec2::new(5, -5), cell_size: 30 });
+        session.fill_seed = Some(u64::MAX);
+
+        let restored = Session::from_json(&session.to_json()).unwrap();
+        assert_eq!(restored, session);
+        let board = restored.board();
+        assert_eq!(board, life);
+        assert_eq!(board.generation(), 42);
+    }
+
+    #[test]
+    fn round_trips_cell_colours() {
+        let mut life = Life::new();
+        life.set_colouring(Some(Colouring::QuadLife));
+        life.insert(I64Vec2::new(0, 0));
+        life.set_paint_colour(3);
+        life.insert(I64Vec2::new(1, 0));
+        let session = Session::capture(&life, I64Vec2::ZERO, 7, false);
+        assert_eq!(session.colours, [(I64Vec2::new(1, 0), 3)]);
+
+        let board = Session::from_json(&session.to_json()).unwrap().board();
+        assert_eq!(board.colouring(), Some(Colouring::QuadLife));
+        assert_eq!((board.colour(I64Vec2::new(0, 0)), board.colour(I64Vec2::new(1, 0))), (1, 3));
+    }
+
+    #[test]
+    fn defaults_missing_fields_and_ignores_unknown_ones() {
+        let session = Session::from_json(r#"{"version": 99, "cells": [[1, 2]], "camera_zoom": 3.5}"#).unwrap();
+        assert_eq!(session.cells, [I64Vec2::new(1, 2)]);
+        assert_eq!(Session { cells: Vec::new(), ..session }, Session::default());
+        assert_eq!(Session::from_json("{}").unwrap(), Session::default());
+    }
+
+    #[test]
+    fn rejects_malformed_sessions() {
+        assert!(matches!(Session::from_json("{"), Err(SessionError::Json(_))));
+        assert!(matches!(Session::from_json("[]"), Err(SessionError::InvalidField("session"))));
+        assert!(matches!(Session::from_json(r#"{"rule": "B9/S"}"#), Err(SessionError::Rule(_))));
+        assert!(matches!(Session::from_json(r#"{"origin": [1]}"#), Err(SessionError::InvalidField("origin"))));
+        assert!(matches!(Session::from_json(r#"{"cells": [[1, "2"]]}"#), Err(SessionError::InvalidField("cells"))));
+        assert!(matches!(Session::from_json(r#"{"generation": -1}"#), Err(SessionError::InvalidField("generation"))));
+        assert!(matches!(Session::from_json(r#"{"fill_seed": 7}"#), Err(SessionError::InvalidField("fill_seed"))));
+        let colouring = Session::from_json(r#"{"colouring": "Rainbow"}"#);
+        assert!(matches!(colouring, Err(SessionError::InvalidField("colouring"))));
+        let too_many_bookmarks = format!(r#"{{"bookmarks": [{}]}}"#, ["null"; BOOKMARK_SLOTS + 1].join(", "));
+        for bookmarks in [r#"{"bookmarks": [[1, 2]]}"#, &too_many_bookmarks] {
+            assert!(matches!(Session::from_json(bookmarks), Err(SessionError::InvalidField("bookmarks"))));
+        }
+        assert!(matches!(Session::load(Path::new("does/not/exist.json")), Err(SessionError::Io(_))));
+    }
+}
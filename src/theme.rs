@@ -0,0 +1,190 @@
+use std::path::Path;
+use std::{fmt, fs, io};
+use raylib::color::Color;
+
+/// The colours used to draw the board and UI.
+///
+/// Live cells shade from `cell` when newborn to `old_cell` as they age, and decaying cells
+/// fade from `cell` into the background. Under Immigration and QuadLife, cells are `cell`,
+/// `second_cell`, `third_cell` or `fourth_cell` by colour instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub background: Color,
+    pub cell: Color,
+    pub old_cell: Color,
+    pub second_cell: Color,
+    pub third_cell: Color,
+    pub fourth_cell: Color,
+    pub grid: Color,
+    pub hover: Color,
+    pub text: Color,
+    /// Selection outlines and paste previews.
+    pub accent: Color,
+}
+
+impl Theme {
+    pub const LIGHT: Theme = Theme {
+        background: Color::new(255, 255, 255, 255),
+        cell: Color::new(64, 96, 160, 255),
+        old_cell: Color::new(120, 24, 16, 255),
+        second_cell: Color::new(200, 120, 0, 255),
+        third_cell: Color::new(40, 150, 60, 255),
+        fourth_cell: Color::new(170, 50, 170, 255),
+        grid: Color::new(200, 200, 200, 255),
+        hover: Color::new(230, 41, 55, 255),
+        text: Color::new(130, 130, 130, 255),
+        accent: Color::new(0, 121, 241, 255),
+    };
+
+    pub const DARK: Theme = Theme {
+        background: Color::new(24, 24, 28, 255),
+        cell: Color::new(120, 170, 255, 255),
+        old_cell: Color::new(255, 140, 60, 255),
+        second_cell: Color::new(255, 210, 80, 255),
+        third_cell: Color::new(110, 220, 120, 255),
+        fourth_cell: Color::new(230, 120, 230, 255),
+        grid: Color::new(48, 48, 56, 255),
+        hover: Color::new(255, 80, 80, 255),
+        text: Color::new(170, 170, 180, 255),
+        accent: Color::new(80, 160, 255, 255),
+    };
+
+    /// Parses `key = #rrggbb` lines, with an optional alpha byte. Keys left out keep the colour
+    /// of the `base` preset, `light` unless a `base = dark` line says otherwise. Blank lines and
+    /// lines starting with `//` are ignored.
+    pub fn parse(input: &str) -> Result<Theme, ThemeError> {
+        let mut theme = Theme::LIGHT;
+
+        for (index, line) in input.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+
+            let (key, value) = line.split_once('=').ok_or(ThemeError::InvalidLine { line: line_number })?;
+            let (key, value) = (key.trim(), value.trim().trim_matches('"'));
+            if key == "base" {
+                theme = match value {
+                    "light" => Theme::LIGHT,
+                    "dark" => Theme::DARK,
+                    _ => return Err(ThemeError::UnknownBase { line: line_number, base: value.to_string() }),
+                };
+                continue;
+            }
+
+            let color = parse_color(value).ok_or(ThemeError::InvalidColor { line: line_number })?;
+            *match key {
+                "background" => &mut theme.background,
+                "cell" => &mut theme.cell,
+                "old_cell" => &mut theme.old_cell,
+                "second_cell" => &mut theme.second_cell,
+                "third_cell" => &mut theme.third_cell,
+                "fourth_cell" => &mut theme.fourth_cell,
+                "grid" => &mut theme.grid,
+                "hover" => &mut theme.hover,
+                "text" => &mut theme.text,
+                "accent" => &mut theme.accent,
+                _ => return Err(ThemeError::UnknownKey { line: line_number, key: key.to_string() }),
+            } = color;
+        }
+
+        Ok(theme)
+    }
+
+    pub fn load(path: &Path) -> Result<Theme, ThemeError> {
+        Theme::parse(&fs::read_to_string(path)?)
+    }
+
+    /// The colour a live cell of a coloured variant is drawn in, by its colour from 1.
+    pub fn coloured_cell(&self, colour: u8) -> Color {
+        match colour {
+            2 => self.second_cell,
+            3 => self.third_cell,
+            4 => self.fourth_cell,
+            _ => self.cell,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::LIGHT
+    }
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+    let hex = value.strip_prefix('#')?;
+    if !matches!(hex.len(), 6 | 8) || !hex.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(3)? } else { 255 };
+    Some(Color::new(channel(0)?, channel(1)?, channel(2)?, alpha))
+}
+
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(io::Error),
+    InvalidLine { line: usize },
+    UnknownKey { line: usize, key: String },
+    UnknownBase { line: usize, base: String },
+    InvalidColor { line: usize },
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ThemeError::Io(err) => write!(f, "{err}"),
+            ThemeError::InvalidLine { line } => write!(f, "expected `key = #rrggbb` on line {line}"),
+            ThemeError::UnknownKey { line, key } => write!(f, "unknown theme colour {key:?} on line {line}"),
+            ThemeError::UnknownBase { line, base } => {
+                write!(f, "unknown base theme {base:?} on line {line} (expected light or dark)")
+            }
+            ThemeError::InvalidColor { line } => write!(f, "invalid colour on line {line} (expected #rrggbb)"),
+        }
+    }
+}
+
+impl std::error::Error for ThemeError {}
+
+impl From<io::Error> for ThemeError {
+    fn from(err: io::Error) -> Self {
+        ThemeError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_overrides_on_a_base() {
+        let input = "// Solarized-ish\nbase = dark\n\nbackground = #002b36\ncell = \"#b58900\"\n\
+            second_cell = #cb4b16\naccent=#268bd280\n";
+        let theme = Theme::parse(input).unwrap();
+
+        assert_eq!(theme.background, Color::new(0x00, 0x2b, 0x36, 255));
+        assert_eq!(theme.cell, Color::new(0xb5, 0x89, 0x00, 255));
+        assert_eq!(theme.accent, Color::new(0x26, 0x8b, 0xd2, 0x80));
+        assert_eq!(theme.second_cell, Color::new(0xcb, 0x4b, 0x16, 255));
+        assert_eq!(theme.coloured_cell(2), theme.second_cell);
+        assert_eq!(theme.coloured_cell(4), Theme::DARK.fourth_cell);
+        assert_eq!(theme.grid, Theme::DARK.grid);
+        assert_eq!(Theme::parse("").unwrap(), Theme::LIGHT);
+    }
+
+    #[test]
+    fn rejects_malformed_themes() {
+        assert!(matches!(Theme::parse("background #fff"), Err(ThemeError::InvalidLine { line: 1 })));
+        assert!(matches!(Theme::parse("\nborder = #ffffff"), Err(ThemeError::UnknownKey { line: 2, .. })));
+        assert!(matches!(Theme::parse("base = sepia"), Err(ThemeError::UnknownBase { line: 1, .. })));
+        for color in ["#fff", "ffffff", "#gggggg", "#ffffff0", "#+fffff"] {
+            assert!(
+                matches!(Theme::parse(&format!("cell = {color}")), Err(ThemeError::InvalidColor { line: 1 })),
+                "{color}",
+            );
+        }
+        assert!(matches!(Theme::load(Path::new("does/not/exist.theme")), Err(ThemeError::Io(_))));
+    }
+}
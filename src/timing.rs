@@ -0,0 +1,55 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How long the most recent steps took, for a rolling average that shows when a growing
+/// pattern starts to slow stepping down.
+#[derive(Debug, Clone)]
+pub struct StepTimes {
+    samples: VecDeque<Duration>,
+    total: Duration,
+    capacity: usize,
+}
+
+impl StepTimes {
+    pub fn new(capacity: usize) -> Self {
+        Self { samples: VecDeque::with_capacity(capacity), total: Duration::ZERO, capacity }
+    }
+
+    /// Records one step's time, dropping the oldest once `capacity` are kept.
+    pub fn record(&mut self, took: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity
+            && let Some(oldest) = self.samples.pop_front()
+        {
+            self.total -= oldest;
+        }
+        self.samples.push_back(took);
+        self.total += took;
+    }
+
+    /// The mean of the kept times, or `None` before any step.
+    pub fn average(&self) -> Option<Duration> {
+        (!self.samples.is_empty()).then(|| self.total / self.samples.len() as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_the_latest_steps() {
+        let mut times = StepTimes::new(3);
+        assert_eq!(times.average(), None);
+        for millis in [10, 2, 4, 6] {
+            times.record(Duration::from_millis(millis));
+        }
+        assert_eq!(times.average(), Some(Duration::from_millis(4)));
+
+        let mut disabled = StepTimes::new(0);
+        disabled.record(Duration::from_millis(1));
+        assert_eq!(disabled.average(), None);
+    }
+}
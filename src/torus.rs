@@ -0,0 +1,93 @@
+use glam::I64Vec2;
+
+/// What lies past the edges of a finite board.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Edges {
+    /// Each edge meets the opposite one.
+    #[default]
+    Wrap,
+    /// Nothing: cells next to a wall just have fewer neighbours.
+    Wall,
+}
+
+/// A finite board whose opposite edges meet, covering the cells from the origin up to, but
+/// not including, `size`. With walls for edges it's a plain rectangle instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Torus {
+    pub size: I64Vec2,
+    pub edges: Edges,
+}
+
+impl Torus {
+    /// Returns `None` unless both dimensions are positive.
+    pub fn new(size: I64Vec2) -> Option<Self> {
+        size.cmpgt(I64Vec2::ZERO).all().then_some(Self { size, edges: Edges::Wrap })
+    }
+
+    /// A board of `size` bounded by walls, or `None` unless both dimensions are positive.
+    pub fn walled(size: I64Vec2) -> Option<Self> {
+        Torus::new(size).map(|torus| torus.with_edges(Edges::Wall))
+    }
+
+    pub fn with_edges(self, edges: Edges) -> Self {
+        Self { edges, ..self }
+    }
+
+    /// Parses a size written as `<width>x<height>`, such as `100x80`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (width, height) = text.trim().split_once(['x', 'X'])?;
+        Torus::new(I64Vec2::new(width.parse().ok()?, height.parse().ok()?))
+    }
+
+    pub fn contains(&self, cell: I64Vec2) -> bool {
+        cell.cmpge(I64Vec2::ZERO).all() && cell.cmplt(self.size).all()
+    }
+
+    /// Maps any cell onto the torus, so stepping off one edge comes back on the opposite one.
+    pub fn wrap(&self, cell: I64Vec2) -> I64Vec2 {
+        I64Vec2::new(cell.x.rem_euclid(self.size.x), cell.y.rem_euclid(self.size.y))
+    }
+
+    /// Where a neighbour lookup for `cell` lands: wrapped back onto the board, or `None` past a
+    /// wall.
+    pub fn neighbour(&self, cell: I64Vec2) -> Option<I64Vec2> {
+        match self.edges {
+            Edges::Wrap => Some(self.wrap(cell)),
+            Edges::Wall => self.contains(cell).then_some(cell),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_sizes() {
+        assert_eq!(Torus::parse("100x80"), Torus::new(I64Vec2::new(100, 80)));
+        assert_eq!(Torus::parse(" 3X4 ").map(|torus| torus.size), Some(I64Vec2::new(3, 4)));
+        for malformed in ["", "100", "0x10", "10x-1", "ax b", "10x10x10"] {
+            assert_eq!(Torus::parse(malformed), None, "{malformed:?}");
+        }
+    }
+
+    #[test]
+    fn wraps_onto_the_opposite_edge() {
+        let torus = Torus::new(I64Vec2::new(10, 5)).unwrap();
+        assert_eq!(torus.wrap(I64Vec2::new(-1, 5)), I64Vec2::new(9, 0));
+        assert_eq!(torus.wrap(I64Vec2::new(23, -7)), I64Vec2::new(3, 3));
+        assert!(torus.contains(I64Vec2::new(9, 4)));
+        assert!(!torus.contains(I64Vec2::new(10, 0)));
+        assert!(!torus.contains(I64Vec2::new(0, -1)));
+        assert_eq!(torus.neighbour(I64Vec2::new(-1, 5)), Some(I64Vec2::new(9, 0)));
+    }
+
+    #[test]
+    fn walls_drop_cells_past_the_edge() {
+        let walled = Torus::walled(I64Vec2::new(10, 5)).unwrap();
+        assert_eq!(walled.neighbour(I64Vec2::new(-1, 2)), None);
+        assert_eq!(walled.neighbour(I64Vec2::new(3, 5)), None);
+        assert_eq!(walled.neighbour(I64Vec2::new(9, 4)), Some(I64Vec2::new(9, 4)));
+        assert_eq!(Torus::walled(I64Vec2::new(0, 5)), None);
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+use glam::I64Vec2;
+
+/// Fade lengths, in seconds, that the trail duration cycles through.
+pub const TRAIL_DURATIONS: [f64; 4] = [0.25, 0.5, 1.0, 2.0];
+
+/// How long a trail takes to fade unless `--trail-duration` says otherwise.
+pub const DEFAULT_TRAIL_DURATION: f64 = 0.5;
+
+/// Afterimages of cells that died recently, each fading out over `duration` seconds. Purely
+/// for drawing: nothing here feeds back into the board.
+#[derive(Debug, Clone)]
+pub struct Trails {
+    died_at: HashMap<I64Vec2, f64>,
+    duration: f64,
+}
+
+impl Trails {
+    pub fn new(duration: f64) -> Self {
+        Self { died_at: HashMap::new(), duration }
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.duration
+    }
+
+    pub fn set_duration(&mut self, duration: f64) {
+        self.duration = duration;
+    }
+
+    /// Starts a trail for each cell that died at `time`.
+    pub fn record(&mut self, died: impl IntoIterator<Item = I64Vec2>, time: f64) {
+        self.died_at.extend(died.into_iter().map(|cell| (cell, time)));
+    }
+
+    /// Forgets the trails that have faded out by `time` and those of cells alive again.
+    pub fn prune(&mut self, time: f64, is_alive: impl Fn(I64Vec2) -> bool) {
+        self.died_at.retain(|&cell, &mut died| time - died < self.duration && !is_alive(cell));
+    }
+
+    pub fn clear(&mut self) {
+        self.died_at.clear();
+    }
+
+    /// Each trail with its opacity at `time`, from 1 as the cell dies down to 0 once faded.
+    pub fn iter(&self, time: f64) -> impl Iterator<Item = (I64Vec2, f32)> + '_ {
+        self.died_at.iter().map(move |(&cell, &died)| {
+            let faded = if self.duration > 0.0 { (time - died) / self.duration } else { 1.0 };
+            (cell, (1.0 - faded).clamp(0.0, 1.0) as f32)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trails_fade_and_expire() {
+        let mut trails = Trails::new(1.0);
+        trails.record([I64Vec2::ZERO, I64Vec2::X], 10.0);
+        let opacity = |trails: &Trails, time| trails.iter(time).map(|(_, opacity)| opacity).collect::<Vec<_>>();
+        assert_eq!(opacity(&trails, 10.25), [0.75, 0.75]);
+
+        trails.prune(10.5, |cell| cell == I64Vec2::X);
+        assert_eq!(trails.iter(10.5).collect::<Vec<_>>(), [(I64Vec2::ZERO, 0.5)]);
+        trails.prune(11.0, |_| false);
+        assert_eq!(trails.iter(11.0).count(), 0);
+    }
+}
@@ -0,0 +1,164 @@
+use glam::I64Vec2;
+
+/// Rotates the cells a quarter turn clockwise (with y pointing down) within their bounding
+/// box, keeping its top-left corner in place.
+pub fn rotate_clockwise(cells: &[I64Vec2]) -> Vec<I64Vec2> {
+    remap(cells, |cell| I64Vec2::new(-cell.y, cell.x))
+}
+
+pub fn rotate_counterclockwise(cells: &[I64Vec2]) -> Vec<I64Vec2> {
+    remap(cells, |cell| I64Vec2::new(cell.y, -cell.x))
+}
+
+/// Rotates the cells a quarter turn about `pivot`, which stays where it is, rather than
+/// within their bounding box.
+pub fn rotate_about(cells: &[I64Vec2], pivot: I64Vec2, is_clockwise: bool) -> Vec<I64Vec2> {
+    let mut result: Vec<I64Vec2> = cells
+        .iter()
+        .map(|&cell| {
+            let offset = cell - pivot;
+            pivot + if is_clockwise { I64Vec2::new(-offset.y, offset.x) } else { I64Vec2::new(offset.y, -offset.x) }
+        })
+        .collect();
+    result.sort_by_key(|cell| (cell.y, cell.x));
+    result
+}
+
+/// Mirrors the cells left to right within their bounding box.
+pub fn flip_horizontal(cells: &[I64Vec2]) -> Vec<I64Vec2> {
+    remap(cells, |cell| I64Vec2::new(-cell.x, cell.y))
+}
+
+/// Mirrors the cells top to bottom within their bounding box.
+pub fn flip_vertical(cells: &[I64Vec2]) -> Vec<I64Vec2> {
+    remap(cells, |cell| I64Vec2::new(cell.x, -cell.y))
+}
+
+/// Grows every cell into a `factor`x`factor` block, keeping `anchor` in place. Scaling back
+/// down by the same factor gives the original cells.
+pub fn scale_up(cells: &[I64Vec2], anchor: I64Vec2, factor: i64) -> Vec<I64Vec2> {
+    let mut result: Vec<I64Vec2> = cells
+        .iter()
+        .flat_map(|&cell| {
+            let corner = anchor.saturating_add((cell - anchor).saturating_mul(I64Vec2::splat(factor)));
+            (0..factor).flat_map(move |y| (0..factor).map(move |x| corner + I64Vec2::new(x, y)))
+        })
+        .collect();
+    result.sort_by_key(|cell| (cell.y, cell.x));
+    result.dedup();
+    result
+}
+
+/// Shrinks every `factor`x`factor` block, counted from `anchor`, into one cell that is alive
+/// if any cell of the block was. Keeping any live cell rather than a majority means thin
+/// lines and sparse patterns don't vanish.
+pub fn scale_down(cells: &[I64Vec2], anchor: I64Vec2, factor: i64) -> Vec<I64Vec2> {
+    let mut result: Vec<I64Vec2> =
+        cells.iter().map(|&cell| anchor + (cell - anchor).div_euclid(I64Vec2::splat(factor))).collect();
+    result.sort_by_key(|cell| (cell.y, cell.x));
+    result.dedup();
+    result
+}
+
+/// Applies `f` and moves the result back onto the original top-left corner. Integer
+/// coordinates keep every transform exact, whatever the size of the region.
+fn remap(cells: &[I64Vec2], f: impl Fn(I64Vec2) -> I64Vec2) -> Vec<I64Vec2> {
+    let Some(min) = cells.iter().copied().reduce(I64Vec2::min) else {
+        return Vec::new();
+    };
+    let mapped: Vec<I64Vec2> = cells.iter().map(|&cell| f(cell)).collect();
+    let mapped_min = mapped.iter().copied().reduce(I64Vec2::min).unwrap();
+
+    let mut result: Vec<I64Vec2> = mapped.into_iter().map(|cell| cell - mapped_min + min).collect();
+    result.sort_by_key(|cell| (cell.y, cell.x));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(points: &[(i64, i64)]) -> Vec<I64Vec2> {
+        let mut cells: Vec<I64Vec2> = points.iter().map(|&(x, y)| I64Vec2::new(x, y)).collect();
+        cells.sort_by_key(|cell| (cell.y, cell.x));
+        cells
+    }
+
+    #[test]
+    fn rotates_an_l_shape_in_place() {
+        // ###
+        // #..
+        let l_shape = cells(&[(5, 5), (6, 5), (7, 5), (5, 6)]);
+        // ##
+        // .#
+        // .#
+        assert_eq!(rotate_clockwise(&l_shape), cells(&[(5, 5), (6, 5), (6, 6), (6, 7)]));
+        // ..#
+        // ###
+        assert_eq!(rotate_clockwise(&rotate_clockwise(&l_shape)), cells(&[(7, 5), (5, 6), (6, 6), (7, 6)]));
+        // #.
+        // #.
+        // ##
+        assert_eq!(rotate_counterclockwise(&l_shape), cells(&[(5, 5), (5, 6), (5, 7), (6, 7)]));
+    }
+
+    #[test]
+    fn rotates_about_a_pivot() {
+        let l_shape = cells(&[(5, 5), (6, 5), (7, 5), (5, 6)]);
+        let pivot = I64Vec2::new(2, 3);
+        // The corner three across and two down from the pivot ends up two across and three up.
+        let rotated = rotate_about(&l_shape, pivot, false);
+        assert_eq!(rotated, cells(&[(4, -2), (4, -1), (4, 0), (5, 0)]));
+        assert_eq!(rotate_about(&rotated, pivot, true), l_shape);
+        let mut turned = l_shape.clone();
+        for _ in 0..4 {
+            turned = rotate_about(&turned, pivot, true);
+        }
+        assert_eq!(turned, l_shape);
+    }
+
+    #[test]
+    fn transforms_are_lossless() {
+        let glider = cells(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        let shape = cells(&[(-3, 8), (0, 8), (-2, 9), (-3, 11)]);
+
+        for pattern in [glider, shape] {
+            let mut rotated = pattern.clone();
+            for _ in 0..4 {
+                rotated = rotate_clockwise(&rotated);
+            }
+            assert_eq!(rotated, pattern);
+            assert_eq!(rotate_counterclockwise(&rotate_clockwise(&pattern)), pattern);
+            assert_eq!(flip_horizontal(&flip_horizontal(&pattern)), pattern);
+            assert_eq!(flip_vertical(&flip_vertical(&pattern)), pattern);
+        }
+    }
+
+    #[test]
+    fn scaling_keeps_the_anchor_and_round_trips() {
+        let anchor = I64Vec2::new(-4, 2);
+        let glider = cells(&[(-3, 2), (-2, 3), (-4, 4), (-3, 4), (-2, 4)]);
+        let scaled = scale_up(&glider, anchor, 3);
+        assert_eq!(scaled.len(), 45);
+        assert_eq!(scaled.first(), Some(&I64Vec2::new(-1, 2)));
+        assert!(scaled.contains(&I64Vec2::new(-4, 8)) && !scaled.contains(&I64Vec2::new(-4, 7)));
+        assert_eq!(scale_down(&scaled, anchor, 3), glider);
+    }
+
+    #[test]
+    fn scaling_down_keeps_any_live_cell() {
+        // #...#.
+        // ......
+        let sparse = cells(&[(0, 0), (4, 0)]);
+        assert_eq!(scale_down(&sparse, I64Vec2::ZERO, 2), cells(&[(0, 0), (2, 0)]));
+        assert_eq!(scale_down(&cells(&[(-1, -1), (1, 1)]), I64Vec2::ZERO, 2), cells(&[(-1, -1), (0, 0)]));
+    }
+
+    #[test]
+    fn flips_mirror_within_bounds() {
+        let glider = cells(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        assert_eq!(flip_horizontal(&glider), cells(&[(1, 0), (0, 1), (0, 2), (1, 2), (2, 2)]));
+        assert_eq!(flip_vertical(&glider), cells(&[(0, 0), (1, 0), (2, 0), (2, 1), (1, 2)]));
+        assert!(rotate_clockwise(&[]).is_empty());
+    }
+}
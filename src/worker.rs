@@ -0,0 +1,90 @@
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::life::{Engine, Life};
+
+/// How many finished generations may wait to be drawn. The window shows one board while the
+/// worker fills the next, so a couple is enough to keep it busy.
+const LOOKAHEAD: usize = 2;
+
+/// Steps a copy of the board on a background thread and hands back each finished generation,
+/// so drawing never waits on a step and never sees a half-stepped board.
+///
+/// The worker doesn't share the caller's board, so edits never race with it: once the board
+/// has been edited the worker no longer [follows](Worker::follows) it and is simply replaced.
+pub struct Worker {
+    /// Each finished generation with how long its step took.
+    results: Receiver<(Life, Duration)>,
+    /// The revision and engine of the board last handed out, or the one the worker began from.
+    revision: u64,
+    engine: Engine,
+}
+
+impl Worker {
+    pub fn spawn(life: &Life) -> Self {
+        let (sender, results) = mpsc::sync_channel(LOOKAHEAD);
+        let mut board = life.without_caches();
+        thread::spawn(move || {
+            loop {
+                let started = Instant::now();
+                board.step();
+                let took = started.elapsed();
+                // Sending fails once the worker has been dropped, which tells the thread to stop.
+                if sender.send((board.without_caches(), took)).is_err() || board.is_stable() {
+                    break;
+                }
+            }
+        });
+        Self { results, revision: life.revision(), engine: life.engine() }
+    }
+
+    /// Whether the board is unchanged since the worker last handed out a generation, so the
+    /// generations still to come follow on from it.
+    pub fn follows(&self, life: &Life) -> bool {
+        life.revision() == self.revision && life.engine() == self.engine
+    }
+
+    /// The next generation and how long its step took, waiting up to `timeout` for it to finish.
+    pub fn next_within(&mut self, timeout: Duration) -> Option<(Life, Duration)> {
+        let next = if timeout.is_zero() {
+            self.results.try_recv().ok()?
+        } else {
+            self.results.recv_timeout(timeout).ok()?
+        };
+        self.revision = next.0.revision();
+        Some(next)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::I64Vec2;
+    use crate::patterns::glider;
+
+    #[test]
+    fn hands_back_each_generation_in_turn() {
+        let mut life = Life::from_cells(glider().into_iter().collect());
+        let mut worker = Worker::spawn(&life);
+        let mut expected = life.clone();
+        for _ in 0..8 {
+            assert!(worker.follows(&life));
+            life = worker.next_within(Duration::from_secs(10)).unwrap().0;
+            expected.step();
+            assert_eq!(life, expected);
+            assert_eq!(life.generation(), expected.generation());
+            assert!(life.live_cells().iter().all(|&cell| life.age(cell) == expected.age(cell)));
+        }
+
+        life.toggle(I64Vec2::ZERO);
+        assert!(!worker.follows(&life));
+    }
+
+    #[test]
+    fn stops_once_the_board_is_still() {
+        let block = [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(x, y)| I64Vec2::new(x, y));
+        let mut worker = Worker::spawn(&Life::from_cells(block.into_iter().collect()));
+        assert!(worker.next_within(Duration::from_secs(10)).unwrap().0.is_stable());
+        assert!(worker.next_within(Duration::from_millis(100)).is_none());
+    }
+}